@@ -5,7 +5,9 @@ import globals from 'globals'
 import tsEslint from 'typescript-eslint'
 
 const config = tsEslint.config(
-  { ignores: ['node_modules', '**/dist/**', '**/build/**', 'eslint.config.js'] },
+  {
+    ignores: ['node_modules', '**/dist/**', '**/build/**', 'eslint.config.js', '**/*.generated.ts']
+  },
   {
     extends: [js.configs.recommended, ...tsEslint.configs.recommended],
     files: ['**/*.{ts,js}'],
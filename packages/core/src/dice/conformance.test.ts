@@ -0,0 +1,90 @@
+import assert from 'node:assert/strict'
+import { readFileSync } from 'node:fs'
+import { describe, it } from 'node:test'
+import { ConformanceFixtures } from '../types/conformance.js'
+import { applyTenDiceRule, calculateRaises, executeRoll, setSeed } from './dice.js'
+
+/**
+ * Runs the conformance scenarios shared with the Rust probability calculator
+ * (tools/probability-calculator, `cargo run --release -- conformance`)
+ */
+const fixtures: ConformanceFixtures = JSON.parse(
+  readFileSync(new URL('../../data/conformance-fixtures.json', import.meta.url), 'utf8')
+)
+
+/** Rolls per scenario */
+const ROUNDS = 20_000
+
+/** Standard errors a simulated mean or probability may drift */
+const MAX_SIGMAS = 5
+
+/** Kolmogorov-Smirnov critical value at alpha = 1e-6 */
+const KS_CRITICAL = Math.sqrt(-Math.log(1e-6 / 2) / (2 * ROUNDS))
+
+describe('conformance scenarios', () => {
+  for (const scenario of fixtures.scenarios) {
+    describe(scenario.name, () => {
+      it('applies the Ten Dice Rule', () => {
+        const { roll, keep, bonus } = applyTenDiceRule(
+          scenario.expression.roll,
+          scenario.expression.keep
+        )
+        assert.deepEqual({ roll, keep, bonus }, scenario.ten_dice)
+      })
+
+      it('rolls the exact distribution', () => {
+        setSeed(`conformance ${scenario.name}`)
+        const totals: number[] = []
+        let successes = 0
+        for (let i = 0; i < ROUNDS; i++) {
+          const result = executeRoll(scenario.expression, scenario.options)
+          totals.push(result.total)
+          if (result.success) successes++
+        }
+
+        const mean = totals.reduce((sum, total) => sum + total, 0) / ROUNDS
+        const meanError = scenario.statistics.stddev / Math.sqrt(ROUNDS)
+        assert.ok(
+          Math.abs(mean - scenario.statistics.mean) <= MAX_SIGMAS * meanError,
+          `mean ${mean} vs exact ${scenario.statistics.mean}`
+        )
+
+        if (scenario.success_probability !== undefined) {
+          const p = scenario.success_probability
+          const rate = successes / ROUNDS
+          assert.ok(
+            Math.abs(rate - p) <= MAX_SIGMAS * Math.sqrt((p * (1 - p)) / ROUNDS),
+            `success rate ${rate} vs exact ${p}`
+          )
+        }
+
+        // Largest gap between the empirical and exact CDFs
+        totals.sort((a, b) => a - b)
+        const values = Object.keys(scenario.distribution)
+          .map(Number)
+          .sort((a, b) => a - b)
+        let index = 0
+        let exact = 0
+        let gap = 0
+        for (const value of values) {
+          while (index < ROUNDS && totals[index] <= value) index++
+          exact += scenario.distribution[value]
+          gap = Math.max(gap, Math.abs(index / ROUNDS - exact))
+        }
+        assert.equal(index, ROUNDS, `total ${totals[index]} has no exact probability`)
+        assert.ok(gap <= KS_CRITICAL, `KS statistic ${gap} above ${KS_CRITICAL}`)
+      })
+    })
+  }
+})
+
+describe('conformance raises', () => {
+  for (const raise of fixtures.raises) {
+    it(`${raise.total} vs TN ${raise.targetNumber}, ${raise.calledRaises} called`, () => {
+      assert.deepEqual(
+        calculateRaises(raise.total, raise.targetNumber, raise.calledRaises),
+        raise.expected
+      )
+    })
+  }
+})
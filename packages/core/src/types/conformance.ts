@@ -0,0 +1,50 @@
+import { RollExpression, RollOptions } from './dice.js'
+import { GoodnessOfFit, Statistics } from './probability.js'
+
+/**
+ * Fixtures shared with the Rust probability calculator
+ * (tools/probability-calculator, `cargo run -- conformance`)
+ */
+export interface ConformanceFixtures {
+  version: string
+  generated_at: string
+  scenarios: ConformanceScenario[]
+  raises: RaiseCase[]
+}
+
+/**
+ * A roll with its exact distribution; `expression` and `options` can be
+ * passed straight to executeRoll
+ */
+export interface ConformanceScenario {
+  name: string
+  expression: RollExpression
+  options: RollOptions
+  /** Expected applyTenDiceRule conversion */
+  ten_dice: { roll: number; keep: number; bonus: number }
+  statistics: Statistics
+  /** P(total >= TN + 5 × called raises), when a TN is given */
+  success_probability?: number
+  /** Mean achieved raises, when a TN is given */
+  expected_raises?: number
+  /** Exact probability of each total, including the modifier */
+  distribution: Record<string, number>
+  /** Fit of the Rust Monte Carlo roller to the distribution */
+  goodness_of_fit?: GoodnessOfFit
+}
+
+/**
+ * A calculateRaises call and its expected result
+ */
+export interface RaiseCase {
+  total: number
+  targetNumber: number
+  calledRaises: number
+  expected: {
+    effectiveTN: number
+    success: boolean
+    calledRaises: number
+    achievedRaises: number
+    marginOfSuccess: number
+  }
+}
@@ -1,2 +1,4 @@
 export * from './dice.js'
 export * from './probability.js'
+export * from './conformance.js'
+export * from './luck.js'
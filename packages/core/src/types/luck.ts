@@ -0,0 +1,49 @@
+import { ExplosionMode, RollExpression, RollOptions } from './dice.js'
+
+/**
+ * Luck scores of a roll history
+ * (tools/probability-calculator, `cargo run -- luck`)
+ */
+export interface LuckReport {
+  generated_at: string
+  /** Rolls scored, after filtering */
+  rolls: number
+  filter: { mode?: ExplosionMode; emphasis?: boolean }
+  /** Luckiest player first */
+  players: PlayerLuck[]
+  luckiest: RollLuck[]
+  unluckiest: RollLuck[]
+}
+
+/**
+ * Mean percentile of a set of rolls (50 is average), with 95% bounds
+ */
+export interface LuckIndex {
+  rolls: number
+  index: number
+  lower: number
+  upper: number
+}
+
+export interface PlayerLuck {
+  player: string
+  luck: LuckIndex
+  /** In order of first roll */
+  sessions: { session: string; luck: LuckIndex }[]
+}
+
+/**
+ * One scored roll
+ */
+export interface RollLuck {
+  player: string
+  session?: string
+  timestamp?: string
+  /** Configuration the roll runs as, e.g. "10k5 skilled, +2" */
+  label: string
+  expression: RollExpression
+  options: RollOptions
+  total: number
+  /** Mid-P percentile of the total (0-100) */
+  percentile: number
+}
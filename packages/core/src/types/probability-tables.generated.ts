@@ -0,0 +1,161 @@
+// Generated by tools/probability-calculator (`cargo run -- schema`) from the Rust
+// output types. Do not edit by hand.
+
+/** Root structure for the JSON output */
+export interface ProbabilityTables {
+  version: string
+  generated_at: string
+  /** Engine used to compute the distributions */
+  engine?: Engine
+  /** Rule set (edition) the tables were generated for */
+  rule_set?: RuleSet
+  /** Conversion to apply to pools above 10 dice before looking them up */
+  ten_dice_rule?: TenDiceRule
+  simulation_rounds: SimulationRounds
+  probability_cutoff: number
+  tables: ProbabilityTable[]
+}
+
+/**
+ * Schema v2 of the probability tables
+ *
+ * Unlike v1, each table's P(total ≥ TN) is a dense array starting at
+ * `min_tn`, values are rounded to `precision` decimals, and the file
+ * records the schema and generator versions.
+ */
+export interface ProbabilityTablesV2 {
+  schema_version: number
+  generator: string
+  generator_version: string
+  generated_at: string
+  engine: Engine
+  rule_set: RuleSet
+  ten_dice_rule: TenDiceRule
+  simulation_rounds: SimulationRounds
+  probability_cutoff: number
+  /** Decimals kept for probabilities, means and standard deviations */
+  precision: number
+  tables: ProbabilityTableV2[]
+}
+
+/** How distributions are computed */
+export type Engine = 'simulation' | 'exact'
+
+/**
+ * Explosion modes for L5R dice rolling
+ *
+ * These are the three standard presets. Each one maps to an [`ExplosionRule`],
+ * which is what the roller actually uses.
+ */
+export type ExplosionMode = 'unskilled' | 'skilled' | 'mastery'
+
+/**
+ * Parameterised explosion rule
+ *
+ * A die explodes when its face is at or above `threshold`: it is rolled again
+ * and the new face is added to its value. `max_chain` caps how many extra
+ * rolls a single die may add, which covers house rules such as "explode only
+ * once". Techniques that make 9s explode only on damage are a threshold-9
+ * rule used for the damage roll tables.
+ */
+export interface ExplosionRule {
+  /** Lowest face that explodes (None = dice never explode) */
+  threshold?: number | null
+  /** Maximum number of extra rolls per die (None = unlimited) */
+  max_chain?: number | null
+}
+
+/** Goodness of fit of a simulated histogram to the exact distribution */
+export interface GoodnessOfFit {
+  chi_square: number
+  degrees_of_freedom: number
+  chi_square_p_value: number
+  /** Largest difference between the empirical and exact CDFs */
+  ks_statistic: number
+  ks_p_value: number
+}
+
+/** Which dice a keep stage selects */
+export type KeepWhich = 'highest' | 'lowest'
+
+/** A single probability table entry */
+export interface ProbabilityTable {
+  roll: number
+  keep: number
+  /** Preset name; absent when the table uses a custom explosion rule */
+  explosion_mode?: ExplosionMode | null
+  /** Explosion rule; absent in tables written before custom rules, which use the preset's */
+  explosion?: ExplosionRule | null
+  emphasis: boolean
+  /** Extra roll pipeline stages (emphasis excluded); absent for standard rolls */
+  pipeline?: Stage[]
+  statistics: Statistics
+  cumulative_probability: Record<string, number>
+  /** Fit of the simulated histogram to the exact distribution; absent for exact tables */
+  goodness_of_fit?: GoodnessOfFit | null
+}
+
+/** A single v2 table */
+export interface ProbabilityTableV2 {
+  roll: number
+  keep: number
+  explosion_mode?: ExplosionMode | null
+  explosion: ExplosionRule
+  emphasis: boolean
+  pipeline?: Stage[]
+  statistics: Statistics
+  /** TN of the first entry of `at_least` */
+  min_tn: number
+  /** P(total ≥ min_tn + i) for each i */
+  at_least: number[]
+  goodness_of_fit?: GoodnessOfFit | null
+}
+
+/**
+ * Roll & Keep rule set (edition) used for a generation run
+ *
+ * A rule set decides which explosion presets exist, whether emphasis
+ * applies, and how pools above 10 dice are converted. Tables only cover
+ * pools up to 10k10, so the conversion is recorded in the table metadata for
+ * consumers to apply before looking a pool up.
+ */
+export type RuleSet = '4e' | 'legacy' | '7th-sea'
+
+/** Simulation round counts per explosion mode */
+export interface SimulationRounds {
+  unskilled: number
+  skilled: number
+  mastery: number
+  /** Rounds used for a non-preset explosion rule, if the run had one */
+  custom?: number | null
+}
+
+/**
+ * A single roll-modifying stage
+ *
+ * Stages run after the dice are rolled with the config's explosion rule.
+ * Dice rerolled by a stage are fresh exploding dice: earlier die stages are
+ * not applied to them again, matching how emphasis works in dice.ts.
+ */
+export type Stage = { at_most: number; stage: 'reroll' } | { from: number; to: number; stage: 'remap' } | { count: number; stage: 'reroll_lowest' } | { which: KeepWhich; stage: 'keep' } | { bonus: number; stage: 'kept_die_bonus' } | { bonus: number; stage: 'modifier' }
+
+/** Statistical measures for a distribution */
+export interface Statistics {
+  mean: number
+  stddev: number
+  median: number
+  percentile_25: number
+  percentile_75: number
+  min: number
+  max: number
+}
+
+/** How pools above 10 rolled or kept dice are converted */
+export interface TenDiceRule {
+  /** Excess rolled dice needed for one extra kept die */
+  rolled_per_kept: number
+  /** Bonus for excess rolled dice left over after conversion */
+  leftover_bonus: number
+  /** Bonus for each kept die above 10 */
+  bonus_per_kept: number
+}
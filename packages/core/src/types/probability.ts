@@ -1,57 +1,67 @@
+// Table types are generated from the Rust output types; regenerate with
+// `cargo run -- schema --typescript ../../packages/core/src/types/probability-tables.generated.ts`
+// in tools/probability-calculator
+export type {
+  Engine,
+  ExplosionRule,
+  GoodnessOfFit,
+  ProbabilityTable,
+  ProbabilityTables,
+  ProbabilityTablesV2,
+  ProbabilityTableV2,
+  RuleSet,
+  SimulationRounds,
+  Stage as PipelineStage,
+  Statistics,
+  TenDiceRule
+} from './probability-tables.generated.js'
+
 /**
- * Represents a single probability table entry for a specific roll configuration
+ * Symbols on one face of an L5R 5e ring (d6) or skill (d12) die
  */
-export interface ProbabilityTable {
-  /** Number of dice to roll */
-  roll: number
-  /** Number of dice to keep */
-  keep: number
-  /** Explosion mode used for this table */
-  explosion_mode: 'unskilled' | 'skilled' | 'mastery'
-  /** Whether emphasis was applied */
-  emphasis: boolean
-  /** Statistical measures for this roll configuration */
-  statistics: Statistics
-  /** Cumulative probability P(total >= TN) for each TN value */
-  cumulative_probability: Record<string, number>
+export interface NarrativeFace {
+  /** Successes (an explosive success counts as one) */
+  success: number
+  /** Explosive success: keeping it rolls another die of the same type */
+  explosive: boolean
+  opportunity: number
+  strife: number
 }
 
 /**
- * Statistical measures for a probability distribution
+ * L5R 5e probability table for one Ring / Skill / keep policy combination
  */
-export interface Statistics {
-  /** Mean (average) value */
-  mean: number
-  /** Standard deviation */
-  stddev: number
-  /** Median (50th percentile) */
-  median: number
-  /** 25th percentile */
-  percentile_25: number
-  /** 75th percentile */
-  percentile_75: number
-  /** Minimum possible value */
-  min: number
-  /** Maximum observed value */
-  max: number
+export interface NarrativeTable {
+  /** Ring rank: ring dice rolled and maximum dice kept */
+  ring: number
+  /** Skill rank: skill dice rolled */
+  skill: number
+  /** How kept dice are chosen */
+  policy: 'max_successes' | 'avoid_strife' | 'max_opportunity'
+  expected_successes: number
+  expected_opportunity: number
+  expected_strife: number
+  /** P(successes >= N) for each N */
+  success_at_least: Record<string, number>
 }
 
 /**
- * Root structure of the probability tables JSON file
+ * Root structure of the narrative (5e) tables JSON file
  */
-export interface ProbabilityTables {
+export interface NarrativeTables {
   /** Version of the table format */
   version: string
   /** Timestamp when tables were generated */
   generated_at: string
-  /** Number of simulation rounds per mode */
-  simulation_rounds: {
-    unskilled: number
-    skilled: number
-    mastery: number
+  /** Dice system, always 'l5r5e' */
+  dice_system: 'l5r5e'
+  /** Number of simulation rounds per configuration */
+  simulation_rounds: number
+  /** Die face definitions used */
+  faces: {
+    ring: NarrativeFace[]
+    skill: NarrativeFace[]
   }
-  /** Probability cutoff threshold used */
-  probability_cutoff: number
-  /** Array of all probability tables */
-  tables: ProbabilityTable[]
+  /** Array of all narrative tables */
+  tables: NarrativeTable[]
 }
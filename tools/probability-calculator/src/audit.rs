@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ExplosionRule, RollConfig};
+use crate::dice::{RollExpression, RollOptions};
+use crate::error::{Error, Result};
+use crate::exact::exact_distribution;
+use crate::fit::{benjamini_hochberg, runs_test, GoodnessOfFit};
+use crate::pipeline::Stage;
+use crate::stats::calculate_statistics_from_pmf;
+
+/// Fewest rolls in a group for its tests to be run
+pub const MIN_GROUP_ROLLS: usize = 30;
+
+/// Default false discovery rate across all tests of an audit
+pub const DEFAULT_FDR: f64 = 0.05;
+
+/// A bot roll from the log
+///
+/// Lines are `RollResult`s as logged by the bot; only the fields needed to
+/// recover the distribution and the total are read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedRoll {
+    pub expression: RollExpression,
+    pub options: RollOptions,
+    pub total: i32,
+}
+
+/// Read a JSONL roll log, skipping blank lines
+///
+/// Each line is parsed as `T`, e.g. [`LoggedRoll`] or a luck history entry.
+pub fn read_roll_log<T: DeserializeOwned, R: BufRead>(log: R) -> Result<Vec<T>> {
+    let mut rolls = Vec::new();
+    for (index, line) in log.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let roll = serde_json::from_str(&line)
+            .map_err(|e| Error::InvalidData(format!("line {}: {}", index + 1, e)))?;
+        rolls.push(roll);
+    }
+    Ok(rolls)
+}
+
+/// Fairness audit of a roll log
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub generated_at: String,
+    pub rolls: usize,
+    /// False discovery rate the flags are controlled at
+    pub fdr: f64,
+    /// Tests flagged after the Benjamini-Hochberg correction
+    pub flagged: usize,
+    pub groups: Vec<GroupAudit>,
+}
+
+/// Rolls sharing one distribution, and their tests
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupAudit {
+    /// e.g. "10k5 skilled +emphasis, +2"
+    pub label: String,
+    pub roll: u8,
+    pub keep: u8,
+    pub explosion: ExplosionRule,
+    pub emphasis: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipeline: Vec<Stage>,
+    pub rolls: usize,
+    pub observed_mean: f64,
+    pub expected_mean: f64,
+    /// Empty when the group has fewer than [`MIN_GROUP_ROLLS`] rolls
+    pub tests: Vec<AuditTest>,
+}
+
+/// One statistical test of a group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditTest {
+    /// "chi_square", "kolmogorov_smirnov" or "runs"
+    pub test: String,
+    pub statistic: f64,
+    pub p_value: f64,
+    /// Benjamini-Hochberg adjusted p-value over every test in the audit
+    pub adjusted_p_value: f64,
+    /// Significant at the audit's false discovery rate
+    pub flagged: bool,
+}
+
+impl GroupAudit {
+    /// Whether any test of this group was flagged
+    pub fn is_flagged(&self) -> bool {
+        self.tests.iter().any(|t| t.flagged)
+    }
+}
+
+/// Label for a configuration, e.g. "6k3 mastery +emphasis, reroll≤2"
+pub(crate) fn config_label(config: &RollConfig) -> String {
+    let mut label = format!("{}k{} {}", config.roll, config.keep, config.explosion);
+    if config.emphasis {
+        label.push_str(" +emphasis");
+    }
+    if !config.pipeline.stages.is_empty() {
+        label.push_str(&format!(", {}", config.pipeline.describe()));
+    }
+    label
+}
+
+/// Test the totals of each configuration against its exact distribution
+///
+/// Rolls are grouped by the configuration they run as (so 12k4 and 10k5 share
+/// a group), keeping log order within each group. Each group with enough
+/// rolls gets chi-square and Kolmogorov-Smirnov tests of its totals and a
+/// runs test above/below the expected median for streaks. p-values are then
+/// corrected for multiple comparisons with Benjamini-Hochberg at `fdr`.
+pub fn audit_rolls(rolls: &[LoggedRoll], fdr: f64) -> Result<AuditReport> {
+    // Step 1: Group totals by configuration, in order of first appearance
+    let mut groups: Vec<(RollConfig, Vec<i32>)> = Vec::new();
+    let mut index: HashMap<RollConfig, usize> = HashMap::new();
+    for (i, roll) in rolls.iter().enumerate() {
+        let config = roll.expression.config(&roll.options)
+            .map_err(|e| Error::InvalidData(format!("roll {}: {}", i + 1, e)))?;
+        let slot = *index.entry(config.clone()).or_insert_with(|| {
+            groups.push((config, Vec::new()));
+            groups.len() - 1
+        });
+        groups[slot].1.push(roll.total);
+    }
+
+    // Step 2: Run the tests on every group large enough
+    let mut audits = Vec::with_capacity(groups.len());
+    for (config, totals) in &groups {
+        let pmf = exact_distribution(config);
+        let statistics = calculate_statistics_from_pmf(&pmf);
+        let n = totals.len();
+
+        let mut tests = Vec::new();
+        if n >= MIN_GROUP_ROLLS {
+            let mut histogram: HashMap<i32, usize> = HashMap::new();
+            for &total in totals {
+                *histogram.entry(total).or_insert(0) += 1;
+            }
+            let fit = GoodnessOfFit::compare(&histogram, n, &pmf);
+            tests.push(("chi_square", fit.chi_square, fit.chi_square_p_value));
+            tests.push(("kolmogorov_smirnov", fit.ks_statistic, fit.ks_p_value));
+            if let Some((z, p_value)) = runs_test(totals, statistics.median) {
+                tests.push(("runs", z, p_value));
+            }
+        }
+
+        audits.push(GroupAudit {
+            label: config_label(config),
+            roll: config.roll,
+            keep: config.keep,
+            explosion: config.explosion,
+            emphasis: config.emphasis,
+            pipeline: config.pipeline.stages.clone(),
+            rolls: n,
+            observed_mean: totals.iter().map(|&t| t as f64).sum::<f64>() / n as f64,
+            expected_mean: statistics.mean,
+            tests: tests.into_iter()
+                .map(|(test, statistic, p_value)| AuditTest {
+                    test: test.to_string(),
+                    statistic,
+                    p_value,
+                    adjusted_p_value: p_value,
+                    flagged: false,
+                })
+                .collect(),
+        });
+    }
+
+    // Step 3: Correct for multiple comparisons across all tests
+    let p_values: Vec<f64> = audits.iter().flat_map(|g| g.tests.iter().map(|t| t.p_value)).collect();
+    let mut adjusted = benjamini_hochberg(&p_values).into_iter();
+    let mut flagged = 0;
+    for test in audits.iter_mut().flat_map(|g| g.tests.iter_mut()) {
+        test.adjusted_p_value = adjusted.next().unwrap_or(1.0);
+        test.flagged = test.adjusted_p_value < fdr;
+        flagged += test.flagged as usize;
+    }
+
+    // Largest groups first
+    audits.sort_by_key(|g| std::cmp::Reverse(g.rolls));
+
+    Ok(AuditReport {
+        generated_at: Utc::now().to_rfc3339(),
+        rolls: rolls.len(),
+        fdr,
+        flagged,
+        groups: audits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExplosionMode;
+    use crate::dice::execute_roll;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    /// Fair rolls from the traced roller, as the bot would log them
+    fn fair_log(rng: &mut StdRng, expression: RollExpression, options: RollOptions, count: usize) -> Vec<LoggedRoll> {
+        (0..count)
+            .map(|_| {
+                let result = execute_roll(expression, options, rng);
+                serde_json::from_value(serde_json::to_value(result).unwrap()).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fair_rolls_pass() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut log = fair_log(&mut rng, RollExpression { roll: 5, keep: 3, modifier: 0 }, RollOptions::plain(ExplosionMode::Skilled, None), 400);
+        log.extend(fair_log(&mut rng, RollExpression { roll: 12, keep: 4, modifier: 2 }, RollOptions::plain(ExplosionMode::Mastery, Some(1)), 300));
+        // Too few to test
+        log.extend(fair_log(&mut rng, RollExpression { roll: 2, keep: 1, modifier: 0 }, RollOptions::plain(ExplosionMode::Unskilled, None), 5));
+
+        let report = audit_rolls(&log, DEFAULT_FDR).unwrap();
+        assert_eq!(report.rolls, 705);
+        assert_eq!(report.flagged, 0, "{:#?}", report.groups);
+        assert_eq!(report.groups.len(), 3);
+        assert_eq!(report.groups[0].tests.len(), 3);
+        assert!(report.groups[2].tests.is_empty());
+        // 12k4+2 runs as 10k5+2
+        assert_eq!(report.groups[1].label, "10k5 mastery +emphasis, +2");
+    }
+
+    #[test]
+    fn test_cursed_rolls_are_flagged() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let expression = RollExpression { roll: 5, keep: 3, modifier: 0 };
+
+        // Logged as skilled, but rolled without explosions
+        let mut cursed = fair_log(&mut rng, expression, RollOptions::plain(ExplosionMode::Unskilled, None), 400);
+        for roll in &mut cursed {
+            roll.options.explosion_mode = ExplosionMode::Skilled;
+        }
+        let report = audit_rolls(&cursed, DEFAULT_FDR).unwrap();
+        assert!(report.groups[0].tests.iter().any(|t| t.test == "chi_square" && t.flagged));
+
+        // Fair totals, but in a streaky order
+        let mut streaky = fair_log(&mut rng, expression, RollOptions::plain(ExplosionMode::Skilled, None), 400);
+        streaky.sort_by_key(|r| r.total);
+        let report = audit_rolls(&streaky, DEFAULT_FDR).unwrap();
+        let runs = report.groups[0].tests.iter().find(|t| t.test == "runs").unwrap();
+        assert!(runs.flagged && runs.statistic < 0.0);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::config::ExplosionMode;
+use crate::config::ExplosionMode::{Mastery, Skilled, Unskilled};
+use crate::error::ValidationError;
+use crate::output::ProbabilityTable;
+
+use Reference::{AtLeast, Mean};
+
+/// Z-score for simulation tolerances
+///
+/// A correct table fails a single check with probability ~6e-7, so a full
+/// run of every benchmark essentially never raises a false alarm.
+pub const BENCHMARK_Z: f64 = 5.0;
+
+/// Tolerance for exact-engine tables (explosion tails are truncated at 1e-12)
+pub const EXACT_TOLERANCE: f64 = 1e-6;
+
+/// A known value of a distribution
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reference {
+    Mean(f64),
+    /// P(total ≥ tn)
+    AtLeast { tn: i32, probability: f64 },
+}
+
+/// A reference value for one standard configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Benchmark {
+    pub name: &'static str,
+    pub roll: u8,
+    pub keep: u8,
+    pub mode: ExplosionMode,
+    pub emphasis: bool,
+    pub reference: Reference,
+}
+
+const fn benchmark(name: &'static str, roll: u8, keep: u8, mode: ExplosionMode, emphasis: bool, reference: Reference) -> Benchmark {
+    Benchmark { name, roll, keep, mode, emphasis, reference }
+}
+
+/// Reference suite
+///
+/// Single-die and small-pool values are analytic; the larger pools are exact
+/// engine results, which agree with the published simulated tables.
+pub const BENCHMARKS: &[Benchmark] = &[
+    // Single die: 5.5, a 10 adds another die (10/9 × 5.5), 9s and 10s (10/8 × 5.5)
+    benchmark("1k1 unskilled mean", 1, 1, Unskilled, false, Mean(5.5)),
+    benchmark("1k1 unskilled P(≥6)", 1, 1, Unskilled, false, AtLeast { tn: 6, probability: 0.5 }),
+    benchmark("1k1 skilled mean", 1, 1, Skilled, false, Mean(55.0 / 9.0)),
+    benchmark("1k1 skilled P(≥20)", 1, 1, Skilled, false, AtLeast { tn: 20, probability: 0.01 }),
+    benchmark("1k1 mastery mean", 1, 1, Mastery, false, Mean(6.875)),
+    benchmark("1k1 mastery P(≥9)", 1, 1, Mastery, false, AtLeast { tn: 9, probability: 0.2 }),
+    // Emphasis: a 1 (p = 0.1) becomes a fresh die
+    benchmark("1k1 unskilled emphasis mean", 1, 1, Unskilled, true, Mean(5.95)),
+    benchmark("1k1 unskilled emphasis P(≥2)", 1, 1, Unskilled, true, AtLeast { tn: 2, probability: 0.99 }),
+    // Highest of two: P(≥v) = 1 - ((v - 1) / 10)²
+    benchmark("2k1 unskilled mean", 2, 1, Unskilled, false, Mean(7.15)),
+    benchmark("2k1 unskilled P(≥10)", 2, 1, Unskilled, false, AtLeast { tn: 10, probability: 0.19 }),
+    benchmark("2k2 skilled mean", 2, 2, Skilled, false, Mean(110.0 / 9.0)),
+    benchmark("10k10 unskilled mean", 10, 10, Unskilled, false, Mean(55.0)),
+    // Exact engine
+    benchmark("5k3 skilled mean", 5, 3, Skilled, false, Mean(24.5126050264)),
+    benchmark("5k3 skilled P(≥25)", 5, 3, Skilled, false, AtLeast { tn: 25, probability: 0.4166970000 }),
+    benchmark("6k2 skilled emphasis mean", 6, 2, Skilled, true, Mean(21.0119049382)),
+    benchmark("10k5 mastery mean", 10, 5, Mastery, false, Mean(52.5119571726)),
+];
+
+impl Benchmark {
+    /// Whether this benchmark describes a table (standard rolls only)
+    pub fn matches(&self, table: &ProbabilityTable) -> bool {
+        table.roll == self.roll
+            && table.keep == self.keep
+            && table.explosion == self.mode.rule()
+            && table.emphasis == self.emphasis
+            && table.pipeline.is_empty()
+    }
+
+    /// Check a table, given its simulation round count (None for exact tables)
+    pub fn check(&self, table: &ProbabilityTable, rounds: Option<usize>) -> Option<ValidationError> {
+        let (expected, actual, standard_error) = match self.reference {
+            Mean(mean) => (mean, table.statistics.mean, table.statistics.stddev),
+            AtLeast { tn, probability } => {
+                (probability, table.probability_at_least(tn), (probability * (1.0 - probability)).sqrt())
+            }
+        };
+
+        // Simulated estimates have a standard error of σ/√n
+        let tolerance = match rounds {
+            Some(n) => BENCHMARK_Z * standard_error / (n as f64).sqrt(),
+            None => EXACT_TOLERANCE,
+        };
+
+        ((actual - expected).abs() > tolerance).then(|| ValidationError::BenchmarkDrift {
+            benchmark: self.name.to_string(),
+            expected,
+            actual,
+            tolerance,
+        })
+    }
+}
+
+/// Check a table against every benchmark that applies to it
+pub fn benchmark_errors(table: &ProbabilityTable, rounds: Option<usize>) -> Vec<ValidationError> {
+    BENCHMARKS.iter()
+        .filter(|b| b.matches(table))
+        .filter_map(|b| b.check(table, rounds))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Engine, RollConfig};
+    use crate::check_table;
+
+    fn config(b: &Benchmark) -> RollConfig {
+        RollConfig::new(b.roll, b.keep, b.mode, b.emphasis).unwrap()
+    }
+
+    #[test]
+    fn test_exact_engine_meets_benchmarks() {
+        for b in BENCHMARKS {
+            let (table, errors) = check_table(&config(b), Engine::Exact);
+            assert!(b.matches(&table));
+            assert!(errors.is_empty(), "{}: {:?}", b.name, errors);
+        }
+    }
+
+    #[test]
+    fn test_simulation_meets_benchmarks() {
+        for b in BENCHMARKS.iter().filter(|b| b.roll <= 2) {
+            let (_, errors) = check_table(&config(b), Engine::Simulation);
+            assert!(errors.is_empty(), "{}: {:?}", b.name, errors);
+        }
+    }
+
+    #[test]
+    fn test_drift_is_detected() {
+        let b = &BENCHMARKS[2]; // 1k1 skilled mean
+        let (mut table, _) = check_table(&config(b), Engine::Exact);
+
+        // Off by 0.05: within simulation noise at 1,000 rounds, drift at 300,000
+        table.statistics.mean += 0.05;
+        assert!(b.check(&table, Some(1_000)).is_none());
+        assert!(matches!(
+            b.check(&table, Some(300_000)),
+            Some(ValidationError::BenchmarkDrift { .. })
+        ));
+        assert_eq!(benchmark_errors(&table, None).len(), 1);
+    }
+}
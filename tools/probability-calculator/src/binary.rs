@@ -0,0 +1,517 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Engine, ExplosionRule};
+use crate::error::{Error, Result, ValidationError};
+use crate::fit::GoodnessOfFit;
+use crate::output::SimulationRounds;
+use crate::pipeline::Stage;
+use crate::rules::{RuleSet, TenDiceRule};
+use crate::schema::ProbabilityTablesV2;
+use crate::stats::Statistics;
+
+/// Compact binary encoding of `ProbabilityTablesV2` (probability-tables.bin)
+///
+/// All integers are little-endian. The file is:
+///
+/// | Section       | Contents                                             |
+/// |---------------|------------------------------------------------------|
+/// | header        | [`HEADER_SIZE`] bytes, see below                     |
+/// | records       | one [`RECORD_SIZE`]-byte record per table            |
+/// | probabilities | u32 quantized P(total ≥ TN), dense per table         |
+/// | metadata      | JSON: generator, rule set, pipelines, goodness of fit |
+///
+/// Header: magic `L5RB`, format version (u16), record size (u16), table
+/// count, then offset and length of the probabilities (in entries) and of
+/// the metadata (in bytes), all u32.
+///
+/// Record: roll, keep, explosion threshold (0 = none), max chain (0xFF =
+/// none), flags (bit 0: emphasis, bit 1: has a pipeline), 3 reserved bytes, mean and stddev (f64),
+/// median, 25th and 75th percentiles, min, max (i32), first TN (i32), then
+/// index and length of the table's run in the probabilities (u32).
+///
+/// Each run is the table's `at_least` array, one entry per TN from `min_tn`.
+pub const MAGIC: &[u8; 4] = b"L5RB";
+
+/// Binary format version
+pub const FORMAT_VERSION: u16 = 2;
+
+pub const HEADER_SIZE: usize = 32;
+pub const RECORD_SIZE: usize = 64;
+
+/// Largest error of a quantized probability (half a step of 1 / u32::MAX)
+pub const QUANTIZATION_ERROR: f64 = 0.5 / u32::MAX as f64;
+
+/// Everything in `ProbabilityTablesV2` that is not in the records
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BinaryMetadata {
+    pub generator_version: String,
+    pub generated_at: String,
+    pub engine: Engine,
+    pub rule_set: RuleSet,
+    pub ten_dice_rule: TenDiceRule,
+    pub simulation_rounds: SimulationRounds,
+    pub probability_cutoff: f64,
+    pub precision: u32,
+    /// Per-table extras, in record order
+    pub tables: Vec<TableExtras>,
+}
+
+/// Per-table fields that are rarely present
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TableExtras {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipeline: Vec<Stage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goodness_of_fit: Option<GoodnessOfFit>,
+}
+
+fn quantize(p: f64) -> u32 {
+    (p.clamp(0.0, 1.0) * u32::MAX as f64).round() as u32
+}
+
+fn dequantize(q: u32) -> f64 {
+    q as f64 / u32::MAX as f64
+}
+
+/// Encode tables in the binary format
+pub fn encode_tables(tables: &ProbabilityTablesV2) -> Result<Vec<u8>> {
+    let count = tables.tables.len();
+    let mut records = Vec::with_capacity(count * RECORD_SIZE);
+    let mut probabilities: Vec<u32> = Vec::new();
+    let mut extras = Vec::with_capacity(count);
+
+    for table in &tables.tables {
+        // Step 1: The dense run of P(total ≥ TN)
+        let start = probabilities.len();
+        probabilities.extend(table.at_least.iter().map(|&p| quantize(p)));
+
+        // Step 2: Fixed-width record
+        let stats = &table.statistics;
+        records.extend_from_slice(&[
+            table.roll,
+            table.keep,
+            table.explosion.threshold.unwrap_or(0),
+            table.explosion.max_chain.unwrap_or(u8::MAX),
+            table.emphasis as u8 | (!table.pipeline.is_empty() as u8) << 1,
+            0, 0, 0,
+        ]);
+        records.extend_from_slice(&stats.mean.to_le_bytes());
+        records.extend_from_slice(&stats.stddev.to_le_bytes());
+        for value in [stats.median, stats.percentile_25, stats.percentile_75, stats.min, stats.max, table.min_tn] {
+            records.extend_from_slice(&value.to_le_bytes());
+        }
+        records.extend_from_slice(&(start as u32).to_le_bytes());
+        records.extend_from_slice(&((probabilities.len() - start) as u32).to_le_bytes());
+        records.resize(records.len().next_multiple_of(RECORD_SIZE), 0);
+
+        extras.push(TableExtras { pipeline: table.pipeline.clone(), goodness_of_fit: table.goodness_of_fit.clone() });
+    }
+
+    let metadata = serde_json::to_vec(&BinaryMetadata {
+        generator_version: tables.generator_version.clone(),
+        generated_at: tables.generated_at.clone(),
+        engine: tables.engine,
+        rule_set: tables.rule_set,
+        ten_dice_rule: tables.ten_dice_rule,
+        simulation_rounds: tables.simulation_rounds.clone(),
+        probability_cutoff: tables.probability_cutoff,
+        precision: tables.precision,
+        tables: extras,
+    })?;
+
+    // Step 3: Header, then the sections in order
+    let probabilities_offset = HEADER_SIZE + records.len();
+    let metadata_offset = probabilities_offset + 4 * probabilities.len();
+    let mut bytes = Vec::with_capacity(metadata_offset + metadata.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(RECORD_SIZE as u16).to_le_bytes());
+    for value in [count, probabilities_offset, probabilities.len(), metadata_offset, metadata.len()] {
+        let value = u32::try_from(value)
+            .map_err(|_| Error::InvalidData("tables too large for the binary format".to_string()))?;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes.resize(HEADER_SIZE, 0);
+    bytes.extend_from_slice(&records);
+    for q in probabilities {
+        bytes.extend_from_slice(&q.to_le_bytes());
+    }
+    bytes.extend_from_slice(&metadata);
+
+    Ok(bytes)
+}
+
+/// Write tables to a binary file
+pub fn write_binary_file<P: AsRef<Path>>(path: P, tables: &ProbabilityTablesV2) -> Result<()> {
+    let bytes = encode_tables(tables)?;
+
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(&bytes)?;
+
+    Ok(())
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read_i32(bytes: &[u8], at: usize) -> i32 {
+    i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read_f64(bytes: &[u8], at: usize) -> f64 {
+    f64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+/// Binary tables, queried in place
+///
+/// Only the header is read when opening; records and probabilities are read
+/// from the bytes on each query, and the metadata is parsed on request.
+pub struct BinaryTables<B = Mmap> {
+    bytes: B,
+    count: usize,
+    probabilities_offset: usize,
+    probability_count: usize,
+    metadata_offset: usize,
+    metadata_len: usize,
+}
+
+impl BinaryTables<Mmap> {
+    /// Memory-map a binary tables file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the file is only read; the tables are not rewritten while loaded
+        let map = unsafe { Mmap::map(&file)? };
+        Self::from_bytes(map)
+    }
+}
+
+impl<B: AsRef<[u8]>> BinaryTables<B> {
+    /// Check the header and section bounds of encoded tables
+    pub fn from_bytes(bytes: B) -> Result<Self> {
+        let data = bytes.as_ref();
+        let invalid = |message: &str| Error::InvalidData(format!("binary tables: {}", message));
+
+        if data.len() < HEADER_SIZE || &data[..4] != MAGIC {
+            return Err(invalid("not a binary tables file"));
+        }
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        if version != FORMAT_VERSION {
+            return Err(invalid(&format!("unsupported format version {}", version)));
+        }
+        if u16::from_le_bytes([data[6], data[7]]) as usize != RECORD_SIZE {
+            return Err(invalid("unexpected record size"));
+        }
+
+        let [count, probabilities_offset, probability_count, metadata_offset, metadata_len] =
+            [8, 12, 16, 20, 24].map(|at| read_u32(data, at) as usize);
+        if probabilities_offset != HEADER_SIZE + count * RECORD_SIZE
+            || metadata_offset != probabilities_offset + 4 * probability_count
+            || metadata_offset + metadata_len != data.len()
+        {
+            return Err(invalid("truncated or inconsistent sections"));
+        }
+
+        let tables = Self { bytes, count, probabilities_offset, probability_count, metadata_offset, metadata_len };
+        for index in 0..tables.count {
+            let table = tables.table(index);
+            if table.start() + table.len() > tables.probability_count {
+                return Err(invalid(&format!("table {} runs past the probabilities", index)));
+            }
+        }
+        Ok(tables)
+    }
+
+    /// Number of tables
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Table at `index`, in generation order
+    pub fn table(&self, index: usize) -> TableView<'_> {
+        assert!(index < self.count, "table {} of {}", index, self.count);
+        let data = self.bytes.as_ref();
+        let at = HEADER_SIZE + index * RECORD_SIZE;
+        TableView {
+            record: &data[at..at + RECORD_SIZE],
+            probabilities: &data[self.probabilities_offset..self.metadata_offset],
+        }
+    }
+
+    /// Iterate over the tables in order
+    pub fn tables(&self) -> impl Iterator<Item = TableView<'_>> {
+        (0..self.count).map(|index| self.table(index))
+    }
+
+    /// Table for a pool, explosion rule, emphasis and pipeline (empty for plain rolls)
+    ///
+    /// Records only flag whether a table has a pipeline, so the metadata is
+    /// parsed to compare stages, and only when looking up a pipeline.
+    pub fn find(
+        &self,
+        roll: u8,
+        keep: u8,
+        explosion: ExplosionRule,
+        emphasis: bool,
+        pipeline: &[Stage],
+    ) -> Option<TableView<'_>> {
+        let metadata = if pipeline.is_empty() { None } else { Some(self.metadata().ok()?) };
+        self.tables().enumerate().find_map(|(index, t)| {
+            let same_pipeline = match &metadata {
+                Some(metadata) => metadata.tables.get(index).is_some_and(|extras| extras.pipeline == pipeline),
+                None => !t.has_pipeline(),
+            };
+            let matches = t.roll() == roll && t.keep() == keep && t.explosion() == explosion && t.emphasis() == emphasis;
+            (matches && same_pipeline).then_some(t)
+        })
+    }
+
+    /// Parse the metadata section
+    pub fn metadata(&self) -> Result<BinaryMetadata> {
+        let data = self.bytes.as_ref();
+        Ok(serde_json::from_slice(&data[self.metadata_offset..self.metadata_offset + self.metadata_len])?)
+    }
+}
+
+/// One table's record and its probabilities, borrowed from the file
+#[derive(Debug, Clone, Copy)]
+pub struct TableView<'a> {
+    record: &'a [u8],
+    probabilities: &'a [u8],
+}
+
+impl TableView<'_> {
+    pub fn roll(&self) -> u8 {
+        self.record[0]
+    }
+
+    pub fn keep(&self) -> u8 {
+        self.record[1]
+    }
+
+    pub fn explosion(&self) -> ExplosionRule {
+        ExplosionRule {
+            threshold: Some(self.record[2]).filter(|&t| t != 0),
+            max_chain: Some(self.record[3]).filter(|&c| c != u8::MAX),
+        }
+    }
+
+    pub fn emphasis(&self) -> bool {
+        self.record[4] & 1 != 0
+    }
+
+    /// Whether the table was generated with a pipeline (its stages are in the metadata)
+    pub fn has_pipeline(&self) -> bool {
+        self.record[4] & 2 != 0
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        Statistics {
+            mean: read_f64(self.record, 8),
+            stddev: read_f64(self.record, 16),
+            median: read_i32(self.record, 24),
+            percentile_25: read_i32(self.record, 28),
+            percentile_75: read_i32(self.record, 32),
+            min: read_i32(self.record, 36),
+            max: read_i32(self.record, 40),
+        }
+    }
+
+    /// First TN of the run
+    pub fn first_tn(&self) -> i32 {
+        read_i32(self.record, 44)
+    }
+
+    fn start(&self) -> usize {
+        read_u32(self.record, 48) as usize
+    }
+
+    /// Number of TNs in the run
+    pub fn len(&self) -> usize {
+        read_u32(self.record, 52) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// P(total ≥ TN), as `ProbabilityTableV2::probability_at_least`
+    pub fn probability_at_least(&self, tn: i32) -> f64 {
+        let offset = (tn as i64 - self.first_tn() as i64).max(0) as usize;
+        if offset >= self.len() {
+            return 0.0;
+        }
+        dequantize(read_u32(self.probabilities, 4 * (self.start() + offset)))
+    }
+}
+
+/// Differences between JSON tables and their binary encoding
+///
+/// Every table must have the same pool, rule, pipeline and statistics, and
+/// give the same P(total ≥ TN) (up to quantization) for every TN from below
+/// its first stored TN to above its last.
+pub fn binary_errors<B: AsRef<[u8]>>(json: &ProbabilityTablesV2, binary: &BinaryTables<B>) -> Vec<ValidationError> {
+    let mismatch = |check: String, expected: String, actual: String| ValidationError::Mismatch { check, expected, actual };
+    if json.tables.len() != binary.len() {
+        return vec![mismatch("table count".to_string(), json.tables.len().to_string(), binary.len().to_string())];
+    }
+    let extras = match binary.metadata() {
+        Ok(metadata) if metadata.tables.len() == binary.len() => metadata.tables,
+        Ok(metadata) => {
+            return vec![mismatch("metadata tables".to_string(), binary.len().to_string(), metadata.tables.len().to_string())]
+        }
+        Err(e) => return vec![mismatch("metadata".to_string(), "valid JSON".to_string(), e.to_string())],
+    };
+
+    let mut errors = Vec::new();
+    for ((table, view), extras) in json.tables.iter().zip(binary.tables()).zip(&extras) {
+        let name = format!("{}k{} {}{}", table.roll, table.keep, table.explosion, if table.emphasis { " +emphasis" } else { "" });
+        let expected = (table.roll, table.keep, table.explosion, table.emphasis, !table.pipeline.is_empty());
+        let actual = (view.roll(), view.keep(), view.explosion(), view.emphasis(), view.has_pipeline());
+        if expected != actual || table.pipeline != extras.pipeline {
+            let expected = format!("{:?} {:?}", expected, table.pipeline);
+            errors.push(mismatch(format!("{} config", name), expected, format!("{:?} {:?}", actual, extras.pipeline)));
+            continue;
+        }
+
+        let (expected, actual) = (&table.statistics, view.statistics());
+        let same_statistics = expected.mean == actual.mean
+            && expected.stddev == actual.stddev
+            && (expected.median, expected.percentile_25, expected.percentile_75, expected.min, expected.max)
+                == (actual.median, actual.percentile_25, actual.percentile_75, actual.min, actual.max);
+        if !same_statistics {
+            errors.push(mismatch(format!("{} statistics", name), format!("{:?}", expected), format!("{:?}", actual)));
+        }
+
+        let last_tn = view.first_tn() + view.len() as i32;
+        for tn in view.first_tn() - 2..=last_tn + 1 {
+            let (expected, actual) = (table.probability_at_least(tn), view.probability_at_least(tn));
+            if (expected - actual).abs() > QUANTIZATION_ERROR {
+                errors.push(mismatch(format!("{} P(total ≥ {})", name, tn), expected.to_string(), actual.to_string()));
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExplosionMode, RollConfig};
+    use crate::output::fixture_tables_v2;
+    use crate::pipeline::RollPipeline;
+    use crate::schema::{parse_tables, DEFAULT_PRECISION, MAX_PRECISION};
+    use crate::Engine;
+
+    fn bonus() -> Vec<Stage> {
+        vec![Stage::Modifier { bonus: 3 }]
+    }
+
+    fn sample_tables() -> ProbabilityTablesV2 {
+        let pipeline = RollPipeline::new(bonus()).unwrap();
+        let configs = [
+            RollConfig::new(1, 1, ExplosionMode::Unskilled, false).unwrap(),
+            RollConfig::new(5, 3, ExplosionMode::Skilled, true).unwrap(),
+            RollConfig::new(6, 2, ExplosionRule::new(Some(8), Some(1)).unwrap(), false).unwrap(),
+            RollConfig::new(2, 1, ExplosionMode::Mastery, false).unwrap().with_pipeline(pipeline),
+            RollConfig::new(2, 1, ExplosionMode::Mastery, false).unwrap(),
+        ];
+        fixture_tables_v2(&configs, Engine::Exact, MAX_PRECISION)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let json = sample_tables();
+        let binary = BinaryTables::from_bytes(encode_tables(&json).unwrap()).unwrap();
+        assert_eq!(binary.len(), 5);
+        assert_eq!(binary_errors(&json, &binary), vec![]);
+
+        // Through JSON text, as the tables are shipped (v2 rounded to its default precision)
+        let mut rounded = json.clone();
+        rounded.set_precision(DEFAULT_PRECISION);
+        let reparsed = parse_tables(&serde_json::to_string_pretty(&rounded).unwrap()).unwrap();
+        let binary_v2 = BinaryTables::from_bytes(encode_tables(&reparsed).unwrap()).unwrap();
+        assert_eq!(binary_errors(&reparsed, &binary_v2), vec![]);
+
+        let metadata = binary.metadata().unwrap();
+        assert_eq!(metadata.generated_at, json.generated_at);
+        assert_eq!(metadata.tables[3].pipeline, bonus());
+        assert!(metadata.tables[0].pipeline.is_empty());
+
+        // Lookups by configuration
+        let view = binary.find(5, 3, ExplosionMode::Skilled.rule(), true, &[]).unwrap();
+        assert_eq!(view.statistics().mean, json.tables[1].statistics.mean);
+        assert_eq!(view.probability_at_least(0), 1.0);
+        assert!(binary.find(5, 3, ExplosionMode::Skilled.rule(), false, &[]).is_none());
+        assert_eq!(binary.table(2).explosion(), ExplosionRule::new(Some(8), Some(1)).unwrap());
+    }
+
+    #[test]
+    fn test_find_matches_pipeline() {
+        let json = sample_tables();
+        let binary = BinaryTables::from_bytes(encode_tables(&json).unwrap()).unwrap();
+        let mastery = ExplosionMode::Mastery.rule();
+
+        let plain = binary.find(2, 1, mastery, false, &[]).unwrap();
+        assert!(!plain.has_pipeline());
+        assert_eq!(plain.statistics().mean, json.tables[4].statistics.mean);
+        let with_bonus = binary.find(2, 1, mastery, false, &bonus()).unwrap();
+        assert!(with_bonus.has_pipeline());
+        assert_eq!(with_bonus.statistics().mean, json.tables[3].statistics.mean);
+        assert!(binary.find(2, 1, mastery, false, &[Stage::Modifier { bonus: 1 }]).is_none());
+
+        // The JSON lookup agrees
+        assert_eq!(json.find(2, 1, mastery, false, &[]).unwrap().statistics.mean, json.tables[4].statistics.mean);
+        assert_eq!(json.find(2, 1, mastery, false, &bonus()).unwrap().statistics.mean, json.tables[3].statistics.mean);
+
+        // A pipeline the binary does not record is reported
+        let mut other = json.clone();
+        other.tables[3].pipeline = vec![Stage::Modifier { bonus: 1 }];
+        assert_eq!(binary_errors(&other, &binary).len(), 1);
+    }
+
+    #[test]
+    fn test_memory_mapped_file() {
+        let json = sample_tables();
+        let path = std::env::temp_dir().join(format!("l5r-binary-{}.bin", std::process::id()));
+        write_binary_file(&path, &json).unwrap();
+
+        let binary = BinaryTables::open(&path).unwrap();
+        assert_eq!(binary_errors(&json, &binary), vec![]);
+        let json_size = serde_json::to_string_pretty(&json).unwrap().len() as u64;
+        assert!(std::fs::metadata(&path).unwrap().len() * 2 < json_size);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        let bytes = encode_tables(&sample_tables()).unwrap();
+        assert!(BinaryTables::from_bytes(&bytes[..HEADER_SIZE - 1]).is_err());
+        assert!(BinaryTables::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'{';
+        assert!(BinaryTables::from_bytes(wrong_magic).is_err());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 1;
+        assert!(BinaryTables::from_bytes(wrong_version).is_err());
+
+        // A table claiming more probabilities than the file holds
+        let mut long_run = bytes;
+        long_run[HEADER_SIZE + 52..HEADER_SIZE + 56].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(BinaryTables::from_bytes(long_run).is_err());
+    }
+}
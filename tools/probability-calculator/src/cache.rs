@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Engine, ExplosionRule, RollConfig};
+use crate::dice::simulate_roll_xky_seeded;
+use crate::error::{Error, Result};
+use crate::pipeline::Stage;
+use crate::rules::RuleSet;
+
+/// Version of the cache entries; bump it when the roller changes what a seed produces
+pub const CACHE_VERSION: u32 = 2;
+
+/// Rounds per seeded block; every preset's round count is a multiple of it
+pub const BLOCK_ROUNDS: usize = 50_000;
+
+/// Stable 64-bit FNV-1a hash
+///
+/// Unlike `std::hash`, this does not change between Rust versions or runs.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Everything a simulated histogram depends on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub version: u32,
+    pub roll: u8,
+    pub keep: u8,
+    pub explosion: ExplosionRule,
+    pub emphasis: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipeline: Vec<Stage>,
+    pub rule_set: RuleSet,
+    pub engine: Engine,
+    pub seed: u64,
+}
+
+impl CacheKey {
+    pub fn new(config: &RollConfig, rule_set: RuleSet, engine: Engine, seed: u64) -> Self {
+        Self {
+            version: CACHE_VERSION,
+            roll: config.roll,
+            keep: config.keep,
+            explosion: config.explosion,
+            emphasis: config.emphasis,
+            pipeline: config.pipeline.stages.clone(),
+            rule_set,
+            engine,
+            seed,
+        }
+    }
+
+    /// Stable 64-bit hash of the key (FNV-1a over its JSON form)
+    pub fn hash(&self) -> u64 {
+        fnv1a(serde_json::to_string(self).expect("cache keys serialize").as_bytes())
+    }
+
+    /// Name of the entry's file in the cache directory
+    pub fn file_name(&self) -> String {
+        format!("{:016x}.json", self.hash())
+    }
+
+    /// Seed of the `block`-th block of rounds (SplitMix64 of the key hash and block index)
+    fn block_seed(&self, block: u32) -> u64 {
+        let mut z = self.hash().wrapping_add((block as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// A raw simulated histogram, built from seeded blocks of at most [`BLOCK_ROUNDS`] rounds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedHistogram {
+    pub key: CacheKey,
+    pub rounds: usize,
+    /// Number of seeded blocks merged into the histogram
+    pub blocks: u32,
+    pub histogram: BTreeMap<i32, usize>,
+}
+
+impl CachedHistogram {
+    /// An empty histogram for the key
+    pub fn new(key: CacheKey) -> Self {
+        Self { key, rounds: 0, blocks: 0, histogram: BTreeMap::new() }
+    }
+
+    /// Simulate `rounds` rolls for the key
+    pub fn simulate(key: CacheKey, config: &RollConfig, rounds: usize) -> Self {
+        let mut cached = Self::new(key);
+        cached.add_rounds(config, rounds);
+        cached
+    }
+
+    /// Simulate `rounds` more rolls and merge them in, tightening the estimate
+    ///
+    /// Matches a single run of the combined rounds only if the histogram
+    /// already holds a multiple of [`BLOCK_ROUNDS`] (see [`Self::simulate_to`]).
+    pub fn add_rounds(&mut self, config: &RollConfig, rounds: usize) {
+        let target = self.rounds + rounds;
+        self.simulate_to(config, target, |_| Ok(())).expect("no-op callback");
+    }
+
+    /// Simulate blocks until the histogram has `target` rounds, calling `after_block` after each
+    ///
+    /// Block seeds follow from the key and block index, so a histogram
+    /// stopped after any block and continued later (e.g. from a checkpoint)
+    /// ends up identical to one simulated in one go. That holds as long as
+    /// every earlier target was a multiple of [`BLOCK_ROUNDS`]: a shorter
+    /// last block is not topped up, so later blocks shift against a single run.
+    pub fn simulate_to<F>(&mut self, config: &RollConfig, target: usize, mut after_block: F) -> Result<()>
+    where
+        F: FnMut(&Self) -> Result<()>,
+    {
+        while self.rounds < target {
+            let rounds = BLOCK_ROUNDS.min(target - self.rounds);
+            let block = simulate_roll_xky_seeded(config, rounds, self.key.block_seed(self.blocks));
+            for (total, count) in block {
+                *self.histogram.entry(total).or_insert(0) += count;
+            }
+            self.rounds += rounds;
+            self.blocks += 1;
+            after_block(self)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the counts add up to the recorded rounds
+    pub fn verify(&self) -> Result<()> {
+        let total: usize = self.histogram.values().sum();
+        if total != self.rounds {
+            return Err(Error::InvalidData(format!(
+                "histogram {:016x} has {} rolls, expected {}",
+                self.key.hash(),
+                total,
+                self.rounds
+            )));
+        }
+        Ok(())
+    }
+
+    /// Add the counts of another histogram of the same key
+    pub fn merge(&mut self, other: &CachedHistogram) -> Result<()> {
+        if other.key != self.key {
+            return Err(Error::InvalidData(format!(
+                "cannot merge histograms of different keys ({:016x} and {:016x})",
+                self.key.hash(),
+                other.key.hash()
+            )));
+        }
+        for (&total, &count) in &other.histogram {
+            *self.histogram.entry(total).or_insert(0) += count;
+        }
+        self.rounds += other.rounds;
+        self.blocks += other.blocks;
+        Ok(())
+    }
+
+    /// The histogram in the form the statistics functions take
+    pub fn histogram(&self) -> HashMap<i32, usize> {
+        self.histogram.iter().map(|(&total, &count)| (total, count)).collect()
+    }
+}
+
+/// A directory of cached histograms, one JSON file per key
+pub struct HistogramCache {
+    dir: PathBuf,
+}
+
+impl HistogramCache {
+    /// Use `dir` as the cache, creating it if needed
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir: dir.as_ref().to_path_buf() })
+    }
+
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(key.file_name())
+    }
+
+    /// The cached histogram for `key`, if any
+    ///
+    /// An entry stored for a different key (a hash collision or an older
+    /// cache version) counts as a miss.
+    pub fn load(&self, key: &CacheKey) -> Result<Option<CachedHistogram>> {
+        let file = match File::open(self.path(key)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let cached: CachedHistogram = serde_json::from_reader(BufReader::new(file))?;
+        if &cached.key != key {
+            return Ok(None);
+        }
+        cached.verify()?;
+        Ok(Some(cached))
+    }
+
+    /// Write the histogram, replacing any previous entry for its key
+    ///
+    /// The file is written next to the entry and renamed over it, so an
+    /// interrupted run never leaves a truncated entry.
+    pub fn store(&self, cached: &CachedHistogram) -> Result<()> {
+        let path = self.path(&cached.key);
+        let tmp = path.with_extension("json.tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, cached)?;
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExplosionMode;
+
+    fn config() -> RollConfig {
+        RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap()
+    }
+
+    fn key(seed: u64) -> CacheKey {
+        CacheKey::new(&config(), RuleSet::default(), Engine::Simulation, seed)
+    }
+
+    #[test]
+    fn test_key_hash() {
+        assert_eq!(key(1).hash(), key(1).hash());
+        assert_ne!(key(1).hash(), key(2).hash());
+        assert_ne!(
+            key(1).hash(),
+            CacheKey::new(&config(), RuleSet::Legacy, Engine::Simulation, 1).hash()
+        );
+        let emphasis = RollConfig::new(3, 2, ExplosionMode::Skilled, true).unwrap();
+        assert_ne!(key(1).hash(), CacheKey::new(&emphasis, RuleSet::default(), Engine::Simulation, 1).hash());
+    }
+
+    #[test]
+    fn test_seeded_blocks_repeat() {
+        let a = CachedHistogram::simulate(key(7), &config(), 2_000);
+        let b = CachedHistogram::simulate(key(7), &config(), 2_000);
+        assert_eq!(a, b);
+        assert_ne!(a.histogram, CachedHistogram::simulate(key(8), &config(), 2_000).histogram);
+    }
+
+    #[test]
+    fn test_add_rounds() {
+        let mut cached = CachedHistogram::simulate(key(7), &config(), 2_000);
+        let first = cached.histogram.clone();
+        cached.add_rounds(&config(), 3_000);
+
+        assert_eq!(cached.rounds, 5_000);
+        assert_eq!(cached.blocks, 2);
+        assert_eq!(cached.histogram.values().sum::<usize>(), 5_000);
+        assert!(first.iter().all(|(total, &count)| cached.histogram[total] >= count));
+    }
+
+    #[test]
+    fn test_resumed_blocks_match() {
+        let target = 2 * BLOCK_ROUNDS + 1_000;
+        let whole = CachedHistogram::simulate(key(7), &config(), target);
+        assert_eq!(whole.blocks, 3);
+
+        // Stop after the first block, then continue from a copy
+        let mut partial = CachedHistogram::new(key(7));
+        let _ = partial.simulate_to(&config(), target, |h| {
+            Err(Error::InvalidData(format!("stopped after {} rounds", h.rounds)))
+        });
+        assert_eq!(partial.rounds, BLOCK_ROUNDS);
+        let mut resumed = partial.clone();
+        resumed.simulate_to(&config(), target, |_| Ok(())).unwrap();
+        assert_eq!(resumed, whole);
+
+        // A cached entry extended later matches one simulated with the extra rounds up front
+        let mut extended = CachedHistogram::simulate(key(7), &config(), 2 * BLOCK_ROUNDS);
+        extended.add_rounds(&config(), 1_000);
+        assert_eq!(extended, whole);
+    }
+
+    #[test]
+    fn test_preset_rounds_fill_blocks() {
+        // Entries extended later only match a single run if they end on a block boundary
+        for mode in [ExplosionMode::Unskilled, ExplosionMode::Skilled, ExplosionMode::Mastery] {
+            assert!(mode.simulation_rounds().is_multiple_of(BLOCK_ROUNDS), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = CachedHistogram::simulate(key(7), &config(), 1_000);
+        let b = CachedHistogram::simulate(key(7), &config(), 500);
+        a.merge(&b).unwrap();
+        assert_eq!(a.rounds, 1_500);
+        assert_eq!(a.histogram.values().sum::<usize>(), 1_500);
+
+        let other = CachedHistogram::simulate(key(8), &config(), 500);
+        assert!(a.merge(&other).is_err());
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = std::env::temp_dir().join(format!("l5r-cache-{}", std::process::id()));
+        let cache = HistogramCache::open(&dir).unwrap();
+        assert_eq!(cache.load(&key(7)).unwrap(), None);
+
+        let cached = CachedHistogram::simulate(key(7), &config(), 1_000);
+        cache.store(&cached).unwrap();
+        assert_eq!(cache.load(&key(7)).unwrap(), Some(cached));
+        assert_eq!(cache.load(&key(8)).unwrap(), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
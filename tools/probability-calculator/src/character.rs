@@ -0,0 +1,401 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+
+use crate::config::ExplosionMode;
+use crate::error::{Error, Result};
+use crate::rules::RuleSet;
+use crate::schools::{build_modified_roll, ModifierEffect, SchoolsFile};
+use crate::sheet::{markdown_table, ModifiedRoll, RollSheet};
+
+/// The eight L5R traits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trait {
+    Stamina,
+    Willpower,
+    Strength,
+    Perception,
+    Agility,
+    Intelligence,
+    Reflexes,
+    Awareness,
+}
+
+/// The five rings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ring {
+    Earth,
+    Water,
+    Fire,
+    Air,
+    Void,
+}
+
+impl Ring {
+    pub const ALL: [Ring; 5] = [Ring::Earth, Ring::Water, Ring::Fire, Ring::Air, Ring::Void];
+
+    /// The two traits a ring is made of (Void has none)
+    pub fn traits(&self) -> Option<(Trait, Trait)> {
+        match self {
+            Ring::Earth => Some((Trait::Stamina, Trait::Willpower)),
+            Ring::Water => Some((Trait::Strength, Trait::Perception)),
+            Ring::Fire => Some((Trait::Agility, Trait::Intelligence)),
+            Ring::Air => Some((Trait::Reflexes, Trait::Awareness)),
+            Ring::Void => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ring::Earth => "Earth",
+            Ring::Water => "Water",
+            Ring::Fire => "Fire",
+            Ring::Air => "Air",
+            Ring::Void => "Void",
+        }
+    }
+}
+
+/// Trait ranks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Traits {
+    pub stamina: u8,
+    pub willpower: u8,
+    pub strength: u8,
+    pub perception: u8,
+    pub agility: u8,
+    pub intelligence: u8,
+    pub reflexes: u8,
+    pub awareness: u8,
+}
+
+impl Traits {
+    pub fn get(&self, t: Trait) -> u8 {
+        match t {
+            Trait::Stamina => self.stamina,
+            Trait::Willpower => self.willpower,
+            Trait::Strength => self.strength,
+            Trait::Perception => self.perception,
+            Trait::Agility => self.agility,
+            Trait::Intelligence => self.intelligence,
+            Trait::Reflexes => self.reflexes,
+            Trait::Awareness => self.awareness,
+        }
+    }
+}
+
+/// A skill with its rank, default trait and emphases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Skill {
+    pub name: String,
+    pub rank: u8,
+    /// Trait rolled with this skill, e.g. Agility for Kenjutsu
+    #[serde(rename = "trait")]
+    pub skill_trait: Trait,
+    #[serde(default)]
+    pub emphases: Vec<String>,
+    /// Roll kinds this skill counts as for technique modifiers, e.g. "attack"
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Skill {
+    /// Whether a technique modifier's `applies_to` covers this skill
+    pub fn matches(&self, applies_to: &str) -> bool {
+        self.name.eq_ignore_ascii_case(applies_to)
+            || self.tags.iter().any(|t| t.eq_ignore_ascii_case(applies_to))
+    }
+}
+
+/// An L5R 4e character, as far as rolls are concerned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Character {
+    pub name: String,
+    pub traits: Traits,
+    /// Void Ring rank (the other rings derive from traits)
+    pub void: u8,
+    pub skills: Vec<Skill>,
+    /// School id from data/schools.json
+    #[serde(default)]
+    pub school: Option<String>,
+    /// Insight Rank
+    #[serde(default = "default_rank")]
+    pub rank: u8,
+}
+
+fn default_rank() -> u8 {
+    1
+}
+
+impl Character {
+    /// Load and validate a character file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let character: Character = serde_json::from_str(&json)?;
+        character.validate()?;
+        Ok(character)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let traits = [
+            Trait::Stamina, Trait::Willpower, Trait::Strength, Trait::Perception,
+            Trait::Agility, Trait::Intelligence, Trait::Reflexes, Trait::Awareness,
+        ];
+        if traits.iter().any(|&t| !(1..=10).contains(&self.traits.get(t))) || !(1..=10).contains(&self.void) {
+            return Err(Error::InvalidData(format!("{}: traits and Void must be 1-10", self.name)));
+        }
+        if !(1..=5).contains(&self.rank) {
+            return Err(Error::InvalidData(format!("{}: Insight Rank must be 1-5, got {}", self.name, self.rank)));
+        }
+        if let Some(skill) = self.skills.iter().find(|s| s.rank > 10) {
+            return Err(Error::InvalidData(format!("{}: {} rank must be 0-10, got {}", self.name, skill.name, skill.rank)));
+        }
+        Ok(())
+    }
+
+    /// Ring rank: the lower of its two traits, or the Void rank
+    pub fn ring(&self, ring: Ring) -> u8 {
+        match ring.traits() {
+            Some((a, b)) => self.traits.get(a).min(self.traits.get(b)),
+            None => self.void,
+        }
+    }
+
+    /// Find a skill by name (case-insensitive)
+    pub fn skill(&self, name: &str) -> Option<&Skill> {
+        self.skills.iter().find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Rank of a skill, 0 if the character does not have it
+    pub fn skill_rank(&self, name: &str) -> u8 {
+        self.skill(name).map_or(0, |s| s.rank)
+    }
+
+    /// Standard skill roll: Trait + Skill rolled, Trait kept
+    ///
+    /// Emphasis applies only when the skill has the named emphasis and is
+    /// ranked. A skill at rank 0 is rolled unskilled (no explosions).
+    /// Technique modifiers are applied on top, followed by the Ten Dice Rule.
+    pub fn skill_roll(
+        &self,
+        skill: &Skill,
+        emphasis: Option<&str>,
+        modifiers: &[&ModifierEffect],
+        rules: RuleSet,
+    ) -> Result<ModifiedRoll> {
+        let trait_rank = self.traits.get(skill.skill_trait);
+        let explosion = if skill.rank == 0 {
+            ExplosionMode::Unskilled.rule()
+        } else {
+            ExplosionMode::Skilled.rule()
+        };
+        let emphasis = skill.rank > 0
+            && emphasis.is_some_and(|e| skill.emphases.iter().any(|s| s.eq_ignore_ascii_case(e)));
+
+        build_modified_roll(
+            trait_rank + skill.rank,
+            trait_rank,
+            explosion,
+            emphasis,
+            rules,
+            modifiers.iter().copied(),
+            |name| self.skill_rank(name),
+        )
+    }
+
+    /// Ring roll: Ring rolled, Ring kept (10s explode)
+    pub fn ring_roll(&self, ring: Ring, rules: RuleSet) -> Result<ModifiedRoll> {
+        let rank = self.ring(ring);
+        build_modified_roll(rank, rank, ExplosionMode::Skilled.rule(), false, rules, [], |_| 0)
+    }
+}
+
+/// Probability sheet for one character
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CharacterSheet {
+    pub generated_at: String,
+    pub name: String,
+    pub school: Option<String>,
+    pub rank: u8,
+    /// Rule set whose Ten Dice Rule and emphasis the rolls use
+    #[serde(default)]
+    pub rule_set: RuleSet,
+    /// Technique names applied to the rolls
+    pub techniques: Vec<String>,
+    pub skills: Vec<RollSheet>,
+    pub rings: Vec<RollSheet>,
+}
+
+/// Generate a character's probability sheet
+///
+/// Every skill gets a row, plus one row per emphasis. When a schools file is
+/// given, technique modifiers whose `applies_to` names the skill or one of its
+/// tags are applied.
+pub fn generate_character_sheet(
+    character: &Character,
+    schools: Option<&SchoolsFile>,
+    rules: RuleSet,
+) -> Result<CharacterSheet> {
+    let school = character.school.as_deref()
+        .and_then(|id| schools?.schools.iter().find(|s| s.id == id));
+    let techniques = school.map(|s| s.techniques_up_to(character.rank)).unwrap_or_default();
+
+    let mut skills = Vec::new();
+    for skill in &character.skills {
+        let modifiers: Vec<&ModifierEffect> = techniques.iter()
+            .flat_map(|t| t.modifiers.iter())
+            .filter(|m| skill.matches(&m.applies_to))
+            .map(|m| &m.effect)
+            .collect();
+
+        skills.push(RollSheet::simulate(&skill.name, &character.skill_roll(skill, None, &modifiers, rules)?));
+        if skill.rank > 0 {
+            for emphasis in &skill.emphases {
+                let label = format!("{} ({})", skill.name, emphasis);
+                let roll = character.skill_roll(skill, Some(emphasis), &modifiers, rules)?;
+                skills.push(RollSheet::simulate(&label, &roll));
+            }
+        }
+    }
+
+    let rings = Ring::ALL.iter()
+        .map(|&ring| Ok(RollSheet::simulate(ring.name(), &character.ring_roll(ring, rules)?)))
+        .collect::<Result<_>>()?;
+
+    Ok(CharacterSheet {
+        generated_at: Utc::now().to_rfc3339(),
+        name: character.name.clone(),
+        school: character.school.clone(),
+        rank: character.rank,
+        rule_set: rules,
+        techniques: techniques.iter().map(|t| t.name.clone()).collect(),
+        skills,
+        rings,
+    })
+}
+
+/// Render a character sheet as Markdown
+pub fn character_sheet_markdown(sheet: &CharacterSheet) -> String {
+    let mut md = format!("# {} — Probability Sheet\n\n", sheet.name);
+    if let Some(school) = &sheet.school {
+        md.push_str(&format!("**School:** {} (Rank {})\n\n", school, sheet.rank));
+    }
+    if !sheet.techniques.is_empty() {
+        md.push_str(&format!("**Techniques applied:** {}\n\n", sheet.techniques.join(", ")));
+    }
+
+    md.push_str("## Skill Rolls\n\nChance to meet or beat each TN.\n\n");
+    md.push_str(&markdown_table(&sheet.skills));
+    md.push_str("\n## Ring Rolls\n\n");
+    md.push_str(&markdown_table(&sheet.rings));
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YOSHI: &str = r#"{
+        "name": "Kakita Yoshi",
+        "traits": {
+            "stamina": 2, "willpower": 2, "strength": 2, "perception": 3,
+            "agility": 3, "intelligence": 2, "reflexes": 4, "awareness": 3
+        },
+        "void": 2,
+        "skills": [
+            { "name": "Iaijutsu", "rank": 3, "trait": "reflexes", "emphases": ["Focus"] },
+            { "name": "Kenjutsu", "rank": 2, "trait": "agility", "emphases": ["Katana"], "tags": ["attack"] },
+            { "name": "Sailing", "rank": 0, "trait": "agility" }
+        ],
+        "school": "crane-kakita-bushi",
+        "rank": 1
+    }"#;
+
+    #[test]
+    fn test_rings_and_pools() {
+        let character: Character = serde_json::from_str(YOSHI).unwrap();
+        assert!(character.validate().is_ok());
+
+        assert_eq!(character.ring(Ring::Air), 3);
+        assert_eq!(character.ring(Ring::Earth), 2);
+        assert_eq!(character.ring(Ring::Void), 2);
+
+        // Iaijutsu: Reflexes 4 + 3 rolled, Reflexes kept
+        let iaijutsu = character.skill("iaijutsu").unwrap();
+        let roll = character.skill_roll(iaijutsu, None, &[], RuleSet::default()).unwrap();
+        assert_eq!(roll.pool, "7k4");
+        assert!(!roll.config.emphasis);
+        assert_eq!(roll.config.explosion, ExplosionMode::Skilled.rule());
+
+        // Matching emphasis applies, others do not
+        assert!(character.skill_roll(iaijutsu, Some("focus"), &[], RuleSet::default()).unwrap().config.emphasis);
+        assert!(!character.skill_roll(iaijutsu, Some("Katana"), &[], RuleSet::default()).unwrap().config.emphasis);
+    }
+
+    #[test]
+    fn test_unskilled_roll() {
+        let character: Character = serde_json::from_str(YOSHI).unwrap();
+        let sailing = character.skill("Sailing").unwrap();
+        let roll = character.skill_roll(sailing, None, &[], RuleSet::default()).unwrap();
+
+        assert_eq!(roll.pool, "3k3");
+        assert_eq!(roll.config.explosion, ExplosionMode::Unskilled.rule());
+    }
+
+    #[test]
+    fn test_technique_modifiers() {
+        let character: Character = serde_json::from_str(YOSHI).unwrap();
+        let kenjutsu = character.skill("Kenjutsu").unwrap();
+        let bonus = ModifierEffect::SkillBonus { skill: "Iaijutsu".to_string(), multiplier: 2 };
+
+        // Twice Iaijutsu 3 on a 5k3 Kenjutsu roll
+        let roll = character.skill_roll(kenjutsu, None, &[&bonus], RuleSet::default()).unwrap();
+        assert_eq!(roll.pool, "5k3+6");
+    }
+
+    #[test]
+    fn test_sheet_applies_school_technique() {
+        let character: Character = serde_json::from_str(YOSHI).unwrap();
+        let schools: SchoolsFile = serde_json::from_str(include_str!("../../../data/schools.json")).unwrap();
+        let sheet = generate_character_sheet(&character, Some(&schools), RuleSet::default()).unwrap();
+
+        assert_eq!(sheet.techniques, vec!["The Way of the Crane"]);
+        // Kenjutsu is tagged as an attack: +2 × Iaijutsu 3
+        let kenjutsu = sheet.skills.iter().find(|r| r.label == "Kenjutsu").unwrap();
+        assert_eq!(kenjutsu.pool, "5k3+6");
+        // Iaijutsu is not, and gets a row per emphasis
+        let iaijutsu: Vec<_> = sheet.skills.iter().filter(|r| r.label.starts_with("Iaijutsu")).collect();
+        assert_eq!(iaijutsu.len(), 2);
+        assert_eq!(iaijutsu[0].pool, "7k4");
+        assert!(iaijutsu[1].emphasis);
+        assert_eq!(sheet.rings.len(), 5);
+    }
+
+    #[test]
+    fn test_invalid_character() {
+        let mut character: Character = serde_json::from_str(YOSHI).unwrap();
+        character.rank = 7;
+        assert!(character.validate().is_err());
+    }
+
+    #[test]
+    fn test_markdown() {
+        let sheet = CharacterSheet {
+            generated_at: String::new(),
+            name: "Kakita Yoshi".to_string(),
+            school: Some("crane-kakita-bushi".to_string()),
+            rank: 1,
+            rule_set: RuleSet::default(),
+            techniques: vec!["The Way of the Crane".to_string()],
+            skills: vec![],
+            rings: vec![],
+        };
+        let md = character_sheet_markdown(&sheet);
+
+        assert!(md.starts_with("# Kakita Yoshi — Probability Sheet"));
+        assert!(md.contains("**Techniques applied:** The Way of the Crane"));
+        assert!(md.contains("## Ring Rolls"));
+    }
+}
@@ -0,0 +1,309 @@
+use std::fs::File;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{fnv1a, CacheKey, CachedHistogram};
+use crate::error::{Error, Result, ValidationError};
+use crate::output::ProbabilityTable;
+
+/// Version of the checkpoint format
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// Seconds between checkpoint writes
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 30;
+
+/// Identity of a generation run: its configs (with rule set, engine and seed) and extra rounds
+///
+/// A checkpoint can only be resumed by a run with the same fingerprint.
+pub fn run_fingerprint(keys: &[CacheKey], extra_rounds: Option<usize>) -> u64 {
+    let hashes: Vec<u64> = keys.iter().map(CacheKey::hash).collect();
+    fnv1a(serde_json::to_string(&(hashes, extra_rounds)).expect("fingerprints serialize").as_bytes())
+}
+
+/// A configuration finished before the checkpoint was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedTable {
+    /// Hash of the config's [`CacheKey`]
+    pub key: u64,
+    /// Rounds simulated; absent for the exact engine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounds: Option<usize>,
+    pub table: ProbabilityTable,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ValidationError>,
+}
+
+/// The histogram of the configuration in progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialHistogram {
+    /// Rounds the histogram is being simulated to
+    pub target: usize,
+    pub histogram: CachedHistogram,
+}
+
+/// First line of a checkpoint file, followed by the body
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointHeader {
+    version: u32,
+    fingerprint: u64,
+    /// FNV-1a hash of the body bytes
+    checksum: u64,
+}
+
+/// Progress of a generation run: its completed tables in config order and the partial histogram
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub fingerprint: u64,
+    pub completed: Vec<CompletedTable>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial: Option<PartialHistogram>,
+}
+
+impl Checkpoint {
+    pub fn new(fingerprint: u64) -> Self {
+        Self { fingerprint, completed: Vec::new(), partial: None }
+    }
+
+    /// Read a checkpoint, rejecting other versions and corrupted bodies
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let split = bytes
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| Error::InvalidData("checkpoint has no header".to_string()))?;
+        let header: CheckpointHeader = serde_json::from_slice(&bytes[..split])?;
+        if header.version != CHECKPOINT_VERSION {
+            return Err(Error::InvalidData(format!(
+                "unsupported checkpoint version {} (expected {})",
+                header.version, CHECKPOINT_VERSION
+            )));
+        }
+
+        let body = &bytes[split + 1..];
+        if fnv1a(body) != header.checksum {
+            return Err(Error::InvalidData("checkpoint checksum mismatch".to_string()));
+        }
+        let checkpoint: Checkpoint = serde_json::from_slice(body)?;
+        if checkpoint.fingerprint != header.fingerprint {
+            return Err(Error::InvalidData("checkpoint header does not match its body".to_string()));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Write the checkpoint, replacing the previous one atomically
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let body = serde_json::to_vec(self)?;
+        let header = CheckpointHeader { version: CHECKPOINT_VERSION, fingerprint: self.fingerprint, checksum: fnv1a(&body) };
+
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, &header)?;
+        writer.write_all(b"\n")?;
+        writer.write_all(&body)?;
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Check that the checkpoint belongs to the run of `keys` and is consistent
+    ///
+    /// Every completed table must be for the config at its position, and the
+    /// partial histogram for the next one, with counts adding up to its rounds.
+    pub fn verify(&self, keys: &[CacheKey], extra_rounds: Option<usize>) -> Result<()> {
+        if self.fingerprint != run_fingerprint(keys, extra_rounds) {
+            return Err(Error::InvalidData(
+                "checkpoint is for a different run (configs, rules, engine, seed or extra rounds changed)".to_string(),
+            ));
+        }
+        if self.completed.len() > keys.len() {
+            return Err(Error::InvalidData(format!(
+                "checkpoint has {} tables for {} configs",
+                self.completed.len(),
+                keys.len()
+            )));
+        }
+
+        for (index, (completed, key)) in self.completed.iter().zip(keys).enumerate() {
+            let table = &completed.table;
+            let matches = completed.key == key.hash()
+                && (table.roll, table.keep, table.explosion, table.emphasis) == (key.roll, key.keep, key.explosion, key.emphasis)
+                && table.pipeline == key.pipeline;
+            if !matches {
+                return Err(Error::InvalidData(format!("checkpoint table {} is for another config", index)));
+            }
+        }
+
+        if let Some(partial) = &self.partial {
+            let index = self.completed.len();
+            if keys.get(index) != Some(&partial.histogram.key) {
+                return Err(Error::InvalidData(format!("partial histogram is not for config {}", index)));
+            }
+            if partial.histogram.rounds > partial.target {
+                return Err(Error::InvalidData(format!(
+                    "partial histogram has {} rounds, more than its target {}",
+                    partial.histogram.rounds, partial.target
+                )));
+            }
+            partial.histogram.verify()?;
+        }
+        Ok(())
+    }
+}
+
+/// Periodically writes the progress of a run to a checkpoint file
+pub struct Checkpointer {
+    path: PathBuf,
+    interval: Duration,
+    last_save: Instant,
+    pub checkpoint: Checkpoint,
+}
+
+impl Checkpointer {
+    pub fn new<P: AsRef<Path>>(path: P, interval: Duration, checkpoint: Checkpoint) -> Self {
+        Self { path: path.as_ref().to_path_buf(), interval, last_save: Instant::now(), checkpoint }
+    }
+
+    /// Write the checkpoint now
+    pub fn save(&mut self) -> Result<()> {
+        self.checkpoint.save(&self.path)?;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+
+    fn save_if_due(&mut self) -> Result<()> {
+        if self.last_save.elapsed() >= self.interval {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Record a simulated block of the config in progress, saving if the interval has passed
+    pub fn progress(&mut self, target: usize, histogram: &CachedHistogram) -> Result<()> {
+        if self.last_save.elapsed() >= self.interval {
+            self.save_partial(target, histogram)?;
+        }
+        Ok(())
+    }
+
+    /// Save the config in progress now, e.g. before a cache entry it extends is overwritten
+    pub fn save_partial(&mut self, target: usize, histogram: &CachedHistogram) -> Result<()> {
+        self.checkpoint.partial = Some(PartialHistogram { target, histogram: histogram.clone() });
+        self.save()
+    }
+
+    /// Record a finished config, saving if the interval has passed
+    pub fn complete(&mut self, completed: CompletedTable) -> Result<()> {
+        self.checkpoint.partial = None;
+        self.checkpoint.completed.push(completed);
+        self.save_if_due()
+    }
+
+    /// Delete the checkpoint once the run's output is written
+    pub fn finish(self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Engine, ExplosionMode, RollConfig};
+    use crate::rules::RuleSet;
+    use crate::check_simulated_table;
+
+    fn configs() -> Vec<RollConfig> {
+        vec![
+            RollConfig::new(2, 1, ExplosionMode::Skilled, false).unwrap(),
+            RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap(),
+        ]
+    }
+
+    fn keys(seed: u64) -> Vec<CacheKey> {
+        configs().iter().map(|c| CacheKey::new(c, RuleSet::default(), Engine::Simulation, seed)).collect()
+    }
+
+    fn sample_checkpoint() -> Checkpoint {
+        let keys = keys(3);
+        let mut checkpoint = Checkpoint::new(run_fingerprint(&keys, None));
+
+        let done = CachedHistogram::simulate(keys[0].clone(), &configs()[0], 1_000);
+        let (table, errors) = check_simulated_table(&configs()[0], &done.histogram(), done.rounds);
+        checkpoint.completed.push(CompletedTable { key: keys[0].hash(), rounds: Some(done.rounds), table, errors });
+
+        let partial = CachedHistogram::simulate(keys[1].clone(), &configs()[1], 500);
+        checkpoint.partial = Some(PartialHistogram { target: 1_000, histogram: partial });
+        checkpoint
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("l5r-checkpoint-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = temp_path("roundtrip");
+        let checkpoint = sample_checkpoint();
+        checkpoint.save(&path).unwrap();
+
+        let loaded = Checkpoint::load(&path).unwrap();
+        loaded.verify(&keys(3), None).unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), serde_json::to_string(&checkpoint).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_corruption() {
+        let path = temp_path("corrupt");
+        sample_checkpoint().save(&path).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 2;
+        bytes[last] = if bytes[last] == b'1' { b'2' } else { b'1' };
+        std::fs::write(&path, bytes).unwrap();
+
+        assert!(Checkpoint::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_other_runs() {
+        let checkpoint = sample_checkpoint();
+        assert!(checkpoint.verify(&keys(4), None).is_err());
+        assert!(checkpoint.verify(&keys(3), Some(10)).is_err());
+
+        // A partial histogram whose counts do not add up
+        let mut tampered = checkpoint.clone();
+        tampered.partial.as_mut().unwrap().histogram.rounds += 1;
+        assert!(tampered.verify(&keys(3), None).is_err());
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted() {
+        let config = &configs()[1];
+        let key = keys(3)[1].clone();
+        let whole = CachedHistogram::simulate(key.clone(), config, 120_000);
+
+        // Interrupt once the first block is checkpointed, then resume from the file
+        let path = temp_path("resume");
+        let mut checkpointer = Checkpointer::new(&path, Duration::ZERO, Checkpoint::new(run_fingerprint(&keys(3), None)));
+        let mut histogram = CachedHistogram::new(key);
+        let _ = histogram.simulate_to(config, 120_000, |h| {
+            checkpointer.progress(120_000, h)?;
+            Err(Error::InvalidData("interrupted".to_string()))
+        });
+
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        let mut partial = checkpoint.partial.unwrap();
+        partial.histogram.simulate_to(config, partial.target, |_| Ok(())).unwrap();
+        assert_eq!(partial.histogram, whole);
+
+        checkpointer.finish().unwrap();
+        assert!(!path.exists());
+    }
+}
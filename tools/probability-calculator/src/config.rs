@@ -1,7 +1,15 @@
+use std::fmt;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::error::{Error, Result};
+use crate::pipeline::{RollPipeline, Stage};
+
 /// Explosion modes for L5R dice rolling
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// These are the three standard presets. Each one maps to an [`ExplosionRule`],
+/// which is what the roller actually uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ExplosionMode {
     Unskilled, // No explosions
@@ -10,60 +18,288 @@ pub enum ExplosionMode {
 }
 
 impl ExplosionMode {
+    /// All presets, in table generation order
+    pub const ALL: [ExplosionMode; 3] = [ExplosionMode::Unskilled, ExplosionMode::Skilled, ExplosionMode::Mastery];
+
+    /// Lowercase name, as used in the JSON tables
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExplosionMode::Unskilled => "unskilled",
+            ExplosionMode::Skilled => "skilled",
+            ExplosionMode::Mastery => "mastery",
+        }
+    }
+
+    /// Get the explosion rule this preset stands for
+    pub fn rule(&self) -> ExplosionRule {
+        match self {
+            ExplosionMode::Unskilled => ExplosionRule::NONE,
+            ExplosionMode::Skilled => ExplosionRule { threshold: Some(10), max_chain: None },
+            ExplosionMode::Mastery => ExplosionRule { threshold: Some(9), max_chain: None },
+        }
+    }
+
     /// Get the number of simulation rounds for this explosion mode
     pub fn simulation_rounds(&self) -> usize {
+        self.rule().simulation_rounds()
+    }
+}
+
+impl std::str::FromStr for ExplosionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        ExplosionMode::ALL.into_iter()
+            .find(|mode| mode.name() == s)
+            .ok_or_else(|| format!("Unknown explosion mode '{}' (expected unskilled, skilled or mastery)", s))
+    }
+}
+
+impl From<ExplosionMode> for ExplosionRule {
+    fn from(mode: ExplosionMode) -> Self {
+        mode.rule()
+    }
+}
+
+/// Parameterised explosion rule
+///
+/// A die explodes when its face is at or above `threshold`: it is rolled again
+/// and the new face is added to its value. `max_chain` caps how many extra
+/// rolls a single die may add, which covers house rules such as "explode only
+/// once". Techniques that make 9s explode only on damage are a threshold-9
+/// rule used for the damage roll tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "ExplosionRuleJson")]
+pub struct ExplosionRule {
+    /// Lowest face that explodes (None = dice never explode)
+    pub threshold: Option<u8>,
+    /// Maximum number of extra rolls per die (None = unlimited)
+    pub max_chain: Option<u8>,
+}
+
+/// An explosion rule as read from JSON, checked by [`ExplosionRule::new`]
+#[derive(Deserialize, JsonSchema)]
+struct ExplosionRuleJson {
+    /// Lowest face that explodes (None = dice never explode)
+    #[schemars(range(min = 2, max = 10))]
+    threshold: Option<u8>,
+    /// Maximum number of extra rolls per die (None = unlimited)
+    max_chain: Option<u8>,
+}
+
+impl TryFrom<ExplosionRuleJson> for ExplosionRule {
+    type Error = Error;
+
+    fn try_from(json: ExplosionRuleJson) -> Result<Self> {
+        ExplosionRule::new(json.threshold, json.max_chain)
+    }
+}
+
+impl ExplosionRule {
+    /// Dice never explode
+    pub const NONE: ExplosionRule = ExplosionRule { threshold: None, max_chain: None };
+
+    pub fn new(threshold: Option<u8>, max_chain: Option<u8>) -> Result<Self> {
+        if let Some(threshold) = threshold {
+            // A threshold of 1 would explode forever
+            if !(2..=10).contains(&threshold) {
+                return Err(Error::InvalidExplosion(format!("threshold must be 2-10, got {}", threshold)));
+            }
+        }
+
+        Ok(Self { threshold, max_chain })
+    }
+
+    /// Whether a face explodes after `depth` explosions already happened on this die
+    pub fn explodes(&self, face: i32, depth: u32) -> bool {
+        let face_explodes = self.threshold.is_some_and(|t| face >= t as i32);
+        let chain_open = self.max_chain.is_none_or(|max| depth < max as u32);
+        face_explodes && chain_open
+    }
+
+    /// Whether this rule can explode at all
+    pub fn can_explode(&self) -> bool {
+        self.threshold.is_some() && self.max_chain != Some(0)
+    }
+
+    /// The preset this rule corresponds to, if any
+    pub fn preset(&self) -> Option<ExplosionMode> {
+        ExplosionMode::ALL.into_iter().find(|mode| mode.rule() == *self)
+    }
+
+    /// Get the number of simulation rounds for this rule
+    ///
+    /// Lower thresholds produce longer tails and need more rounds.
+    pub fn simulation_rounds(&self) -> usize {
+        match self.threshold {
+            _ if !self.can_explode() => 200_000,
+            Some(10) => 300_000,
+            _ => 500_000,
+        }
+    }
+
+    /// Short identifier usable in file names, e.g. `e8-c1`
+    pub fn slug(&self) -> String {
+        match (self.threshold, self.max_chain) {
+            (None, _) => "e0".to_string(),
+            (Some(t), None) => format!("e{}", t),
+            (Some(t), Some(c)) => format!("e{}-c{}", t, c),
+        }
+    }
+}
+
+impl fmt::Display for ExplosionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(mode) = self.preset() {
+            return write!(f, "{}", mode.name());
+        }
+
+        match (self.threshold, self.max_chain) {
+            (None, _) => write!(f, "no explosions"),
+            (Some(t), None) => write!(f, "explode {}+", t),
+            (Some(t), Some(c)) => write!(f, "explode {}+ (max {})", t, c),
+        }
+    }
+}
+
+/// How distributions are computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    #[default]
+    Simulation, // Monte Carlo (dice.rs)
+    Exact,      // Exact convolution (exact.rs)
+}
+
+impl Engine {
+    /// Lowercase name, as used in the JSON tables
+    pub fn name(&self) -> &'static str {
         match self {
-            ExplosionMode::Unskilled => 200_000,
-            ExplosionMode::Skilled => 300_000,
-            ExplosionMode::Mastery => 500_000,
+            Engine::Simulation => "simulation",
+            Engine::Exact => "exact",
         }
     }
 }
 
-/// Configuration for a single roll type
+impl std::str::FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "simulation" => Ok(Engine::Simulation),
+            "exact" => Ok(Engine::Exact),
+            _ => Err(format!("Unknown engine '{}' (expected simulation or exact)", s)),
+        }
+    }
+}
+
+/// A dice pool, written XkY
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pool {
+    pub roll: u8,
+    pub keep: u8,
+}
+
+impl fmt::Display for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}k{}", self.roll, self.keep)
+    }
+}
+
+impl std::str::FromStr for Pool {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (roll, keep) = s.to_lowercase().split_once('k')
+            .and_then(|(roll, keep)| Some((roll.parse::<u8>().ok()?, keep.parse::<u8>().ok()?)))
+            .ok_or_else(|| format!("Invalid pool '{}' (expected XkY, e.g. 5k3)", s))?;
+        if !(1..=10).contains(&roll) || keep < 1 || keep > roll {
+            return Err(format!("Invalid pool '{}' (roll must be 1-10 and keep 1-roll)", s));
+        }
+        Ok(Pool { roll, keep })
+    }
+}
+
+/// Configuration for a single roll type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RollConfig {
     pub roll: u8,               // X in XkY (1-10)
     pub keep: u8,               // Y in XkY (1-X)
-    pub explosion_mode: ExplosionMode,
+    pub explosion: ExplosionRule,
     pub emphasis: bool,         // Whether emphasis is active
+    pub pipeline: RollPipeline, // Extra roll-modifying stages
 }
 
 impl RollConfig {
-    pub fn new(roll: u8, keep: u8, explosion_mode: ExplosionMode, emphasis: bool) -> Self {
-        assert!(roll >= 1 && roll <= 10, "Roll must be 1-10");
-        assert!(keep >= 1 && keep <= roll, "Keep must be 1-roll");
-        
+    pub fn new(roll: u8, keep: u8, explosion: impl Into<ExplosionRule>, emphasis: bool) -> Result<Self> {
+        if !(1..=10).contains(&roll) {
+            return Err(Error::InvalidConfig(format!("roll must be 1-10, got {}", roll)));
+        }
+        if keep < 1 || keep > roll {
+            return Err(Error::InvalidConfig(format!("keep must be 1-{}, got {}", roll, keep)));
+        }
+
+        Ok(Self::pool(roll, keep, explosion.into(), emphasis))
+    }
+
+    /// Build a config for a pool already known to be valid
+    fn pool(roll: u8, keep: u8, explosion: ExplosionRule, emphasis: bool) -> Self {
         Self {
             roll,
             keep,
-            explosion_mode,
+            explosion,
             emphasis,
+            pipeline: RollPipeline::default(),
         }
     }
 
+    /// Use a roll pipeline on top of the standard roll
+    pub fn with_pipeline(mut self, pipeline: RollPipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// The full list of stages for this roll, with emphasis as the first stage
+    ///
+    /// Emphasis rerolls non-exploded 1s (see dice.rs).
+    pub fn stages(&self) -> Vec<Stage> {
+        let emphasis = self.emphasis.then_some(Stage::Reroll { at_most: 1 });
+        emphasis.into_iter().chain(self.pipeline.stages.iter().copied()).collect()
+    }
+
     /// Get the number of simulation rounds for this configuration
     pub fn simulation_rounds(&self) -> usize {
-        self.explosion_mode.simulation_rounds()
+        self.explosion.simulation_rounds()
     }
 }
 
-/// Generate all 330 roll configurations
-pub fn generate_all_configs() -> Vec<RollConfig> {
-    let mut configs = Vec::with_capacity(330);
+/// Generate every pool (55) and emphasis state for each explosion rule
+///
+/// Configs are ordered by pool, then rule, then emphasis. Without emphasis
+/// only the non-emphasis configs are generated.
+pub fn generate_configs(rules: &[ExplosionRule], with_emphasis: bool) -> Vec<RollConfig> {
+    let emphasis_states: &[bool] = if with_emphasis { &[false, true] } else { &[false] };
+    let mut configs = Vec::with_capacity(55 * emphasis_states.len() * rules.len());
     
     // Enumerate all XkY combinations (55 pools)
     for roll in 1..=10 {
         for keep in 1..=roll {
-            // For each pool, try all explosion modes (3) and emphasis states (2)
-            for explosion_mode in [ExplosionMode::Unskilled, ExplosionMode::Skilled, ExplosionMode::Mastery] {
-                for emphasis in [false, true] {
-                    configs.push(RollConfig::new(roll, keep, explosion_mode, emphasis));
+            for &explosion in rules {
+                for &emphasis in emphasis_states {
+                    configs.push(RollConfig::pool(roll, keep, explosion, emphasis));
                 }
             }
         }
     }
     
+    configs
+}
+
+/// Generate all 330 roll configurations (4e rule set)
+pub fn generate_all_configs() -> Vec<RollConfig> {
+    // For each pool, try all explosion modes (3) and emphasis states (2)
+    let configs = generate_configs(&ExplosionMode::ALL.map(|mode| mode.rule()), true);
+    
     assert_eq!(configs.len(), 330, "Expected 330 configurations");
     configs
 }
@@ -80,9 +316,9 @@ mod tests {
         assert_eq!(configs.len(), 330);
         
         // Check that we have all explosion modes
-        let unskilled_count = configs.iter().filter(|c| c.explosion_mode == ExplosionMode::Unskilled).count();
-        let skilled_count = configs.iter().filter(|c| c.explosion_mode == ExplosionMode::Skilled).count();
-        let mastery_count = configs.iter().filter(|c| c.explosion_mode == ExplosionMode::Mastery).count();
+        let unskilled_count = configs.iter().filter(|c| c.explosion == ExplosionMode::Unskilled.rule()).count();
+        let skilled_count = configs.iter().filter(|c| c.explosion == ExplosionMode::Skilled.rule()).count();
+        let mastery_count = configs.iter().filter(|c| c.explosion == ExplosionMode::Mastery.rule()).count();
         
         assert_eq!(unskilled_count, 110); // 55 pools × 2 emphasis states
         assert_eq!(skilled_count, 110);
@@ -106,5 +342,61 @@ mod tests {
             assert!(config.keep >= 1 && config.keep <= config.roll);
         }
     }
+
+    #[test]
+    fn test_config_order() {
+        // Same order as the published tables: pool, then mode, then emphasis
+        let configs = generate_all_configs();
+        assert_eq!(configs[0], RollConfig::new(1, 1, ExplosionMode::Unskilled, false).unwrap());
+        assert_eq!(configs[1], RollConfig::new(1, 1, ExplosionMode::Unskilled, true).unwrap());
+        assert_eq!(configs[2], RollConfig::new(1, 1, ExplosionMode::Skilled, false).unwrap());
+        assert_eq!(configs[6], RollConfig::new(2, 1, ExplosionMode::Unskilled, false).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        assert!(matches!(RollConfig::new(11, 3, ExplosionMode::Skilled, false), Err(Error::InvalidConfig(_))));
+        assert!(matches!(RollConfig::new(3, 4, ExplosionMode::Skilled, false), Err(Error::InvalidConfig(_))));
+        assert!(matches!(RollConfig::new(3, 0, ExplosionMode::Skilled, false), Err(Error::InvalidConfig(_))));
+        assert!(matches!(ExplosionRule::new(Some(1), None), Err(Error::InvalidExplosion(_))));
+
+        // Rules read from JSON are checked too: a threshold of 1 would roll forever
+        let rule: ExplosionRule = serde_json::from_str(r#"{"threshold": 8, "max_chain": 1}"#).unwrap();
+        assert_eq!(rule, ExplosionRule::new(Some(8), Some(1)).unwrap());
+        assert!(serde_json::from_str::<ExplosionRule>(r#"{"threshold": 1, "max_chain": null}"#).is_err());
+        assert!(serde_json::from_str::<ExplosionRule>(r#"{"threshold": 0, "max_chain": null}"#).is_err());
+    }
+
+    #[test]
+    fn test_explosion_rule_presets() {
+        assert_eq!(ExplosionMode::Skilled.rule().preset(), Some(ExplosionMode::Skilled));
+        assert_eq!(ExplosionRule::new(Some(8), None).unwrap().preset(), None);
+        assert_eq!(ExplosionRule::new(Some(10), Some(1)).unwrap().preset(), None);
+
+        assert_eq!(ExplosionMode::Mastery.rule().to_string(), "mastery");
+        assert_eq!(ExplosionRule::new(Some(8), Some(1)).unwrap().to_string(), "explode 8+ (max 1)");
+        assert_eq!(ExplosionRule::new(Some(8), Some(1)).unwrap().slug(), "e8-c1");
+    }
+
+    #[test]
+    fn test_pool_parsing() {
+        assert_eq!("5k3".parse(), Ok(Pool { roll: 5, keep: 3 }));
+        assert_eq!("10K10".parse::<Pool>().unwrap().to_string(), "10k10");
+        assert!("3k4".parse::<Pool>().is_err());
+        assert!("11k2".parse::<Pool>().is_err());
+        assert!("5d3".parse::<Pool>().is_err());
+    }
+
+    #[test]
+    fn test_explosion_rule_chain_cap() {
+        let once = ExplosionRule::new(Some(10), Some(1)).unwrap();
+
+        assert!(once.explodes(10, 0));
+        assert!(!once.explodes(10, 1)); // Second 10 in the chain does not explode
+        assert!(!once.explodes(9, 0));
+
+        // A zero-length chain never explodes
+        assert!(!ExplosionRule::new(Some(10), Some(0)).unwrap().can_explode());
+    }
 }
 
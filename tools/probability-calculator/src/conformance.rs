@@ -0,0 +1,293 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::benchmarks::EXACT_TOLERANCE;
+use crate::config::RollConfig;
+use crate::dice::{calculate_raises, simulate_roll_xky, Raises, RollExpression, RollOptions};
+use crate::error::{Error, Result, ValidationError};
+use crate::exact::exact_distribution;
+use crate::fit::GoodnessOfFit;
+use crate::rules::TenDiceConversion;
+use crate::stats::{calculate_statistics_from_pmf, pmf_errors, Statistics};
+
+/// Shared conformance scenarios (data/conformance/scenarios.json)
+///
+/// Expressions and options use the shapes of `RollExpression` and
+/// `RollOptions` in packages/core/src/types/dice.ts, so the TypeScript tests
+/// can pass them straight to `executeRoll`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioFile {
+    pub scenarios: Vec<Scenario>,
+    #[serde(default)]
+    pub raises: Vec<RaiseCase>,
+}
+
+/// One roll to run through both rollers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub expression: RollExpression,
+    pub options: RollOptions,
+    /// Known values, e.g. analytic means
+    #[serde(default)]
+    pub expected: Expected,
+}
+
+/// Values a scenario must reproduce exactly
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Expected {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ten_dice: Option<TenDiceConversion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success_probability: Option<f64>,
+}
+
+/// A calculateRaises call and its expected result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaiseCase {
+    pub total: i32,
+    pub target_number: i32,
+    pub called_raises: u32,
+    pub expected: Raises,
+}
+
+impl ScenarioFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let file: ScenarioFile = serde_json::from_str(&json)?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Check names are unique and every scenario maps onto a roll configuration
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+
+        for scenario in &self.scenarios {
+            if scenario.name.is_empty() {
+                return Err(Error::InvalidData("Scenarios need a name".to_string()));
+            }
+            if !names.insert(scenario.name.as_str()) {
+                return Err(Error::InvalidData(format!("Duplicate scenario '{}'", scenario.name)));
+            }
+            scenario.config()
+                .map_err(|e| Error::InvalidData(format!("{}: {}", scenario.name, e)))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Scenario {
+    /// Ten Dice Rule conversion, as applyTenDiceRule in dice.ts
+    pub fn ten_dice(&self) -> TenDiceConversion {
+        self.expression.ten_dice()
+    }
+
+    /// Roll configuration the scenario runs as
+    pub fn config(&self) -> Result<RollConfig> {
+        self.expression.config(&self.options)
+    }
+
+    /// Effective TN once called raises are added
+    fn effective_tn(&self) -> Option<i32> {
+        self.options.target_number
+            .map(|tn| tn + 5 * self.options.called_raises.unwrap_or(0) as i32)
+    }
+}
+
+/// Fixture file for the TypeScript tests
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConformanceFixtures {
+    pub version: String,
+    pub generated_at: String,
+    pub scenarios: Vec<ScenarioFixture>,
+    pub raises: Vec<RaiseCase>,
+}
+
+/// A scenario with its exact distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioFixture {
+    pub name: String,
+    pub expression: RollExpression,
+    pub options: RollOptions,
+    pub ten_dice: TenDiceConversion,
+    pub statistics: Statistics,
+    /// P(total ≥ effective TN), when a TN is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success_probability: Option<f64>,
+    /// Mean achieved raises, when a TN is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_raises: Option<f64>,
+    /// Exact probability of each total, including the modifier
+    pub distribution: BTreeMap<String, f64>,
+    /// Fit of the Rust Monte Carlo roller to the distribution, when simulated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goodness_of_fit: Option<GoodnessOfFit>,
+}
+
+/// Run a scenario through the exact engine and check its expectations
+///
+/// With `simulate`, the Monte Carlo roller is also tested against the exact
+/// distribution.
+pub fn run_scenario(scenario: &Scenario, simulate: bool) -> Result<(ScenarioFixture, Vec<ValidationError>)> {
+    let config = scenario.config()?;
+    let pmf = exact_distribution(&config);
+    let statistics = calculate_statistics_from_pmf(&pmf);
+    let ten_dice = scenario.ten_dice();
+    let mut errors = pmf_errors(&pmf);
+
+    // Step 1: Success odds and raises against the TN
+    let called_raises = scenario.options.called_raises.unwrap_or(0);
+    let success_probability = scenario.effective_tn()
+        .map(|tn| pmf.range(tn..).map(|(_, p)| p).sum::<f64>());
+    let expected_raises = scenario.options.target_number.map(|tn| {
+        pmf.iter()
+            .map(|(&total, p)| p * calculate_raises(total, tn, called_raises).achieved_raises as f64)
+            .sum::<f64>()
+    });
+
+    // Step 2: Compare with the expected values
+    if let Some(expected) = scenario.expected.ten_dice.filter(|&e| e != ten_dice) {
+        errors.push(ValidationError::Mismatch {
+            check: format!("{} ten dice", scenario.name),
+            expected: format!("{:?}", expected),
+            actual: format!("{:?}", ten_dice),
+        });
+    }
+    let values = [
+        ("mean", scenario.expected.mean, Some(statistics.mean)),
+        ("success probability", scenario.expected.success_probability, success_probability),
+    ];
+    for (label, expected, actual) in values {
+        if let Some(expected) = expected {
+            // A missing value (e.g. no TN for a success probability) always drifts
+            let actual = actual.unwrap_or(f64::NAN);
+            if actual.is_nan() || (actual - expected).abs() > EXACT_TOLERANCE {
+                errors.push(ValidationError::BenchmarkDrift {
+                    benchmark: format!("{} {}", scenario.name, label),
+                    expected,
+                    actual,
+                    tolerance: EXACT_TOLERANCE,
+                });
+            }
+        }
+    }
+
+    // Step 3: Test the Monte Carlo roller against the exact distribution
+    let goodness_of_fit = simulate.then(|| {
+        let histogram = simulate_roll_xky(&config);
+        GoodnessOfFit::compare(&histogram, config.simulation_rounds(), &pmf)
+    });
+    if let Some(fit) = &goodness_of_fit {
+        errors.extend(fit.errors());
+    }
+
+    let fixture = ScenarioFixture {
+        name: scenario.name.clone(),
+        expression: scenario.expression,
+        options: scenario.options,
+        ten_dice,
+        statistics,
+        success_probability,
+        expected_raises,
+        distribution: pmf.iter().map(|(total, &p)| (total.to_string(), p)).collect(),
+        goodness_of_fit,
+    };
+    Ok((fixture, errors))
+}
+
+/// Check a raise case against calculate_raises
+pub fn raise_errors(case: &RaiseCase) -> Vec<ValidationError> {
+    let actual = calculate_raises(case.total, case.target_number, case.called_raises);
+    if actual == case.expected {
+        return Vec::new();
+    }
+    vec![ValidationError::Mismatch {
+        check: format!("raises for {} vs TN {} with {} called", case.total, case.target_number, case.called_raises),
+        expected: format!("{:?}", case.expected),
+        actual: format!("{:?}", actual),
+    }]
+}
+
+/// Run every scenario and raise case, returning the fixtures and any errors
+pub fn run_conformance(file: &ScenarioFile, simulate: bool) -> Result<(ConformanceFixtures, Vec<ValidationError>)> {
+    let mut scenarios = Vec::with_capacity(file.scenarios.len());
+    let mut errors = Vec::new();
+
+    for scenario in &file.scenarios {
+        let (fixture, scenario_errors) = run_scenario(scenario, simulate)?;
+        scenarios.push(fixture);
+        errors.extend(scenario_errors);
+    }
+    for case in &file.raises {
+        errors.extend(raise_errors(case));
+    }
+
+    let fixtures = ConformanceFixtures {
+        version: "1.0.0".to_string(),
+        generated_at: Utc::now().to_rfc3339(),
+        scenarios,
+        raises: file.raises.clone(),
+    };
+    Ok((fixtures, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Stage;
+
+    fn shared_scenarios() -> ScenarioFile {
+        let file: ScenarioFile = serde_json::from_str(include_str!("../../../data/conformance/scenarios.json")).unwrap();
+        file.validate().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_shared_scenarios_conform() {
+        let (fixtures, errors) = run_conformance(&shared_scenarios(), false).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(fixtures.scenarios.iter().all(|s| s.goodness_of_fit.is_none()));
+
+        // TN scenarios carry success odds and raises
+        let tn = fixtures.scenarios.iter().find(|s| s.name.starts_with("5k3+5")).unwrap();
+        assert!(tn.success_probability.unwrap() > 0.0 && tn.expected_raises.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_emphasis_threshold_mapping() {
+        let file = shared_scenarios();
+        let config = |name: &str| file.scenarios.iter().find(|s| s.name == name).unwrap().config().unwrap();
+
+        // Threshold 1 is table emphasis; others are a reroll stage
+        let one = config("1k1 unskilled, emphasis 1");
+        assert!(one.emphasis && one.pipeline.stages.is_empty());
+        let two = config("1k1 unskilled, emphasis 2");
+        assert!(!two.emphasis);
+        assert_eq!(two.pipeline.stages, vec![Stage::Reroll { at_most: 2 }]);
+
+        // Ten Dice Rule bonus and modifier fold into one stage
+        let ten_dice = config("14k12-3 mastery (Ten Dice Rule, excess kept)");
+        assert_eq!((ten_dice.roll, ten_dice.keep), (10, 10));
+        assert_eq!(ten_dice.pipeline.stages, vec![Stage::Modifier { bonus: 5 }]);
+    }
+
+    #[test]
+    fn test_mismatches_are_reported() {
+        let mut file = shared_scenarios();
+        file.scenarios[0].expected.mean = Some(6.0);
+        file.scenarios[11].expected.ten_dice = Some(TenDiceConversion { roll: 10, keep: 6, bonus: 0 });
+        file.raises[0].expected.achieved_raises = 1;
+
+        let (_, errors) = run_conformance(&file, false).unwrap();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], ValidationError::BenchmarkDrift { .. }));
+        assert!(matches!(errors[1], ValidationError::Mismatch { .. }));
+    }
+}
@@ -1,54 +1,77 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::config::{ExplosionMode, RollConfig};
+use crate::config::{ExplosionMode, ExplosionRule, RollConfig};
+use crate::error::Result;
+use crate::pipeline::{KeepWhich, RollPipeline, Stage};
+use crate::rules::{TenDiceConversion, TenDiceRule};
+use crate::seedrandom::SeedRandom;
 
-/// Represents a single die result with explosion tracking
-#[derive(Debug, Clone)]
+/// Source of d10 faces for the roller
+///
+/// Any `rand` RNG rolls uniformly; [`SeedRandom`] draws faces the way dice.ts
+/// does, so seeded bot rolls come out identical.
+pub trait D10Source {
+    /// Next face, 1-10
+    fn d10(&mut self) -> i32;
+}
+
+impl<R: Rng> D10Source for R {
+    fn d10(&mut self) -> i32 {
+        self.gen_range(1..=10)
+    }
+}
+
+impl D10Source for SeedRandom {
+    /// `Math.floor(rng() * 10) + 1`
+    fn d10(&mut self) -> i32 {
+        (self.next_f64() * 10.0).floor() as i32 + 1
+    }
+}
+
+/// A single die with its explosion chain (`DieResult` in dice.ts)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DieResult {
     pub value: i32,
+    /// Every face rolled, the first one and each explosion
+    pub rolls: Vec<i32>,
     pub exploded: bool,
 }
 
 impl DieResult {
-    fn new(value: i32, exploded: bool) -> Self {
-        Self { value, exploded }
+    fn new(rolls: Vec<i32>) -> Self {
+        Self { value: rolls.iter().sum(), exploded: rolls.len() > 1, rolls }
     }
 }
 
 /// Roll a single d10 with explosion logic
 /// 
-/// Returns the total value and whether the die exploded.
+/// Returns the total value, every face rolled and whether the die exploded.
 /// This matches the logic in packages/core/src/dice/dice.ts
-pub fn roll_d10_with_explosion<R: Rng>(mode: ExplosionMode, rng: &mut R) -> DieResult {
-    let first_roll = rng.gen_range(1..=10);
-    let mut total = first_roll;
-    let mut exploded = false;
+pub fn roll_d10_with_explosion<R: D10Source>(explosion: ExplosionRule, rng: &mut R) -> DieResult {
+    let mut rolls = Vec::with_capacity(1);
     
-    // Check if first roll explodes
-    let mut should_explode = match mode {
-        ExplosionMode::Unskilled => false,
-        ExplosionMode::Skilled => first_roll == 10,
-        ExplosionMode::Mastery => first_roll == 9 || first_roll == 10,
-    };
-    
-    if should_explode {
-        exploded = true;
-    }
-    
-    // Keep rolling while die explodes
-    while should_explode {
-        let next_roll = rng.gen_range(1..=10);
-        total += next_roll;
-        
-        // Check if this roll also explodes
-        should_explode = match mode {
-            ExplosionMode::Unskilled => false,
-            ExplosionMode::Skilled => next_roll == 10,
-            ExplosionMode::Mastery => next_roll == 9 || next_roll == 10,
-        };
+    // Keep rolling while the die explodes and the chain is not capped
+    loop {
+        let face = rng.d10();
+        rolls.push(face);
+        
+        if !explosion.explodes(face, rolls.len() as u32 - 1) {
+            break;
+        }
     }
     
-    DieResult::new(total, exploded)
+    DieResult::new(rolls)
+}
+
+/// A die rerolled by emphasis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmphasisReroll {
+    pub dice_index: usize,
+    pub old_value: i32,
+    pub new_value: i32,
 }
 
 /// Apply emphasis: reroll non-exploded dice showing 1
@@ -57,62 +80,340 @@ pub fn roll_d10_with_explosion<R: Rng>(mode: ExplosionMode, rng: &mut R) -> DieR
 /// Emphasis only rerolls dice that:
 /// 1. Did NOT explode
 /// 2. Show a value of 1
-pub fn apply_emphasis<R: Rng>(
-    dice: &mut Vec<DieResult>,
-    mode: ExplosionMode,
+pub fn apply_emphasis<R: D10Source>(
+    dice: &mut [DieResult],
+    explosion: ExplosionRule,
+    rng: &mut R,
+) -> Vec<EmphasisReroll> {
+    apply_reroll(dice, 1, explosion, rng)
+}
+
+/// Reroll non-exploded dice showing `at_most` or less, once, in dice order
+pub fn apply_reroll<R: D10Source>(
+    dice: &mut [DieResult],
+    at_most: u8,
+    explosion: ExplosionRule,
     rng: &mut R,
-) {
-    for die in dice.iter_mut() {
-        // Only reroll non-exploded dice showing 1
-        if !die.exploded && die.value == 1 {
-            let new_die = roll_d10_with_explosion(mode, rng);
-            *die = new_die;
+) -> Vec<EmphasisReroll> {
+    let mut rerolls = Vec::new();
+    for (dice_index, die) in dice.iter_mut().enumerate() {
+        if !die.exploded && die.value <= at_most as i32 {
+            let old_value = die.value;
+            *die = roll_d10_with_explosion(explosion, rng);
+            rerolls.push(EmphasisReroll { dice_index, old_value, new_value: die.value });
         }
     }
+    rerolls
 }
 
-/// Simulate a single XkY roll and return the total
+/// A die without its roll chain
+///
+/// The Monte Carlo hot path only needs the value, and skipping the chain
+/// avoids an allocation per die.
+#[derive(Debug, Clone, Copy)]
+struct Die {
+    value: i32,
+    exploded: bool,
+}
+
+/// Roll a single d10 like [`roll_d10_with_explosion`], keeping only the total
+fn roll_die<R: D10Source>(explosion: ExplosionRule, rng: &mut R) -> Die {
+    let mut total = 0;
+    let mut depth = 0;
+    
+    loop {
+        let face = rng.d10();
+        total += face;
+        
+        if !explosion.explodes(face, depth) {
+            break;
+        }
+        depth += 1;
+    }
+    
+    Die { value: total, exploded: depth > 0 }
+}
+
+/// Reroll non-exploded dice showing `at_most` or less, like [`apply_reroll`]
+fn reroll_dice<R: D10Source>(dice: &mut [Die], at_most: u8, explosion: ExplosionRule, rng: &mut R) {
+    for die in dice.iter_mut().filter(|d| !d.exploded && d.value <= at_most as i32) {
+        *die = roll_die(explosion, rng);
+    }
+}
+
+/// Simulate a single XkY roll through its pipeline and return the total
 fn simulate_single_roll<R: Rng>(
     config: &RollConfig,
     rng: &mut R,
 ) -> i32 {
     // Step 1: Roll all dice
-    let mut dice: Vec<DieResult> = (0..config.roll)
-        .map(|_| roll_d10_with_explosion(config.explosion_mode, rng))
+    let mut dice: Vec<Die> = (0..config.roll)
+        .map(|_| roll_die(config.explosion, rng))
         .collect();
     
-    // Step 2: Apply emphasis if enabled
+    // Step 2: Apply emphasis (reroll 1s) if enabled
     if config.emphasis {
-        apply_emphasis(&mut dice, config.explosion_mode, rng);
+        reroll_dice(&mut dice, 1, config.explosion, rng);
+    }
+    
+    // Step 3: Run the pipeline stages
+    let mut keep_which = KeepWhich::Highest;
+    let mut bonus = 0;
+    
+    for stage in &config.pipeline.stages {
+        match *stage {
+            Stage::Reroll { at_most } => reroll_dice(&mut dice, at_most, config.explosion, rng),
+            Stage::Remap { from, to } => {
+                for die in dice.iter_mut().filter(|d| !d.exploded && d.value == from as i32) {
+                    die.value = to as i32;
+                }
+            }
+            Stage::RerollLowest { count } => {
+                dice.sort_by_key(|d| d.value);
+                for die in dice.iter_mut().take(count as usize) {
+                    *die = roll_die(config.explosion, rng);
+                }
+            }
+            Stage::Keep { which } => keep_which = which,
+            Stage::KeptDieBonus { bonus: per_die } => bonus += per_die * config.keep as i32,
+            Stage::Modifier { bonus: flat } => bonus += flat,
+        }
     }
     
-    // Step 3: Keep highest dice
-    dice.sort_by(|a, b| b.value.cmp(&a.value));
+    // Step 4: Keep highest (or lowest) dice
+    match keep_which {
+        KeepWhich::Highest => dice.sort_by_key(|d| std::cmp::Reverse(d.value)),
+        KeepWhich::Lowest => dice.sort_by_key(|d| d.value),
+    }
     let kept_dice = &dice[0..(config.keep as usize)];
     
-    // Step 4: Sum kept dice
-    kept_dice.iter().map(|d| d.value).sum()
+    // Step 5: Sum kept dice and apply total modifiers
+    kept_dice.iter().map(|d| d.value).sum::<i32>() + bonus
 }
 
 /// Run Monte Carlo simulation for a roll configuration
 /// 
 /// Returns a histogram: total value → count
 pub fn simulate_roll_xky(config: &RollConfig) -> HashMap<i32, usize> {
-    let rounds = config.simulation_rounds();
+    simulate_with_rng(config, config.simulation_rounds(), &mut rand::thread_rng())
+}
+
+/// Run `rounds` simulated rolls from a fixed seed
+///
+/// The same config, rounds and seed always give the same histogram (for a
+/// given `rand` version).
+pub fn simulate_roll_xky_seeded(config: &RollConfig, rounds: usize, seed: u64) -> HashMap<i32, usize> {
+    simulate_with_rng(config, rounds, &mut StdRng::seed_from_u64(seed))
+}
+
+fn simulate_with_rng<R: Rng>(config: &RollConfig, rounds: usize, rng: &mut R) -> HashMap<i32, usize> {
     let mut histogram: HashMap<i32, usize> = HashMap::new();
-    let mut rng = rand::thread_rng();
     
     for _ in 0..rounds {
-        let total = simulate_single_roll(config, &mut rng);
+        let total = simulate_single_roll(config, rng);
         *histogram.entry(total).or_insert(0) += 1;
     }
     
     histogram
 }
 
+/// Outcome of a roll against a TN with called raises
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Raises {
+    #[serde(rename = "effectiveTN")]
+    pub effective_tn: i32,
+    pub success: bool,
+    pub called_raises: u32,
+    /// Called raises plus one per extra 5 over the TN; 0 on a failure
+    pub achieved_raises: u32,
+    pub margin_of_success: i32,
+}
+
+/// Check a total against a TN with called raises (+5 TN each)
+///
+/// Matches calculateRaises in packages/core/src/dice/dice.ts
+pub fn calculate_raises(total: i32, base_tn: i32, called_raises: u32) -> Raises {
+    let effective_tn = base_tn + called_raises as i32 * 5;
+    let success = total >= effective_tn;
+    let margin = total - effective_tn;
+
+    let achieved_raises = match success {
+        true => called_raises + (margin / 5) as u32,
+        false => 0,
+    };
+
+    Raises {
+        effective_tn,
+        success,
+        called_raises,
+        achieved_raises,
+        margin_of_success: margin,
+    }
+}
+
+/// A parsed XkY+Z expression, as in dice.ts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollExpression {
+    pub roll: u32,
+    pub keep: u32,
+    pub modifier: i32,
+}
+
+/// Roll options, as in dice.ts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollOptions {
+    pub explosion_mode: ExplosionMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_number: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub called_raises: Option<u32>,
+    /// Reroll non-exploded dice showing this or less, once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emphasis_threshold: Option<u8>,
+}
+
+#[cfg(test)]
+impl RollOptions {
+    /// Options without a TN or called raises
+    pub(crate) fn plain(explosion_mode: ExplosionMode, emphasis_threshold: Option<u8>) -> Self {
+        RollOptions { explosion_mode, target_number: None, called_raises: None, emphasis_threshold }
+    }
+}
+
+/// Pool size before the Ten Dice Rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pool {
+    pub roll: u32,
+    pub keep: u32,
+}
+
+/// Ten Dice Rule conversion of a roll (`TenDiceRuleConversion` in dice.ts)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenDiceRuleApplied {
+    pub original: Pool,
+    pub converted: TenDiceConversion,
+}
+
+/// Result of a complete roll, field for field as `RollResult` in dice.ts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollResult {
+    pub expression: RollExpression,
+    /// Options after defaults (`calledRaises` is always set)
+    pub options: RollOptions,
+    pub all_dice: Vec<DieResult>,
+    /// Indices of kept dice, highest first
+    pub kept_indices: Vec<usize>,
+    pub subtotal: i32,
+    pub total: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ten_dice_rule_applied: Option<TenDiceRuleApplied>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emphasis_rerolls: Option<Vec<EmphasisReroll>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub achieved_raises: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin_of_success: Option<i32>,
+}
+
+impl RollExpression {
+    /// Ten Dice Rule conversion, as applyTenDiceRule in dice.ts
+    pub fn ten_dice(&self) -> TenDiceConversion {
+        TenDiceRule::FOURTH_EDITION.apply(self.roll, self.keep)
+    }
+
+    /// Roll configuration whose distribution this roll follows
+    ///
+    /// dice.ts takes any emphasis threshold while tables only know emphasis
+    /// on 1s, so other thresholds become a `reroll` stage. The modifier and
+    /// Ten Dice Rule bonus become a `modifier` stage. Keeping more dice than
+    /// are rolled keeps them all, as `executeRoll` does.
+    pub fn config(&self, options: &RollOptions) -> Result<RollConfig> {
+        let converted = self.ten_dice();
+        let threshold = options.emphasis_threshold;
+
+        let mut stages = Vec::new();
+        if let Some(at_most) = threshold.filter(|&t| t != 1) {
+            stages.push(Stage::Reroll { at_most });
+        }
+        let bonus = self.modifier + converted.bonus;
+        if bonus != 0 {
+            stages.push(Stage::Modifier { bonus });
+        }
+
+        let keep = converted.keep.min(converted.roll);
+        Ok(RollConfig::new(converted.roll, keep, options.explosion_mode, threshold == Some(1))?
+            .with_pipeline(RollPipeline::new(stages)?))
+    }
+}
+
+/// Execute a Roll & Keep roll with L5R 4th Edition rules, with its full trace
+///
+/// Port of `executeRoll` in packages/core/src/dice/dice.ts. With a
+/// [`SeedRandom`] it draws in the same order as the TypeScript roller, so the
+/// same seed and sequence of rolls give identical results.
+pub fn execute_roll<R: D10Source>(expression: RollExpression, options: RollOptions, rng: &mut R) -> RollResult {
+    let options = RollOptions { called_raises: Some(options.called_raises.unwrap_or(0)), ..options };
+    let explosion = options.explosion_mode.rule();
+    let mut roll = expression.roll;
+    let mut keep = expression.keep;
+    let mut modifier = expression.modifier;
+
+    // Step 1: Apply Ten Dice Rule
+    let mut ten_dice_rule_applied = None;
+    if roll > 10 || keep > 10 {
+        let converted = expression.ten_dice();
+        roll = converted.roll as u32;
+        keep = converted.keep as u32;
+        modifier += converted.bonus;
+        ten_dice_rule_applied = Some(TenDiceRuleApplied { original: Pool { roll: expression.roll, keep: expression.keep }, converted });
+    }
+
+    // Step 2: Roll all dice
+    let mut all_dice: Vec<DieResult> = (0..roll).map(|_| roll_d10_with_explosion(explosion, rng)).collect();
+
+    // Step 3: Apply emphasis rerolls
+    let emphasis_rerolls = options.emphasis_threshold
+        .map(|threshold| apply_reroll(&mut all_dice, threshold, explosion, rng))
+        .filter(|rerolls| !rerolls.is_empty());
+
+    // Step 4: Keep highest dice (stable sort, like Array.prototype.sort)
+    let mut kept_indices: Vec<usize> = (0..all_dice.len()).collect();
+    kept_indices.sort_by_key(|&i| std::cmp::Reverse(all_dice[i].value));
+    kept_indices.truncate(keep as usize);
+
+    // Step 5: Calculate totals
+    let subtotal = kept_indices.iter().map(|&i| all_dice[i].value).sum();
+    let total = subtotal + modifier;
+
+    // Step 6: Check against TN and calculate raises
+    let raises = options.target_number
+        .map(|tn| calculate_raises(total, tn, options.called_raises.unwrap_or(0)));
+
+    RollResult {
+        expression,
+        options,
+        all_dice,
+        kept_indices,
+        subtotal,
+        total,
+        ten_dice_rule_applied,
+        emphasis_rerolls,
+        success: raises.map(|r| r.success),
+        achieved_raises: raises.map(|r| r.achieved_raises),
+        margin_of_success: raises.map(|r| r.margin_of_success),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ExplosionMode;
+    use crate::pipeline::RollPipeline;
     use rand::SeedableRng;
     use rand::rngs::StdRng;
 
@@ -122,7 +423,7 @@ mod tests {
         
         // Roll many times with unskilled mode
         for _ in 0..1000 {
-            let result = roll_d10_with_explosion(ExplosionMode::Unskilled, &mut rng);
+            let result = roll_d10_with_explosion(ExplosionMode::Unskilled.rule(), &mut rng);
             // Unskilled should never explode
             assert!(!result.exploded);
             // Value should be 1-10
@@ -136,7 +437,7 @@ mod tests {
         let mut found_explosion = false;
         
         for _ in 0..1000 {
-            let result = roll_d10_with_explosion(ExplosionMode::Skilled, &mut rng);
+            let result = roll_d10_with_explosion(ExplosionMode::Skilled.rule(), &mut rng);
             if result.exploded {
                 found_explosion = true;
                 // Exploded dice should have value > 10
@@ -154,7 +455,7 @@ mod tests {
         let mut found_explosion = false;
         
         for _ in 0..1000 {
-            let result = roll_d10_with_explosion(ExplosionMode::Mastery, &mut rng);
+            let result = roll_d10_with_explosion(ExplosionMode::Mastery.rule(), &mut rng);
             if result.exploded {
                 found_explosion = true;
                 // Exploded dice should have value > 8 (at least 9)
@@ -172,13 +473,14 @@ mod tests {
         
         // Create dice with a non-exploded 1
         let mut dice = vec![
-            DieResult::new(1, false),  // Should be rerolled
-            DieResult::new(5, false),  // Should not be rerolled
-            DieResult::new(10, true),  // Should not be rerolled (exploded)
+            DieResult::new(vec![1]),      // Should be rerolled
+            DieResult::new(vec![5]),      // Should not be rerolled
+            DieResult::new(vec![10, 1]),  // Should not be rerolled (exploded)
         ];
         
         let original_value = dice[0].value;
-        apply_emphasis(&mut dice, ExplosionMode::Skilled, &mut rng);
+        let rerolls = apply_emphasis(&mut dice, ExplosionMode::Skilled.rule(), &mut rng);
+        assert_eq!(rerolls, vec![EmphasisReroll { dice_index: 0, old_value: 1, new_value: dice[0].value }]);
         
         // First die should have been rerolled (value changed)
         // Note: There's a small chance it rolls 1 again, but very unlikely
@@ -188,12 +490,12 @@ mod tests {
         assert_eq!(dice[1].value, 5);
         
         // Third die should be unchanged (was exploded)
-        assert_eq!(dice[2].value, 10);
+        assert_eq!(dice[2].value, 11);
     }
 
     #[test]
     fn test_simulate_roll_histogram() {
-        let config = RollConfig::new(3, 2, ExplosionMode::Skilled, false);
+        let config = RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap();
         let histogram = simulate_roll_xky(&config);
         
         // Should have entries
@@ -211,7 +513,7 @@ mod tests {
 
     #[test]
     fn test_simulate_roll_basic_sanity() {
-        let config = RollConfig::new(5, 3, ExplosionMode::Skilled, false);
+        let config = RollConfig::new(5, 3, ExplosionMode::Skilled, false).unwrap();
         let histogram = simulate_roll_xky(&config);
         
         // Minimum possible: three 1s = 3
@@ -221,5 +523,132 @@ mod tests {
         // Should have reasonable distribution
         assert!(histogram.len() > 10); // More than 10 unique outcomes
     }
-}
 
+    #[test]
+    fn test_low_threshold_explosion() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let rule = ExplosionRule::new(Some(8), None).unwrap();
+        
+        for _ in 0..1000 {
+            let result = roll_d10_with_explosion(rule, &mut rng);
+            if result.exploded {
+                // 8+ followed by at least a 1
+                assert!(result.value > 8);
+            } else {
+                // Non-exploded dice can only show 1-7
+                assert!(result.value <= 7);
+            }
+        }
+    }
+
+    #[test]
+    fn test_capped_explosion_chain() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let once = ExplosionRule::new(Some(10), Some(1)).unwrap();
+        
+        for _ in 0..10_000 {
+            let result = roll_d10_with_explosion(once, &mut rng);
+            // At most one extra roll: the value can never exceed 10 + 10
+            assert!(result.value <= 20);
+        }
+    }
+
+    #[test]
+    fn test_pipeline_stages() {
+        let mut rng = StdRng::seed_from_u64(42);
+        
+        // 1s as 2s on an unskilled 1k1: never below 2
+        let config = RollConfig::new(1, 1, ExplosionMode::Unskilled, false).unwrap()
+            .with_pipeline(RollPipeline::new(vec![Stage::Remap { from: 1, to: 2 }]).unwrap());
+        for _ in 0..1000 {
+            let total = simulate_single_roll(&config, &mut rng);
+            assert!((2..=10).contains(&total));
+        }
+        
+        // Keep lowest with +1 per kept die and a -5 modifier on an unskilled 3k2
+        let config = RollConfig::new(3, 2, ExplosionMode::Unskilled, false).unwrap()
+            .with_pipeline(RollPipeline::new(vec![
+                Stage::Keep { which: KeepWhich::Lowest },
+                Stage::KeptDieBonus { bonus: 1 },
+                Stage::Modifier { bonus: -5 },
+            ]).unwrap());
+        for _ in 0..1000 {
+            let total = simulate_single_roll(&config, &mut rng);
+            // Two lowest of three d10s: 2-20, +2 per kept dice, -5
+            assert!((-1..=17).contains(&total));
+        }
+    }
+
+    #[test]
+    fn test_calculate_raises() {
+        // One extra raise per full 5 over the TN
+        let raises = calculate_raises(31, 15, 1);
+        assert_eq!((raises.effective_tn, raises.success, raises.achieved_raises), (20, true, 3));
+        assert_eq!(raises.margin_of_success, 11);
+
+        // Failing loses the called raises
+        let raises = calculate_raises(19, 15, 1);
+        assert_eq!((raises.success, raises.achieved_raises, raises.margin_of_success), (false, 0, -1));
+
+        let json = serde_json::to_value(calculate_raises(24, 20, 0)).unwrap();
+        assert_eq!(json["effectiveTN"], 20);
+        assert_eq!(json["marginOfSuccess"], 4);
+    }
+
+    #[test]
+    fn test_execute_roll_trace() {
+        let expression = RollExpression { roll: 13, keep: 4, modifier: 1 };
+        let options = RollOptions { target_number: Some(20), emphasis_threshold: Some(2), ..RollOptions::plain(ExplosionMode::Mastery, None) };
+        let result = execute_roll(expression, options, &mut SeedRandom::new("hello."));
+
+        // 13k4+1 → 10k5+3
+        let applied = result.ten_dice_rule_applied.unwrap();
+        assert_eq!(applied.converted, TenDiceConversion { roll: 10, keep: 5, bonus: 2 });
+        assert_eq!(result.all_dice.len(), 10);
+        assert_eq!(result.kept_indices.len(), 5);
+        assert_eq!(result.total, result.subtotal + 3);
+
+        for die in &result.all_dice {
+            assert_eq!(die.value, die.rolls.iter().sum::<i32>());
+            assert_eq!(die.exploded, die.rolls.len() > 1);
+        }
+        // Kept dice are the highest, highest first
+        let kept: Vec<i32> = result.kept_indices.iter().map(|&i| result.all_dice[i].value).collect();
+        assert!(kept.windows(2).all(|w| w[0] >= w[1]));
+        assert!(result.all_dice.iter().all(|d| d.value <= kept[0]));
+
+        // First draw of "hello." is 0.928… → a 10, which explodes
+        assert_eq!(result.all_dice[0].rolls[0], 10);
+        assert_eq!(result.options.called_raises, Some(0));
+        assert!(result.success.is_some());
+    }
+
+    #[test]
+    fn test_traced_die_matches_simulation() {
+        // Same seed, same faces: the chain only adds the individual rolls
+        let mut traced = StdRng::seed_from_u64(7);
+        let mut plain = StdRng::seed_from_u64(7);
+        for _ in 0..1000 {
+            let die = roll_d10_with_explosion(ExplosionMode::Mastery.rule(), &mut traced);
+            let Die { value, exploded } = roll_die(ExplosionMode::Mastery.rule(), &mut plain);
+            assert_eq!((die.value, die.exploded), (value, exploded));
+        }
+    }
+
+    #[test]
+    fn test_execute_roll_emphasis_trace() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let expression = RollExpression { roll: 10, keep: 3, modifier: 0 };
+        let options = RollOptions { emphasis_threshold: Some(3), ..RollOptions::plain(ExplosionMode::Unskilled, None) };
+
+        for _ in 0..100 {
+            let result = execute_roll(expression, options, &mut rng);
+            assert!(result.ten_dice_rule_applied.is_none() && result.success.is_none());
+            // Each reroll is recorded against the die it replaced
+            for reroll in result.emphasis_rerolls.iter().flatten() {
+                assert!(reroll.old_value <= 3);
+                assert_eq!(result.all_dice[reroll.dice_index].value, reroll.new_value);
+            }
+        }
+    }
+}
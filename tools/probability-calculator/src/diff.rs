@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Engine, ExplosionRule};
+use crate::output::SimulationRounds;
+use crate::pipeline::{RollPipeline, Stage};
+use crate::schema::{ProbabilityTableV2, ProbabilityTablesV2};
+
+/// Default z-score a change must exceed to be flagged
+///
+/// Each file has hundreds of tables of dozens of TNs, so the threshold is
+/// well above the usual 1.96 to keep noise from being flagged.
+pub const DEFAULT_Z: f64 = 4.0;
+
+/// Changes smaller than this are float noise, not changes
+const EPSILON: f64 = 1e-12;
+
+/// Identity of a table across files
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ConfigKey {
+    roll: u8,
+    keep: u8,
+    explosion: ExplosionRule,
+    emphasis: bool,
+    pipeline: Vec<Stage>,
+}
+
+impl ConfigKey {
+    fn of(table: &ProbabilityTableV2) -> Self {
+        Self {
+            roll: table.roll,
+            keep: table.keep,
+            explosion: table.explosion,
+            emphasis: table.emphasis,
+            pipeline: table.pipeline.clone(),
+        }
+    }
+
+    /// e.g. `5k3 skilled + emphasis (reroll≤2)`
+    fn label(&self) -> String {
+        let mut label = format!("{}k{} {}", self.roll, self.keep, self.explosion);
+        if self.emphasis {
+            label.push_str(" + emphasis");
+        }
+        if !self.pipeline.is_empty() {
+            label.push_str(&format!(" ({})", RollPipeline { stages: self.pipeline.clone() }.describe()));
+        }
+        label
+    }
+}
+
+/// A changed metadata field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// A changed statistic of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatisticChange {
+    pub field: String,
+    pub old: f64,
+    pub new: f64,
+}
+
+/// Differences between the two versions of one table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    pub config: String,
+    pub statistics: Vec<StatisticChange>,
+    /// Largest |new - old| of P(total ≥ TN), and where
+    pub max_abs_change: f64,
+    pub max_abs_tn: i32,
+    /// Largest |new - old| / max(old, new) of P(total ≥ TN), and where
+    pub max_rel_change: f64,
+    pub max_rel_tn: i32,
+    /// TNs whose change sampling error and rounding cannot explain
+    pub flagged_tns: Vec<i32>,
+    /// Whether the mean moved more than sampling error can explain
+    pub mean_flagged: bool,
+}
+
+impl ConfigDiff {
+    pub fn is_flagged(&self) -> bool {
+        self.mean_flagged || !self.flagged_tns.is_empty()
+    }
+}
+
+/// Differences between two table files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub old_generated_at: String,
+    pub new_generated_at: String,
+    pub z: f64,
+    pub metadata: Vec<MetadataChange>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Tables with any difference, flagged or not, in the new file's order
+    pub changed: Vec<ConfigDiff>,
+    pub unchanged: usize,
+    /// Number of changed tables with a flagged change
+    pub flagged: usize,
+}
+
+/// Sampling and storage error of one file's tables
+struct Noise {
+    engine: Engine,
+    rounds: SimulationRounds,
+    /// Largest error from rounding or dropping tail values
+    storage: f64,
+}
+
+impl Noise {
+    fn of(tables: &ProbabilityTablesV2) -> Self {
+        Self {
+            engine: tables.engine,
+            rounds: tables.simulation_rounds.clone(),
+            storage: 0.5 * 10f64.powi(-(tables.precision as i32)) + tables.probability_cutoff,
+        }
+    }
+
+    /// Rolls behind each table with this rule; None if the table is exact
+    fn rounds(&self, rule: ExplosionRule) -> Option<f64> {
+        (self.engine == Engine::Simulation).then(|| self.rounds.for_rule(rule) as f64)
+    }
+
+    /// Variance of an estimated proportion `p`
+    fn proportion_variance(&self, rule: ExplosionRule, p: f64) -> f64 {
+        self.rounds(rule).map_or(0.0, |n| p * (1.0 - p) / n)
+    }
+
+    /// Variance of an estimated mean with standard deviation `stddev`
+    fn mean_variance(&self, rule: ExplosionRule, stddev: f64) -> f64 {
+        self.rounds(rule).map_or(0.0, |n| stddev * stddev / n)
+    }
+}
+
+/// Compare two tables of the same config
+fn diff_table(
+    key: &ConfigKey,
+    old: &ProbabilityTableV2,
+    new: &ProbabilityTableV2,
+    noise: (&Noise, &Noise),
+    z: f64,
+) -> Option<ConfigDiff> {
+    let (old_noise, new_noise) = noise;
+    let tolerance = old_noise.storage + new_noise.storage;
+
+    let mut diff = ConfigDiff {
+        config: key.label(),
+        statistics: Vec::new(),
+        max_abs_change: 0.0,
+        max_abs_tn: 0,
+        max_rel_change: 0.0,
+        max_rel_tn: 0,
+        flagged_tns: Vec::new(),
+        mean_flagged: false,
+    };
+
+    let (a, b) = (&old.statistics, &new.statistics);
+    let fields = [
+        ("mean", a.mean, b.mean),
+        ("stddev", a.stddev, b.stddev),
+        ("median", a.median as f64, b.median as f64),
+        ("percentile_25", a.percentile_25 as f64, b.percentile_25 as f64),
+        ("percentile_75", a.percentile_75 as f64, b.percentile_75 as f64),
+        ("min", a.min as f64, b.min as f64),
+        ("max", a.max as f64, b.max as f64),
+    ];
+    for (field, old, new) in fields {
+        if (new - old).abs() > EPSILON {
+            diff.statistics.push(StatisticChange { field: field.to_string(), old, new });
+        }
+    }
+
+    // Means are stored rounded like the probabilities
+    let mean_se = (old_noise.mean_variance(key.explosion, a.stddev) + new_noise.mean_variance(key.explosion, b.stddev)).sqrt();
+    diff.mean_flagged = (b.mean - a.mean).abs() > z * mean_se + tolerance;
+
+    let lo = old.min_tn.min(new.min_tn);
+    let hi = (old.min_tn + old.at_least.len() as i32).max(new.min_tn + new.at_least.len() as i32);
+    for tn in lo..=hi {
+        let (p, q) = (old.probability_at_least(tn), new.probability_at_least(tn));
+        let change = (q - p).abs();
+        if change <= EPSILON {
+            continue;
+        }
+        if change > diff.max_abs_change {
+            diff.max_abs_change = change;
+            diff.max_abs_tn = tn;
+        }
+        let relative = change / p.max(q);
+        if relative > diff.max_rel_change {
+            diff.max_rel_change = relative;
+            diff.max_rel_tn = tn;
+        }
+
+        // Two-proportion test on the pooled estimate
+        let pooled = (p + q) / 2.0;
+        let se = (old_noise.proportion_variance(key.explosion, pooled)
+            + new_noise.proportion_variance(key.explosion, pooled))
+            .sqrt();
+        if change > z * se + tolerance {
+            diff.flagged_tns.push(tn);
+        }
+    }
+
+    let changed = !diff.statistics.is_empty() || diff.max_abs_change > 0.0;
+    changed.then_some(diff)
+}
+
+/// Compact JSON of a metadata value
+fn json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Metadata fields that differ
+fn metadata_changes(old: &ProbabilityTablesV2, new: &ProbabilityTablesV2) -> Vec<MetadataChange> {
+    let fields = [
+        ("engine", old.engine.name().to_string(), new.engine.name().to_string()),
+        ("rule_set", old.rule_set.name().to_string(), new.rule_set.name().to_string()),
+        ("ten_dice_rule", json(&old.ten_dice_rule), json(&new.ten_dice_rule)),
+        ("simulation_rounds", json(&old.simulation_rounds), json(&new.simulation_rounds)),
+        ("probability_cutoff", old.probability_cutoff.to_string(), new.probability_cutoff.to_string()),
+        ("precision", old.precision.to_string(), new.precision.to_string()),
+        ("generator_version", old.generator_version.clone(), new.generator_version.clone()),
+    ];
+    fields.into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| MetadataChange { field: field.to_string(), old, new })
+        .collect()
+}
+
+/// Compare two table files, flagging changes beyond `z` standard errors
+///
+/// Each file's sampling error comes from its own recorded simulation rounds
+/// (exact tables have none); rounding to its precision and the probability
+/// cutoff are added on top.
+pub fn diff_tables(old: &ProbabilityTablesV2, new: &ProbabilityTablesV2, z: f64) -> TableDiff {
+    let noise = (Noise::of(old), Noise::of(new));
+    let old_by_key: HashMap<ConfigKey, &ProbabilityTableV2> = old.tables.iter().map(|t| (ConfigKey::of(t), t)).collect();
+    let new_keys: HashSet<ConfigKey> = new.tables.iter().map(ConfigKey::of).collect();
+
+    let mut diff = TableDiff {
+        old_generated_at: old.generated_at.clone(),
+        new_generated_at: new.generated_at.clone(),
+        z,
+        metadata: metadata_changes(old, new),
+        added: Vec::new(),
+        removed: old.tables.iter()
+            .map(ConfigKey::of)
+            .filter(|key| !new_keys.contains(key))
+            .map(|key| key.label())
+            .collect(),
+        changed: Vec::new(),
+        unchanged: 0,
+        flagged: 0,
+    };
+
+    for table in &new.tables {
+        let key = ConfigKey::of(table);
+        match old_by_key.get(&key) {
+            None => diff.added.push(key.label()),
+            Some(old_table) => match diff_table(&key, old_table, table, (&noise.0, &noise.1), z) {
+                Some(config) => {
+                    diff.flagged += config.is_flagged() as usize;
+                    diff.changed.push(config);
+                }
+                None => diff.unchanged += 1,
+            },
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExplosionMode, RollConfig};
+    use crate::output::fixture_tables_v2;
+    use crate::schema::DEFAULT_PRECISION;
+
+    fn tables(engine: Engine, configs: &[RollConfig]) -> ProbabilityTablesV2 {
+        fixture_tables_v2(configs, engine, DEFAULT_PRECISION)
+    }
+
+    fn configs() -> Vec<RollConfig> {
+        vec![
+            RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap(),
+            RollConfig::new(4, 2, ExplosionMode::Unskilled, true).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_identical_files() {
+        let exact = tables(Engine::Exact, &configs());
+        let diff = diff_tables(&exact, &exact, DEFAULT_Z);
+        assert!(diff.changed.is_empty() && diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+
+    #[test]
+    fn test_added_and_removed() {
+        let old = tables(Engine::Exact, &configs());
+        let mut new_configs = configs();
+        new_configs[0] = RollConfig::new(3, 2, ExplosionMode::Mastery, false).unwrap();
+        let new = tables(Engine::Exact, &new_configs);
+
+        let diff = diff_tables(&old, &new, DEFAULT_Z);
+        assert_eq!(diff.added, ["3k2 mastery"]);
+        assert_eq!(diff.removed, ["3k2 skilled"]);
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn test_simulation_noise_is_not_flagged() {
+        // Two independent simulations of the same configs differ only by noise
+        let (old, new) = (tables(Engine::Simulation, &configs()), tables(Engine::Simulation, &configs()));
+        let diff = diff_tables(&old, &new, DEFAULT_Z);
+
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.flagged, 0, "{:?}", diff.changed);
+        assert!(diff.changed.iter().all(|c| c.max_abs_change < 0.01));
+    }
+
+    #[test]
+    fn test_real_changes_are_flagged() {
+        let old = tables(Engine::Simulation, &configs());
+        let mut new = old.clone();
+
+        // Shift one table by a TN: far beyond 300k-roll sampling error
+        let table = &mut new.tables[0];
+        table.min_tn += 1;
+        table.statistics.mean += 1.0;
+
+        let diff = diff_tables(&old, &new, DEFAULT_Z);
+        assert_eq!(diff.flagged, 1);
+        let changed = &diff.changed[0];
+        assert_eq!(changed.config, "3k2 skilled");
+        assert!(changed.mean_flagged && !changed.flagged_tns.is_empty());
+        assert!(changed.max_abs_change > 0.05);
+        assert_eq!(changed.statistics[0].field, "mean");
+
+        // Exact tables have no sampling error: any change beyond rounding is flagged
+        let exact = tables(Engine::Exact, &configs());
+        let mut nudged = exact.clone();
+        nudged.tables[1].at_least[5] -= 1e-4;
+        let diff = diff_tables(&exact, &nudged, DEFAULT_Z);
+        assert_eq!(diff.changed[0].flagged_tns, [nudged.tables[1].min_tn + 5]);
+    }
+
+    #[test]
+    fn test_metadata_changes() {
+        let old = tables(Engine::Exact, &configs());
+        let mut new = old.clone();
+        new.precision = 4;
+        let diff = diff_tables(&old, &new, DEFAULT_Z);
+        assert_eq!(diff.metadata, [MetadataChange { field: "precision".into(), old: "6".into(), new: "4".into() }]);
+    }
+}
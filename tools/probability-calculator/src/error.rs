@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors returned by the probability calculator library
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Roll configuration outside the supported pools (1-10 rolled, 1-X kept)
+    #[error("Invalid roll configuration: {0}")]
+    InvalidConfig(String),
+
+    /// Explosion rule that cannot be rolled, e.g. a threshold of 1
+    #[error("Invalid explosion rule: {0}")]
+    InvalidExplosion(String),
+
+    /// Roll pipeline with stages out of order or bad parameters
+    #[error("Invalid pipeline: {0}")]
+    InvalidPipeline(String),
+
+    /// Distribution that failed validation
+    #[error("Invalid distribution: {0}")]
+    Validation(#[from] ValidationError),
+
+    /// Schools or character data that failed validation
+    #[error("Invalid data: {0}")]
+    InvalidData(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// A distribution validation failure
+#[derive(Debug, Clone, PartialEq, Error, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValidationError {
+    /// Histogram counts do not add up to the number of simulated rolls
+    #[error("count sum mismatch: {actual} != {expected}")]
+    CountMismatch { expected: usize, actual: usize },
+
+    /// Probabilities do not sum to 1.0 within the tolerance
+    #[error("probability sum not 1.0: {sum}")]
+    ProbabilitySum { sum: f64, tolerance: f64 },
+
+    /// P(total ≥ TN) increases from the previous TN
+    #[error("non-monotonic cumulative at TN {tn}: {probability} > {previous}")]
+    NonMonotonic { tn: i32, probability: f64, previous: f64 },
+
+    /// Probability outside [0, 1]
+    #[error("probability out of range at TN {tn}: {probability}")]
+    OutOfRange { tn: i32, probability: f64 },
+
+    /// Value differs from a reference benchmark by more than the tolerance
+    #[error("benchmark '{benchmark}' drifted: {actual} vs {expected} (±{tolerance})")]
+    BenchmarkDrift { benchmark: String, expected: f64, actual: f64, tolerance: f64 },
+
+    /// Simulated histogram rejected by a goodness-of-fit test against the exact distribution
+    #[error("{test} test rejects the simulation: statistic {statistic}, p = {p_value:e}")]
+    PoorFit { test: String, statistic: f64, p_value: f64 },
+
+    /// Exact result differs from a conformance expectation
+    #[error("{check}: expected {expected}, got {actual}")]
+    Mismatch { check: String, expected: String, actual: String },
+
+    /// Output that does not match the JSON Schema of its Rust type
+    #[error("schema violation at {path}: {message}")]
+    SchemaViolation { path: String, message: String },
+}
+
+/// Result type used throughout the library
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+use crate::config::{ExplosionRule, RollConfig};
+use crate::pipeline::{KeepWhich, Stage};
+
+/// Explosion chains are truncated once their remaining probability drops below this
+pub const EXACT_CUTOFF: f64 = 1e-12;
+
+/// Exact distribution of a single die, split by whether it exploded
+///
+/// Both vectors are indexed by die value. Emphasis-style rerolls and remaps
+/// only touch dice that did not explode, so the split has to be kept until all
+/// per-die stages have run.
+#[derive(Debug, Clone)]
+pub struct DieDistribution {
+    /// P(value) for dice that did not explode
+    pub plain: Vec<f64>,
+    /// P(value) for dice that exploded
+    pub exploded: Vec<f64>,
+}
+
+impl DieDistribution {
+    /// Distribution of a freshly rolled d10 under an explosion rule
+    pub fn new(explosion: ExplosionRule) -> Self {
+        let uniform = {
+            let mut faces = vec![0.0; 11];
+            faces[1..].fill(0.1);
+            faces
+        };
+
+        let threshold = match explosion.threshold {
+            Some(t) if explosion.can_explode() => t as usize,
+            _ => return Self { plain: uniform, exploded: vec![0.0; 11] },
+        };
+
+        // Value added by the rest of the chain once the first face exploded.
+        // A capped chain ends on a plain roll; an unlimited one is truncated
+        // after enough levels that the remaining mass is below EXACT_CUTOFF.
+        let levels = chain_levels(explosion);
+        let mut tail = if explosion.max_chain.is_some() { uniform } else { vec![0.0; 1] };
+        for _ in 1..levels {
+            tail = explosion_step(&tail, threshold);
+        }
+
+        // Split the first roll into plain and exploded outcomes
+        let mut plain = vec![0.0; 11];
+        plain[1..threshold].fill(0.1);
+        let mut exploded = vec![0.0; 11 + tail.len()];
+        for face in threshold..=10 {
+            for (value, &p) in tail.iter().enumerate() {
+                exploded[face + value] += 0.1 * p;
+            }
+        }
+
+        Self { plain, exploded }
+    }
+
+    /// Combined P(value), regardless of explosion
+    pub fn pmf(&self) -> Vec<f64> {
+        let len = self.plain.len().max(self.exploded.len());
+        (0..len)
+            .map(|v| self.plain.get(v).unwrap_or(&0.0) + self.exploded.get(v).unwrap_or(&0.0))
+            .collect()
+    }
+
+    /// Reroll non-exploded dice showing `at_most` or less into a fresh die
+    fn reroll(&mut self, at_most: u8, fresh: &DieDistribution) {
+        let limit = (at_most as usize + 1).min(self.plain.len());
+        let moved: f64 = self.plain[..limit].iter().sum();
+        self.plain[..limit].fill(0.0);
+
+        add_scaled(&mut self.plain, &fresh.plain, moved);
+        add_scaled(&mut self.exploded, &fresh.exploded, moved);
+    }
+
+    /// Treat non-exploded dice showing `from` as showing `to`
+    fn remap(&mut self, from: u8, to: u8) {
+        let moved = std::mem::take(&mut self.plain[from as usize]);
+        self.plain[to as usize] += moved;
+    }
+}
+
+/// One level of an explosion chain: faces below the threshold stop, the rest add `next`
+fn explosion_step(next: &[f64], threshold: usize) -> Vec<f64> {
+    let mut dist = vec![0.0; 11 + next.len()];
+    dist[1..threshold].fill(0.1);
+    for face in threshold..=10 {
+        for (value, &p) in next.iter().enumerate() {
+            dist[face + value] += 0.1 * p;
+        }
+    }
+    dist
+}
+
+/// Number of explosion levels the exact engine walks for a rule
+///
+/// A capped chain walks `max_chain` levels; an unlimited one is truncated once
+/// the remaining mass is below EXACT_CUTOFF. The engine's cost grows with it.
+pub fn chain_levels(explosion: ExplosionRule) -> usize {
+    match explosion.threshold {
+        Some(threshold) if explosion.can_explode() => match explosion.max_chain {
+            Some(max) => max as usize,
+            None => {
+                let p_explode = (11 - threshold) as f64 / 10.0;
+                (EXACT_CUTOFF.ln() / p_explode.ln()).ceil() as usize
+            }
+        },
+        _ => 0,
+    }
+}
+
+fn add_scaled(target: &mut Vec<f64>, source: &[f64], scale: f64) {
+    if target.len() < source.len() {
+        target.resize(source.len(), 0.0);
+    }
+    for (t, &s) in target.iter_mut().zip(source) {
+        *t += s * scale;
+    }
+}
+
+/// Compute the exact distribution of a roll configuration
+///
+/// Runs the same stages as the simulator in dice.rs. Returns P(total) for every
+/// reachable total; explosion chains are truncated at EXACT_CUTOFF, so the
+/// probabilities sum to slightly less than 1.
+pub fn exact_distribution(config: &RollConfig) -> BTreeMap<i32, f64> {
+    let fresh = DieDistribution::new(config.explosion);
+    let stages = config.stages();
+
+    // Step 1: Per-die stages (emphasis is the first one when enabled)
+    let mut die = fresh.clone();
+    for stage in &stages {
+        match *stage {
+            Stage::Reroll { at_most } => die.reroll(at_most, &fresh),
+            Stage::Remap { from, to } => die.remap(from, to),
+            _ => {}
+        }
+    }
+
+    // Step 2: Pool and keep stages
+    let pool = PoolSpec {
+        dice: config.roll as usize,
+        keep: config.keep as usize,
+        reroll_lowest: (config.pipeline.reroll_lowest() as usize).min(config.roll as usize),
+        which: config.pipeline.keep_which(),
+    };
+    let kept_sums = kept_sum_distribution(&pool, &die.pmf(), &fresh.pmf());
+
+    // Step 3: Total stages shift the whole distribution
+    let bonus = config.pipeline.total_bonus(config.keep);
+    kept_sums.into_iter()
+        .enumerate()
+        .filter(|&(_, p)| p > 0.0)
+        .map(|(sum, p)| (sum as i32 + bonus, p))
+        .collect()
+}
+
+/// Shape of the pool for the keep DP
+struct PoolSpec {
+    dice: usize,
+    keep: usize,
+    reroll_lowest: usize,
+    which: KeepWhich,
+}
+
+impl PoolSpec {
+    /// Originals still in the pool after `assigned` of them have taken a value
+    ///
+    /// Values are assigned in keep order, so the rerolled lowest originals are
+    /// the last ones when keeping highest and the first ones when keeping lowest.
+    fn eligible(&self, assigned: usize) -> usize {
+        match self.which {
+            KeepWhich::Highest => assigned.min(self.dice - self.reroll_lowest),
+            KeepWhich::Lowest => assigned.saturating_sub(self.reroll_lowest),
+        }
+    }
+
+    fn kept(&self, originals: usize, fresh: usize) -> usize {
+        (self.eligible(originals) + fresh).min(self.keep)
+    }
+}
+
+/// Distribution of the kept sum, indexed by sum
+///
+/// Walks die values in keep order (highest first for keep-highest), choosing
+/// how many originals and fresh dice take each value. The state is the number
+/// of originals and fresh dice assigned so far plus the kept sum. Once all
+/// kept slots are filled, the remaining dice can only take later values, which
+/// happens with probability (remaining mass)^(remaining dice).
+fn kept_sum_distribution(pool: &PoolSpec, originals: &[f64], fresh: &[f64]) -> Vec<f64> {
+    let n = pool.dice;
+    let c = pool.reroll_lowest;
+    let len = originals.len().max(fresh.len());
+    let p = |v: usize| originals.get(v).copied().unwrap_or(0.0);
+    let q = |v: usize| fresh.get(v).copied().unwrap_or(0.0);
+
+    let mut order: Vec<usize> = (1..len).filter(|&v| p(v) > 0.0 || q(v) > 0.0).collect();
+    if pool.which == KeepWhich::Highest {
+        order.reverse();
+    }
+
+    // Mass of the values after each position in the walk
+    let mut rest_p = vec![0.0; order.len()];
+    let mut rest_q = vec![0.0; order.len()];
+    for pos in (0..order.len().saturating_sub(1)).rev() {
+        rest_p[pos] = rest_p[pos + 1] + p(order[pos + 1]);
+        rest_q[pos] = rest_q[pos + 1] + q(order[pos + 1]);
+    }
+
+    let binomial = binomial_table(n.max(c));
+    let mut result = vec![0.0; pool.keep * len + 1];
+    let mut states = vec![vec![Vec::<f64>::new(); c + 1]; n + 1];
+    states[0][0] = vec![1.0];
+
+    for (pos, &v) in order.iter().enumerate() {
+        let mut next = vec![vec![Vec::<f64>::new(); c + 1]; n + 1];
+
+        for a in 0..=n {
+            for b in 0..=c {
+                let current = std::mem::take(&mut states[a][b]);
+                if current.is_empty() {
+                    continue;
+                }
+                let kept = pool.kept(a, b);
+
+                for i in 0..=(n - a) {
+                    let weight_i = binomial[n - a][i] * p(v).powi(i as i32);
+                    if weight_i == 0.0 {
+                        break;
+                    }
+                    for j in 0..=(c - b) {
+                        let weight = weight_i * binomial[c - b][j] * q(v).powi(j as i32);
+                        if weight == 0.0 {
+                            break;
+                        }
+
+                        let new_kept = pool.kept(a + i, b + j);
+                        let added = (new_kept - kept) * v;
+
+                        if new_kept == pool.keep {
+                            // Every kept slot is filled: the rest only needs later values
+                            let tail = weight
+                                * rest_p[pos].powi((n - a - i) as i32)
+                                * rest_q[pos].powi((c - b - j) as i32);
+                            for (sum, &prob) in current.iter().enumerate() {
+                                result[sum + added] += prob * tail;
+                            }
+                        } else {
+                            let target = &mut next[a + i][b + j];
+                            if target.len() < current.len() + added {
+                                target.resize(current.len() + added, 0.0);
+                            }
+                            for (sum, &prob) in current.iter().enumerate() {
+                                target[sum + added] += prob * weight;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        states = next;
+    }
+
+    result
+}
+
+fn binomial_table(n: usize) -> Vec<Vec<f64>> {
+    let mut table = vec![vec![0.0; n + 1]; n + 1];
+    for i in 0..=n {
+        table[i][0] = 1.0;
+        for j in 1..=i {
+            table[i][j] = table[i - 1][j - 1] + if j < i { table[i - 1][j] } else { 0.0 };
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExplosionMode;
+    use crate::pipeline::RollPipeline;
+
+    fn mean(pmf: &BTreeMap<i32, f64>) -> f64 {
+        pmf.iter().map(|(&v, &p)| v as f64 * p).sum()
+    }
+
+    fn die_mean(explosion: ExplosionRule) -> f64 {
+        DieDistribution::new(explosion).pmf().iter().enumerate().map(|(v, p)| v as f64 * p).sum()
+    }
+
+    #[test]
+    fn test_single_die_means() {
+        // Unskilled d10: 5.5
+        assert!((die_mean(ExplosionMode::Unskilled.rule()) - 5.5).abs() < 1e-9);
+        // Skilled: 5.5 / (1 - 0.1) = 6.111...
+        assert!((die_mean(ExplosionMode::Skilled.rule()) - 55.0 / 9.0).abs() < 1e-6);
+        // Mastery: 5.5 / (1 - 0.2) = 6.875
+        assert!((die_mean(ExplosionMode::Mastery.rule()) - 6.875).abs() < 1e-6);
+        // Explode once on 10: 5.5 + 0.1 × 5.5
+        let once = ExplosionRule::new(Some(10), Some(1)).unwrap();
+        assert!((die_mean(once) - 6.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_emphasis_single_die() {
+        // Emphasis on an unskilled 1k1: the 1 is rerolled, so P(1) = 0.01
+        let config = RollConfig::new(1, 1, ExplosionMode::Unskilled, true).unwrap();
+        let pmf = exact_distribution(&config);
+
+        assert!((pmf[&1] - 0.01).abs() < 1e-12);
+        assert!((pmf[&2] - 0.11).abs() < 1e-12);
+        assert!((pmf.values().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_keep_highest_two_dice() {
+        // 2k1 unskilled: P(max = v) = (v² - (v-1)²) / 100
+        let config = RollConfig::new(2, 1, ExplosionMode::Unskilled, false).unwrap();
+        let pmf = exact_distribution(&config);
+
+        for v in 1..=10 {
+            let expected = (v * v - (v - 1) * (v - 1)) as f64 / 100.0;
+            assert!((pmf[&v] - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_keep_lowest_two_dice() {
+        // 2k1 unskilled keep lowest: P(min = v) = ((11-v)² - (10-v)²) / 100
+        let config = RollConfig::new(2, 1, ExplosionMode::Unskilled, false).unwrap()
+            .with_pipeline(RollPipeline::new(vec![Stage::Keep { which: KeepWhich::Lowest }]).unwrap());
+        let pmf = exact_distribution(&config);
+
+        for v in 1..=10 {
+            let expected = ((11 - v) * (11 - v) - (10 - v) * (10 - v)) as f64 / 100.0;
+            assert!((pmf[&v] - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_reroll_lowest_matches_enumeration() {
+        // 2k2 unskilled, reroll the lowest die: enumerate all 1000 outcomes
+        let config = RollConfig::new(2, 2, ExplosionMode::Unskilled, false).unwrap()
+            .with_pipeline(RollPipeline::new(vec![
+                Stage::RerollLowest { count: 1 },
+                Stage::KeptDieBonus { bonus: 1 },
+            ]).unwrap());
+        let pmf = exact_distribution(&config);
+
+        let mut expected: BTreeMap<i32, f64> = BTreeMap::new();
+        for a in 1..=10 {
+            for b in 1..=10 {
+                for fresh in 1..=10 {
+                    *expected.entry(a.max(b) + fresh + 2).or_insert(0.0) += 0.001;
+                }
+            }
+        }
+
+        assert_eq!(pmf.len(), expected.len());
+        for (total, p) in expected {
+            assert!((pmf[&total] - p).abs() < 1e-12, "total {}", total);
+        }
+    }
+
+    #[test]
+    fn test_exact_sums_to_one() {
+        let config = RollConfig::new(10, 4, ExplosionMode::Mastery, true).unwrap();
+        let pmf = exact_distribution(&config);
+
+        // Only the truncated explosion tails are missing
+        assert!((pmf.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        // The best four of ten mastery dice sit well above 4 × 5.5
+        assert!(mean(&pmf) > 30.0);
+    }
+
+    #[test]
+    fn test_exact_matches_simulation() {
+        // Every stage kind at once, on an exploding pool
+        let config = RollConfig::new(4, 2, ExplosionMode::Skilled, true).unwrap()
+            .with_pipeline(RollPipeline::new(vec![
+                Stage::Remap { from: 2, to: 3 },
+                Stage::RerollLowest { count: 1 },
+                Stage::KeptDieBonus { bonus: 1 },
+                Stage::Modifier { bonus: 2 },
+            ]).unwrap());
+
+        let pmf = exact_distribution(&config);
+        let histogram = crate::dice::simulate_roll_xky(&config);
+        let rounds = config.simulation_rounds() as f64;
+
+        let exact_mean = mean(&pmf);
+        let variance: f64 = pmf.iter().map(|(&v, &p)| (v as f64 - exact_mean).powi(2) * p).sum();
+        let simulated_mean = histogram.iter().map(|(&v, &c)| v as f64 * c as f64).sum::<f64>() / rounds;
+
+        // Within 5 standard errors of the exact mean
+        assert!((simulated_mean - exact_mean).abs() < 5.0 * (variance / rounds).sqrt());
+    }
+}
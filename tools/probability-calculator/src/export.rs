@@ -0,0 +1,409 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use parquet::basic::Compression;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int32Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Engine;
+use crate::error::Result;
+use crate::pipeline::RollPipeline;
+use crate::schema::{round_to, ProbabilityTableV2, ProbabilityTablesV2};
+
+/// z-score of the 95% confidence intervals
+const Z_95: f64 = 1.959963984540054;
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!("Unknown export format '{}' (expected csv or parquet)", s)),
+        }
+    }
+}
+
+/// One TN of one table, in long format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbabilityRow {
+    pub roll: u8,
+    pub keep: u8,
+    /// Preset name, or the custom explosion rule
+    pub mode: String,
+    pub emphasis: bool,
+    /// Pipeline summary, empty for standard rolls
+    pub pipeline: String,
+    pub tn: i32,
+    /// P(total ≥ TN)
+    pub at_least: f64,
+    /// P(total = TN), rounded to the tables' precision
+    pub pmf: f64,
+    /// 95% Wilson interval of `at_least`; empty for exact tables
+    pub ci_lower: Option<f64>,
+    pub ci_upper: Option<f64>,
+}
+
+/// Statistics of one table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatisticsRow {
+    pub roll: u8,
+    pub keep: u8,
+    pub mode: String,
+    pub emphasis: bool,
+    pub pipeline: String,
+    pub mean: f64,
+    pub stddev: f64,
+    pub median: i32,
+    pub percentile_25: i32,
+    pub percentile_75: i32,
+    pub min: i32,
+    pub max: i32,
+    /// Goodness of fit to the exact distribution; empty for exact tables
+    pub chi_square_p_value: Option<f64>,
+    pub ks_p_value: Option<f64>,
+}
+
+/// 95% Wilson score interval of a proportion `p` estimated from `n` rolls
+pub fn wilson_interval(p: f64, n: usize) -> (f64, f64) {
+    let n = n as f64;
+    let z2 = Z_95 * Z_95;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let half_width = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+    ((center - half_width).max(0.0), (center + half_width).min(1.0))
+}
+
+fn pipeline_label(table: &ProbabilityTableV2) -> String {
+    RollPipeline { stages: table.pipeline.clone() }.describe()
+}
+
+/// Long-format rows: one per table and TN
+pub fn probability_rows(tables: &ProbabilityTablesV2) -> Vec<ProbabilityRow> {
+    let simulated = tables.engine == Engine::Simulation;
+    let mut rows = Vec::new();
+
+    for table in &tables.tables {
+        let mode = table.explosion.to_string();
+        let pipeline = pipeline_label(table);
+        let rounds = tables.simulation_rounds.for_rule(table.explosion);
+
+        for (i, &at_least) in table.at_least.iter().enumerate() {
+            let next = table.at_least.get(i + 1).copied().unwrap_or(0.0);
+            let ci = simulated.then(|| wilson_interval(at_least, rounds));
+            rows.push(ProbabilityRow {
+                roll: table.roll,
+                keep: table.keep,
+                mode: mode.clone(),
+                emphasis: table.emphasis,
+                pipeline: pipeline.clone(),
+                tn: table.min_tn + i as i32,
+                at_least,
+                pmf: round_to((at_least - next).max(0.0), tables.precision),
+                ci_lower: ci.map(|(lower, _)| lower),
+                ci_upper: ci.map(|(_, upper)| upper),
+            });
+        }
+    }
+    rows
+}
+
+/// One statistics row per table
+pub fn statistics_rows(tables: &ProbabilityTablesV2) -> Vec<StatisticsRow> {
+    tables.tables.iter()
+        .map(|table| StatisticsRow {
+            roll: table.roll,
+            keep: table.keep,
+            mode: table.explosion.to_string(),
+            emphasis: table.emphasis,
+            pipeline: pipeline_label(table),
+            mean: table.statistics.mean,
+            stddev: table.statistics.stddev,
+            median: table.statistics.median,
+            percentile_25: table.statistics.percentile_25,
+            percentile_75: table.statistics.percentile_75,
+            min: table.statistics.min,
+            max: table.statistics.max,
+            chi_square_p_value: table.goodness_of_fit.as_ref().map(|f| f.chi_square_p_value),
+            ks_p_value: table.goodness_of_fit.as_ref().map(|f| f.ks_p_value),
+        })
+        .collect()
+}
+
+/// Write rows as CSV with a header
+pub fn write_csv<P: AsRef<Path>, T: Serialize>(path: P, rows: &[T]) -> Result<()> {
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = csv::Writer::from_path(path)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// A column of a Parquet file
+pub enum Column {
+    Int32(Vec<i32>),
+    Boolean(Vec<bool>),
+    Double(Vec<f64>),
+    OptionalDouble(Vec<Option<f64>>),
+    Text(Vec<String>),
+}
+
+impl Column {
+    /// Field declaration in a Parquet message type
+    fn field(&self, name: &str) -> String {
+        match self {
+            Column::Int32(_) => format!("REQUIRED INT32 {};", name),
+            Column::Boolean(_) => format!("REQUIRED BOOLEAN {};", name),
+            Column::Double(_) => format!("REQUIRED DOUBLE {};", name),
+            Column::OptionalDouble(_) => format!("OPTIONAL DOUBLE {};", name),
+            Column::Text(_) => format!("REQUIRED BYTE_ARRAY {} (UTF8);", name),
+        }
+    }
+}
+
+/// Rows that can be written column by column
+pub trait Columnar: Sized {
+    /// Parquet message name
+    const NAME: &'static str;
+
+    fn columns(rows: &[Self]) -> Vec<(&'static str, Column)>;
+}
+
+impl Columnar for ProbabilityRow {
+    const NAME: &'static str = "probability_row";
+
+    fn columns(rows: &[Self]) -> Vec<(&'static str, Column)> {
+        vec![
+            ("roll", Column::Int32(rows.iter().map(|r| r.roll as i32).collect())),
+            ("keep", Column::Int32(rows.iter().map(|r| r.keep as i32).collect())),
+            ("mode", Column::Text(rows.iter().map(|r| r.mode.clone()).collect())),
+            ("emphasis", Column::Boolean(rows.iter().map(|r| r.emphasis).collect())),
+            ("pipeline", Column::Text(rows.iter().map(|r| r.pipeline.clone()).collect())),
+            ("tn", Column::Int32(rows.iter().map(|r| r.tn).collect())),
+            ("at_least", Column::Double(rows.iter().map(|r| r.at_least).collect())),
+            ("pmf", Column::Double(rows.iter().map(|r| r.pmf).collect())),
+            ("ci_lower", Column::OptionalDouble(rows.iter().map(|r| r.ci_lower).collect())),
+            ("ci_upper", Column::OptionalDouble(rows.iter().map(|r| r.ci_upper).collect())),
+        ]
+    }
+}
+
+impl Columnar for StatisticsRow {
+    const NAME: &'static str = "statistics_row";
+
+    fn columns(rows: &[Self]) -> Vec<(&'static str, Column)> {
+        vec![
+            ("roll", Column::Int32(rows.iter().map(|r| r.roll as i32).collect())),
+            ("keep", Column::Int32(rows.iter().map(|r| r.keep as i32).collect())),
+            ("mode", Column::Text(rows.iter().map(|r| r.mode.clone()).collect())),
+            ("emphasis", Column::Boolean(rows.iter().map(|r| r.emphasis).collect())),
+            ("pipeline", Column::Text(rows.iter().map(|r| r.pipeline.clone()).collect())),
+            ("mean", Column::Double(rows.iter().map(|r| r.mean).collect())),
+            ("stddev", Column::Double(rows.iter().map(|r| r.stddev).collect())),
+            ("median", Column::Int32(rows.iter().map(|r| r.median).collect())),
+            ("percentile_25", Column::Int32(rows.iter().map(|r| r.percentile_25).collect())),
+            ("percentile_75", Column::Int32(rows.iter().map(|r| r.percentile_75).collect())),
+            ("min", Column::Int32(rows.iter().map(|r| r.min).collect())),
+            ("max", Column::Int32(rows.iter().map(|r| r.max).collect())),
+            ("chi_square_p_value", Column::OptionalDouble(rows.iter().map(|r| r.chi_square_p_value).collect())),
+            ("ks_p_value", Column::OptionalDouble(rows.iter().map(|r| r.ks_p_value).collect())),
+        ]
+    }
+}
+
+/// Write rows as a Snappy-compressed Parquet file (one row group)
+pub fn write_parquet<P: AsRef<Path>, T: Columnar>(path: P, rows: &[T]) -> Result<()> {
+    let columns = T::columns(rows);
+    let fields: Vec<String> = columns.iter().map(|(name, column)| column.field(name)).collect();
+    let schema = Arc::new(parse_message_type(&format!("message {} {{ {} }}", T::NAME, fields.join(" ")))?);
+    let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+    let mut columns = columns.into_iter();
+
+    while let Some(mut column_writer) = row_group.next_column()? {
+        let (_, column) = columns.next().expect("one column per field");
+        match column {
+            Column::Int32(values) => {
+                column_writer.typed::<Int32Type>().write_batch(&values, None, None)?;
+            }
+            Column::Boolean(values) => {
+                column_writer.typed::<BoolType>().write_batch(&values, None, None)?;
+            }
+            Column::Double(values) => {
+                column_writer.typed::<DoubleType>().write_batch(&values, None, None)?;
+            }
+            Column::OptionalDouble(values) => {
+                // Definition level 1 marks a present value; only those are written
+                let levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+                let present: Vec<f64> = values.into_iter().flatten().collect();
+                column_writer.typed::<DoubleType>().write_batch(&present, Some(&levels), None)?;
+            }
+            Column::Text(values) => {
+                let values: Vec<ByteArray> = values.iter().map(|v| ByteArray::from(v.as_str())).collect();
+                column_writer.typed::<ByteArrayType>().write_batch(&values, None, None)?;
+            }
+        }
+        column_writer.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Write probabilities.<ext> and statistics.<ext> to `dir`, returning their paths
+pub fn export_tables<P: AsRef<Path>>(tables: &ProbabilityTablesV2, dir: P, format: ExportFormat) -> Result<[String; 2]> {
+    let dir = dir.as_ref();
+    let probabilities = dir.join(format!("probabilities.{}", format.extension()));
+    let statistics = dir.join(format!("statistics.{}", format.extension()));
+
+    match format {
+        ExportFormat::Csv => {
+            write_csv(&probabilities, &probability_rows(tables))?;
+            write_csv(&statistics, &statistics_rows(tables))?;
+        }
+        ExportFormat::Parquet => {
+            write_parquet(&probabilities, &probability_rows(tables))?;
+            write_parquet(&statistics, &statistics_rows(tables))?;
+        }
+    }
+    Ok([probabilities.display().to_string(), statistics.display().to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExplosionMode, RollConfig};
+    use crate::output::fixture_tables_v2;
+    use crate::pipeline::Stage;
+    use crate::schema::MAX_PRECISION;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::{Field, RowAccessor};
+
+    fn sample_tables(engine: Engine) -> ProbabilityTablesV2 {
+        let pipeline = RollPipeline::new(vec![Stage::Modifier { bonus: 2 }]).unwrap();
+        let configs = [
+            RollConfig::new(1, 1, ExplosionMode::Unskilled, false).unwrap(),
+            RollConfig::new(3, 2, ExplosionMode::Skilled, true).unwrap().with_pipeline(pipeline),
+        ];
+        fixture_tables_v2(&configs, engine, MAX_PRECISION)
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("l5r-export-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_probability_rows() {
+        let tables = sample_tables(Engine::Exact);
+        let rows = probability_rows(&tables);
+        let one = rows.iter().filter(|r| r.roll == 1).collect::<Vec<_>>();
+
+        // 1k1: TN 0 to 10, each face 10%
+        assert_eq!(one.len(), 11);
+        assert_eq!((one[0].tn, one[10].tn), (0, 10));
+        assert!(one[1..].iter().all(|r| (r.pmf - 0.1).abs() < 1e-12));
+        assert!((one[10].at_least - 0.1).abs() < 1e-12);
+        assert!(one.iter().all(|r| r.ci_lower.is_none() && r.mode == "unskilled"));
+
+        // The PMF of every table sums to 1
+        let total: f64 = rows.iter().filter(|r| r.roll == 3).map(|r| r.pmf).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!(rows.iter().any(|r| r.pipeline == "+2" && r.emphasis));
+    }
+
+    #[test]
+    fn test_confidence_intervals() {
+        let (lower, upper) = wilson_interval(0.5, 10_000);
+        assert!((lower - 0.4902).abs() < 1e-4 && (upper - 0.5098).abs() < 1e-4);
+        // Certain and impossible TNs keep an interval on the open side only
+        assert!((wilson_interval(1.0, 100).1 - 1.0).abs() < 1e-12 && wilson_interval(1.0, 100).0 < 0.97);
+        assert!(wilson_interval(0.0, 100).0.abs() < 1e-12 && wilson_interval(0.0, 100).1 > 0.03);
+
+        let rows = probability_rows(&sample_tables(Engine::Simulation));
+        assert!(rows.iter().all(|r| r.ci_lower.unwrap() <= r.at_least + 1e-12 && r.at_least <= r.ci_upper.unwrap() + 1e-12));
+    }
+
+    #[test]
+    fn test_intervals_use_recorded_rounds() {
+        // Tables run with --extra-rounds record more rounds than the preset default
+        let mut tables = sample_tables(Engine::Simulation);
+        let rounds = 4 * ExplosionMode::Unskilled.simulation_rounds();
+        tables.simulation_rounds.unskilled = rounds;
+
+        let rows = probability_rows(&tables);
+        let row = rows.iter().find(|r| r.roll == 1 && r.tn == 5).unwrap();
+        assert_eq!((row.ci_lower.unwrap(), row.ci_upper.unwrap()), wilson_interval(row.at_least, rounds));
+        let (lower, upper) = wilson_interval(row.at_least, ExplosionMode::Unskilled.simulation_rounds());
+        assert!(row.ci_upper.unwrap() - row.ci_lower.unwrap() < upper - lower);
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let tables = sample_tables(Engine::Simulation);
+        let dir = temp_dir("csv");
+        let [probabilities, statistics] = export_tables(&tables, &dir, ExportFormat::Csv).unwrap();
+
+        let mut reader = csv::Reader::from_path(&probabilities).unwrap();
+        let rows: Vec<ProbabilityRow> = reader.deserialize().map(|r| r.unwrap()).collect();
+        assert_eq!(rows, probability_rows(&tables));
+
+        let header = std::fs::read_to_string(&statistics).unwrap();
+        assert!(header.starts_with("roll,keep,mode,emphasis,pipeline,mean,stddev,median,"));
+        assert_eq!(header.lines().count(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parquet_export() {
+        let tables = sample_tables(Engine::Exact);
+        let dir = temp_dir("parquet");
+        let [probabilities, statistics] = export_tables(&tables, &dir, ExportFormat::Parquet).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&probabilities).unwrap()).unwrap();
+        let expected = probability_rows(&tables);
+        assert_eq!(reader.metadata().file_metadata().num_rows() as usize, expected.len());
+
+        // Read the rows back and compare a few fields
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().map(|r| r.unwrap()).collect();
+        let last = rows.last().unwrap();
+        assert_eq!(last.get_int(5).unwrap(), expected.last().unwrap().tn);
+        assert_eq!(last.get_string(2).unwrap(), "skilled");
+        assert_eq!(last.get_string(4).unwrap(), "+2");
+        assert_eq!(last.get_double(6).unwrap(), expected.last().unwrap().at_least);
+        assert!(matches!(last.get_column_iter().nth(8), Some((_, Field::Null))));
+
+        let reader = SerializedFileReader::new(File::open(&statistics).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
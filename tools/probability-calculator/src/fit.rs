@@ -0,0 +1,356 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::RollConfig;
+use crate::error::ValidationError;
+use crate::exact;
+
+/// p-value below which a simulated histogram is rejected
+///
+/// Low enough that a full run of several hundred tables essentially never
+/// rejects a correct roller, while real rule drift gives p-values far below it.
+pub const FIT_ALPHA: f64 = 1e-6;
+
+/// Longest explosion chain (in exact engine levels) a simulated table is fitted for
+///
+/// The fit needs the exact distribution, whose cost grows quickly with the
+/// chain length: thresholds of 4 and below would take longer than the
+/// simulation itself, so their tables are published without a fit.
+pub const MAX_FIT_CHAIN_LEVELS: usize = 60;
+
+/// Minimum expected count per chi-square bin
+const MIN_EXPECTED: f64 = 5.0;
+
+/// Goodness of fit of a simulated histogram to the exact distribution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GoodnessOfFit {
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    pub chi_square_p_value: f64,
+    /// Largest difference between the empirical and exact CDFs
+    pub ks_statistic: f64,
+    pub ks_p_value: f64,
+}
+
+impl GoodnessOfFit {
+    /// Whether simulated tables of a config are fitted (see [`MAX_FIT_CHAIN_LEVELS`])
+    pub fn applies(config: &RollConfig) -> bool {
+        exact::chain_levels(config.explosion) <= MAX_FIT_CHAIN_LEVELS
+    }
+
+    /// Compare a histogram of `total_count` rolls with an exact distribution
+    pub fn compare(histogram: &HashMap<i32, usize>, total_count: usize, pmf: &BTreeMap<i32, f64>) -> Self {
+        let n = total_count as f64;
+        let values: BTreeSet<i32> = histogram.keys().chain(pmf.keys()).copied().collect();
+        let observed = |v: &i32| histogram.get(v).copied().unwrap_or(0) as f64;
+        let expected = |v: &i32| pmf.get(v).copied().unwrap_or(0.0) * n;
+
+        // Chi-square over adjacent values merged until each bin expects ≥ 5
+        let mut bins: Vec<(f64, f64)> = Vec::new();
+        let mut current = (0.0, 0.0);
+        for v in &values {
+            current.0 += observed(v);
+            current.1 += expected(v);
+            if current.1 >= MIN_EXPECTED {
+                bins.push(current);
+                current = (0.0, 0.0);
+            }
+        }
+        // Leftover tail joins the last bin
+        match bins.last_mut() {
+            Some(last) => {
+                last.0 += current.0;
+                last.1 += current.1;
+            }
+            None => bins.push(current),
+        }
+
+        let chi_square: f64 = bins.iter()
+            .filter(|(_, e)| *e > 0.0)
+            .map(|(o, e)| (o - e) * (o - e) / e)
+            .sum();
+        let degrees_of_freedom = bins.len().saturating_sub(1);
+        let chi_square_p_value = match degrees_of_freedom {
+            0 => 1.0,
+            df => chi_square_survival(chi_square, df),
+        };
+
+        // Kolmogorov-Smirnov over the same support
+        let mut empirical_cdf = 0.0;
+        let mut exact_cdf = 0.0;
+        let mut ks_statistic: f64 = 0.0;
+        for v in &values {
+            empirical_cdf += observed(v) / n;
+            exact_cdf += expected(v) / n;
+            ks_statistic = ks_statistic.max((empirical_cdf - exact_cdf).abs());
+        }
+
+        Self {
+            chi_square,
+            degrees_of_freedom,
+            chi_square_p_value,
+            ks_statistic,
+            ks_p_value: ks_survival(ks_statistic, total_count),
+        }
+    }
+
+    /// Errors for tests that reject the fit at [`FIT_ALPHA`]
+    pub fn errors(&self) -> Vec<ValidationError> {
+        [
+            ("chi_square", self.chi_square, self.chi_square_p_value),
+            ("kolmogorov_smirnov", self.ks_statistic, self.ks_p_value),
+        ]
+        .into_iter()
+        .filter(|&(_, _, p_value)| p_value < FIT_ALPHA)
+        .map(|(test, statistic, p_value)| ValidationError::PoorFit { test: test.to_string(), statistic, p_value })
+        .collect()
+    }
+}
+
+/// P(X ≥ x) for a chi-square distribution with `df` degrees of freedom
+pub fn chi_square_survival(x: f64, df: usize) -> f64 {
+    upper_regularized_gamma(df as f64 / 2.0, x / 2.0)
+}
+
+/// Asymptotic p-value of a one-sample KS statistic `d` over `n` samples
+///
+/// Uses Stephens' small-sample correction. For discrete distributions the
+/// test is conservative: p-values are larger than the exact ones.
+pub fn ks_survival(d: f64, n: usize) -> f64 {
+    let sqrt_n = (n as f64).sqrt();
+    let lambda = (sqrt_n + 0.12 + 0.11 / sqrt_n) * d;
+    if lambda < 0.2 {
+        return 1.0;
+    }
+
+    // Q(λ) = 2 Σ (-1)^(k-1) exp(-2k²λ²)
+    let mut sum = 0.0;
+    for k in 1..=100 {
+        let k = k as f64;
+        let term = (-2.0 * k * k * lambda * lambda).exp();
+        sum += if k as u32 % 2 == 1 { term } else { -term };
+        if term < 1e-16 {
+            break;
+        }
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// Wald-Wolfowitz runs test of a sequence above and below `median`
+///
+/// Values equal to the median are dropped. Returns the z score and the
+/// two-sided p-value: too few runs means streaks, too many means alternation.
+/// `None` when every value falls on one side.
+pub fn runs_test(values: &[i32], median: i32) -> Option<(f64, f64)> {
+    let signs: Vec<bool> = values.iter().filter(|&&v| v != median).map(|&v| v > median).collect();
+    let above = signs.iter().filter(|&&s| s).count() as f64;
+    let below = signs.len() as f64 - above;
+    if above == 0.0 || below == 0.0 {
+        return None;
+    }
+
+    let runs = 1 + signs.windows(2).filter(|w| w[0] != w[1]).count();
+    let n = above + below;
+    let expected = 2.0 * above * below / n + 1.0;
+    let variance = 2.0 * above * below * (2.0 * above * below - n) / (n * n * (n - 1.0));
+    if variance <= 0.0 {
+        return None;
+    }
+
+    let z = (runs as f64 - expected) / variance.sqrt();
+    // P(|Z| ≥ z) for a standard normal is P(χ²(1) ≥ z²)
+    Some((z, chi_square_survival(z * z, 1)))
+}
+
+/// Benjamini-Hochberg adjusted p-values, in the order given
+///
+/// A test is significant at false discovery rate q when its adjusted
+/// p-value is below q.
+pub fn benjamini_hochberg(p_values: &[f64]) -> Vec<f64> {
+    let m = p_values.len();
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+
+    // Step down from the largest p-value, keeping the running minimum
+    let mut adjusted = vec![0.0; m];
+    let mut running_min: f64 = 1.0;
+    for (rank, &index) in order.iter().enumerate().rev() {
+        running_min = running_min.min(p_values[index] * m as f64 / (rank + 1) as f64);
+        adjusted[index] = running_min;
+    }
+    adjusted
+}
+
+/// Q(a, x) = Γ(a, x) / Γ(a)
+///
+/// Series expansion below a + 1, continued fraction above (Numerical Recipes).
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+
+    if x < a + 1.0 {
+        // P(a, x) = e^-x x^a / Γ(a) Σ x^n / (a (a+1) ... (a+n))
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denominator = a;
+        for _ in 0..1000 {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * log_prefix.exp()).max(0.0)
+    } else {
+        // Lentz's method for the continued fraction of Γ(a, x)
+        const TINY: f64 = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (log_prefix.exp() * h).min(1.0)
+    }
+}
+
+/// ln Γ(x) for x > 0 (Lanczos approximation, g = 7)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection: Γ(x) Γ(1-x) = π / sin(πx)
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS.iter()
+        .enumerate()
+        .skip(1)
+        .fold(COEFFICIENTS[0], |sum, (i, &c)| sum + c / (x + i as f64));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExplosionMode, ExplosionRule, RollConfig};
+    use crate::dice::simulate_roll_xky;
+    use crate::exact::exact_distribution;
+
+    #[test]
+    fn test_special_functions() {
+        // ln Γ(n) = ln((n-1)!)
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-12);
+
+        // Chi-square with 2 degrees of freedom: P(X ≥ x) = e^(-x/2)
+        for x in [0.5, 2.0, 10.0, 40.0] {
+            assert!((chi_square_survival(x, 2) - (-x / 2.0).exp()).abs() < 1e-12);
+        }
+        // 95th percentile with 10 degrees of freedom is 18.307
+        assert!((chi_square_survival(18.307, 10) - 0.05).abs() < 1e-4);
+
+        // Kolmogorov distribution: Q(1.358) ≈ 0.05
+        assert!((ks_survival(1.358 / 1e4, 100_000_000) - 0.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_runs_test() {
+        // Strict alternation has too many runs, sorted values too few
+        let alternating: Vec<i32> = (0..100).map(|i| if i % 2 == 0 { 1 } else { 9 }).collect();
+        let (z, p) = runs_test(&alternating, 5).unwrap();
+        assert!(z > 5.0 && p < 1e-6);
+
+        let mut sorted = alternating.clone();
+        sorted.sort();
+        let (z, p) = runs_test(&sorted, 5).unwrap();
+        assert!(z < -5.0 && p < 1e-6);
+
+        // Values on the median are ignored; one side only cannot be tested
+        assert!(runs_test(&[5, 5, 9, 9], 5).is_none());
+    }
+
+    #[test]
+    fn test_benjamini_hochberg() {
+        let adjusted = benjamini_hochberg(&[0.01, 0.04, 0.03, 0.005]);
+        let expected = [0.02, 0.04, 0.04, 0.02];
+        for (a, e) in adjusted.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-12);
+        }
+        assert!(benjamini_hochberg(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_simulation_fits_exact() {
+        for config in [
+            RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap(),
+            RollConfig::new(4, 2, ExplosionMode::Mastery, true).unwrap(),
+        ] {
+            let histogram = simulate_roll_xky(&config);
+            let fit = GoodnessOfFit::compare(&histogram, config.simulation_rounds(), &exact_distribution(&config));
+
+            assert!(fit.degrees_of_freedom > 10);
+            assert!(fit.errors().is_empty(), "{:?}: {:?}", config, fit);
+        }
+    }
+
+    #[test]
+    fn test_fit_applies() {
+        // The presets are fitted; thresholds of 4 and below are skipped unless capped
+        assert!(GoodnessOfFit::applies(&RollConfig::new(10, 10, ExplosionMode::Mastery, false).unwrap()));
+        let rule = |threshold, max_chain| {
+            let explosion = ExplosionRule::new(Some(threshold), max_chain).unwrap();
+            RollConfig::new(5, 3, explosion, false).unwrap()
+        };
+        assert!(GoodnessOfFit::applies(&rule(5, None)));
+        assert!(!GoodnessOfFit::applies(&rule(4, None)));
+        assert!(GoodnessOfFit::applies(&rule(3, Some(2))));
+    }
+
+    #[test]
+    fn test_rule_drift_is_rejected() {
+        // Simulating without emphasis against the exact emphasis distribution
+        let simulated = RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap();
+        let exact = RollConfig::new(3, 2, ExplosionMode::Skilled, true).unwrap();
+
+        let histogram = simulate_roll_xky(&simulated);
+        let fit = GoodnessOfFit::compare(&histogram, simulated.simulation_rounds(), &exact_distribution(&exact));
+
+        assert!(fit.chi_square_p_value < 1e-12);
+        assert!(fit.ks_p_value < 1e-12);
+        assert_eq!(fit.errors().len(), 2);
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use jsonschema::paths::{Location, LocationSegment};
+use schemars::schema_for;
+use serde_json::Value;
+
+use crate::error::ValidationError;
+use crate::output::ProbabilityTables;
+use crate::schema::ProbabilityTablesV2;
+
+/// JSON Schema of the v1 tables (probability-tables.json)
+pub fn tables_schema() -> Value {
+    schema_for!(ProbabilityTables).to_value()
+}
+
+/// JSON Schema of the v2 tables
+pub fn tables_v2_schema() -> Value {
+    schema_for!(ProbabilityTablesV2).to_value()
+}
+
+/// Check a JSON document against a schema, with the `jsonschema` crate
+///
+/// Paths look like `$.tables[3].roll`; an invalid schema is reported at `$`.
+pub fn schema_errors(schema: &Value, instance: &Value) -> Vec<ValidationError> {
+    let validator = match jsonschema::validator_for(schema) {
+        Ok(validator) => validator,
+        Err(e) => {
+            return vec![ValidationError::SchemaViolation { path: "$".to_string(), message: format!("invalid schema: {}", e) }]
+        }
+    };
+    validator
+        .iter_errors(instance)
+        .map(|e| ValidationError::SchemaViolation { path: path(&e.instance_path), message: e.to_string() })
+        .collect()
+}
+
+/// `$.tables[3].roll` for the JSON pointer `/tables/3/roll`
+fn path(location: &Location) -> String {
+    location.into_iter().fold("$".to_string(), |path, segment| match segment {
+        LocationSegment::Property(name) => format!("{}.{}", path, name),
+        LocationSegment::Index(index) => format!("{}[{}]", path, index),
+    })
+}
+
+/// TypeScript declarations for the types of one or more schemas
+///
+/// Each root schema becomes a declaration named by its `title`, and each
+/// entry of `$defs` one named by its key (shared definitions once). Objects
+/// become interfaces, everything else type aliases; doc comments on the
+/// Rust types are kept as JSDoc.
+pub fn typescript_declarations(schemas: &[Value]) -> String {
+    let mut declarations: BTreeMap<String, &Value> = BTreeMap::new();
+    let mut roots = Vec::new();
+    for schema in schemas {
+        if let Some(title) = schema.get("title").and_then(Value::as_str) {
+            roots.push(title.to_string());
+            declarations.insert(title.to_string(), schema);
+        }
+        for (name, definition) in schema.get("$defs").and_then(Value::as_object).into_iter().flatten() {
+            declarations.insert(name.clone(), definition);
+        }
+    }
+
+    let mut out = String::from(
+        "// Generated by tools/probability-calculator (`cargo run -- schema`) from the Rust\n\
+         // output types. Do not edit by hand.\n",
+    );
+    // Roots first, then the definitions they use
+    let order = roots.iter().cloned()
+        .chain(declarations.keys().filter(|name| !roots.contains(name)).cloned())
+        .collect::<Vec<_>>();
+    for name in order {
+        out.push('\n');
+        out.push_str(&declaration(&name, declarations[&name]));
+    }
+    out
+}
+
+fn declaration(name: &str, schema: &Value) -> String {
+    let mut out = jsdoc(schema, "");
+    match schema.get("properties").and_then(Value::as_object) {
+        Some(properties) if schema.get("type").and_then(Value::as_str) == Some("object") => {
+            out.push_str(&format!("export interface {} {{\n", name));
+            let required = required(schema);
+            for (property, sub) in properties {
+                out.push_str(&jsdoc(sub, "  "));
+                let optional = if required.contains(&property.as_str()) { "" } else { "?" };
+                out.push_str(&format!("  {}{}: {}\n", property_name(property), optional, ts_type(sub)));
+            }
+            out.push_str("}\n");
+        }
+        _ => out.push_str(&format!("export type {} = {}\n", name, ts_type(schema))),
+    }
+    out
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).collect()
+}
+
+fn jsdoc(schema: &Value, indent: &str) -> String {
+    let Some(description) = schema.get("description").and_then(Value::as_str) else {
+        return String::new();
+    };
+    let lines: Vec<&str> = description.lines().collect();
+    if lines.len() == 1 {
+        return format!("{}/** {} */\n", indent, lines[0]);
+    }
+    let mut out = format!("{}/**\n", indent);
+    for line in lines {
+        out.push_str(&format!("{} *{}{}\n", indent, if line.is_empty() { "" } else { " " }, line));
+    }
+    out.push_str(&format!("{} */\n", indent));
+    out
+}
+
+fn property_name(name: &str) -> String {
+    let identifier = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if identifier { name.to_string() } else { format!("'{}'", name) }
+}
+
+fn literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\'', "\\'")),
+        other => other.to_string(),
+    }
+}
+
+/// Join alternatives, dropping duplicates
+fn union(types: impl IntoIterator<Item = String>) -> String {
+    let mut seen: Vec<String> = Vec::new();
+    for t in types {
+        if !seen.contains(&t) {
+            seen.push(t);
+        }
+    }
+    seen.join(" | ")
+}
+
+fn ts_type(schema: &Value) -> String {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        _ => return "unknown".to_string(),
+    };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference.rsplit('/').next().unwrap_or(reference).to_string();
+    }
+    if let Some(value) = schema.get("const") {
+        return literal(value);
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        return union(options.iter().map(literal));
+    }
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(options) = schema.get(keyword).and_then(Value::as_array) {
+            return union(options.iter().map(ts_type));
+        }
+    }
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(t)) => vec![t.as_str()],
+        _ => return "unknown".to_string(),
+    };
+    union(types.into_iter().map(|t| match t {
+        "null" => "null".to_string(),
+        "boolean" => "boolean".to_string(),
+        "integer" | "number" => "number".to_string(),
+        "string" => "string".to_string(),
+        "array" => {
+            let item = schema.get("items").map_or_else(|| "unknown".to_string(), ts_type);
+            if item.contains(' ') { format!("({})[]", item) } else { format!("{}[]", item) }
+        }
+        "object" => object_type(schema),
+        _ => "unknown".to_string(),
+    }))
+}
+
+/// Inline object type: a record for maps, otherwise `{ a: T; b?: U }`
+fn object_type(schema: &serde_json::Map<String, Value>) -> String {
+    match schema.get("properties").and_then(Value::as_object) {
+        Some(properties) => {
+            let required = required(&Value::Object(schema.clone()))
+                .into_iter().map(str::to_string).collect::<Vec<_>>();
+            let members = properties.iter()
+                .map(|(name, sub)| {
+                    let optional = if required.contains(name) { "" } else { "?" };
+                    format!("{}{}: {}", property_name(name), optional, ts_type(sub))
+                })
+                .collect::<Vec<_>>();
+            format!("{{ {} }}", members.join("; "))
+        }
+        None => {
+            let value = schema.get("additionalProperties").map_or_else(|| "unknown".to_string(), ts_type);
+            format!("Record<string, {}>", value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExplosionMode, RollConfig};
+    use crate::output::fixture_tables;
+    use crate::pipeline::{RollPipeline, Stage};
+    use crate::Engine;
+
+    fn sample_tables() -> ProbabilityTables {
+        let pipeline = RollPipeline::new(vec![Stage::Reroll { at_most: 2 }]).unwrap();
+        let configs = [
+            RollConfig::new(2, 2, ExplosionMode::Skilled, true).unwrap().with_pipeline(pipeline),
+            RollConfig::new(2, 1, ExplosionMode::Unskilled, false).unwrap(),
+        ];
+        // Simulated tables carry a goodness of fit
+        fixture_tables(&configs, Engine::Simulation)
+    }
+
+    #[test]
+    fn test_tables_match_schema() {
+        let tables = sample_tables();
+        let json = serde_json::to_value(&tables).unwrap();
+        assert_eq!(schema_errors(&tables_schema(), &json), vec![]);
+
+        let v2 = serde_json::to_value(ProbabilityTablesV2::from_v1(&tables, 6).unwrap()).unwrap();
+        assert_eq!(schema_errors(&tables_v2_schema(), &v2), vec![]);
+        // The versions are not interchangeable
+        assert!(!schema_errors(&tables_schema(), &v2).is_empty());
+    }
+
+    #[test]
+    fn test_published_tables_match_schema() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../packages/core/data/probability-tables.json");
+        let json: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(schema_errors(&tables_schema(), &json), vec![]);
+    }
+
+    #[test]
+    fn test_violations_are_reported() {
+        let schema = tables_schema();
+        let mut json = serde_json::to_value(sample_tables()).unwrap();
+        json["tables"][0]["roll"] = (-1).into();
+        json["tables"][1]["explosion_mode"] = "reckless".into();
+        json["tables"][1]["statistics"].as_object_mut().unwrap().remove("median");
+        json["tables"][0]["pipeline"][0]["stage"] = "explode".into();
+
+        let errors = schema_errors(&schema, &json);
+        let paths: Vec<_> = errors.iter()
+            .map(|e| match e {
+                ValidationError::SchemaViolation { path, .. } => path.as_str(),
+                other => panic!("{:?}", other),
+            })
+            .collect();
+        assert!(paths.contains(&"$.tables[0].roll"), "{:?}", errors);
+        assert!(paths.contains(&"$.tables[1].explosion_mode"), "{:?}", errors);
+        assert!(paths.contains(&"$.tables[1].statistics"), "{:?}", errors);
+        assert!(paths.contains(&"$.tables[0].pipeline[0]"), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_typescript_declarations() {
+        let ts = typescript_declarations(&[tables_schema(), tables_v2_schema()]);
+        assert!(ts.contains("export interface ProbabilityTables {"));
+        assert!(ts.contains("export interface ProbabilityTablesV2 {"));
+        assert!(ts.contains("  cumulative_probability: Record<string, number>\n"));
+        assert!(ts.contains("  at_least: number[]\n"));
+        assert!(ts.contains("export type RuleSet = '4e' | 'legacy' | '7th-sea'\n"));
+        assert!(ts.contains("{ at_most: number; stage: 'reroll' }"));
+        // Shared definitions are declared once
+        assert_eq!(ts.matches("export interface Statistics {").count(), 1);
+    }
+
+    /// The schemas and declarations committed to packages/core must match the
+    /// output types; rerun `cargo run -- schema --typescript ...` after changing them
+    #[test]
+    fn test_committed_schemas_are_current() {
+        let core = concat!(env!("CARGO_MANIFEST_DIR"), "/../../packages/core");
+        for (name, schema) in [
+            ("probability-tables.schema.json", tables_schema()),
+            ("probability-tables.v2.schema.json", tables_v2_schema()),
+        ] {
+            let committed: Value = serde_json::from_str(&std::fs::read_to_string(format!("{}/data/{}", core, name)).unwrap()).unwrap();
+            assert!(committed == schema, "{} is out of date", name);
+        }
+
+        let committed = std::fs::read_to_string(format!("{}/src/types/probability-tables.generated.ts", core)).unwrap();
+        let ts = typescript_declarations(&[tables_schema(), tables_v2_schema()]);
+        assert!(committed == ts, "probability-tables.generated.ts is out of date");
+    }
+}
@@ -0,0 +1,135 @@
+//! L5R Roll & Keep probability calculator
+//!
+//! Computes probability distributions for XkY rolls with explosions,
+//! emphasis and roll pipelines, and writes them as JSON lookup tables.
+//!
+//! - [`config`]: roll configurations, explosion rules and engines
+//! - [`pipeline`]: roll-modifying stages (rerolls, keep lowest, bonuses)
+//! - [`rules`]: rule sets (editions) and the Ten Dice Rule
+//! - [`dice`]: the Monte Carlo roller and the traced roller (`execute_roll`)
+//! - [`exact`]: the exact distribution engine
+//! - [`stats`]: statistics, cumulative probabilities and validation
+//! - [`validation`]: collect-all validation reports
+//! - [`benchmarks`]: reference values every table is checked against
+//! - [`fit`]: goodness of fit of simulations to exact distributions
+//! - [`conformance`]: shared scenarios checked against the TypeScript roller
+//! - [`output`]: the JSON table schema (v1)
+//! - [`schema`]: schema v2 with dense arrays, and reading either version
+//! - [`json_schema`]: JSON Schema and TypeScript declarations of the tables
+//! - [`cache`]: on-disk cache of seeded simulation histograms
+//! - [`checkpoint`]: checkpoints of generation runs, for `--resume`
+//! - [`diff`]: comparison of regenerated tables against sampling error
+//! - [`binary`]: the compact binary table format and its memory-mapped loader
+//! - [`export`]: long-format CSV and Parquet exports for analysis
+//! - [`sqlite`]: a normalized SQLite database of generation runs, with query views
+//! - [`report`]: HTML and Markdown reports with SVG charts
+//! - [`terminal`]: terminal histograms and success curves for single configs
+//! - [`schools`], [`character`], [`sheet`]: probability sheets
+//! - [`narrative`]: L5R 5e narrative dice
+//! - [`seedrandom`], [`replay`]: the bot's seeded RNG and roller, for replaying rolls
+//! - [`audit`]: fairness tests of logged bot rolls
+//! - [`luck`]: per-player luck scores from roll histories
+//!
+//! ```
+//! use probability_calculator::{generate_table, Engine, ExplosionMode, RollConfig};
+//!
+//! let config = RollConfig::new(5, 3, ExplosionMode::Skilled, false)?;
+//! let table = generate_table(&config, Engine::Exact)?;
+//! assert!((table.statistics.mean - 25.0).abs() < 2.0);
+//! # Ok::<(), probability_calculator::Error>(())
+//! ```
+
+pub mod audit;
+pub mod benchmarks;
+pub mod binary;
+pub mod cache;
+pub mod character;
+pub mod checkpoint;
+pub mod config;
+pub mod conformance;
+pub mod dice;
+pub mod diff;
+pub mod error;
+pub mod export;
+pub mod exact;
+pub mod fit;
+pub mod json_schema;
+pub mod luck;
+pub mod narrative;
+pub mod output;
+pub mod pipeline;
+pub mod replay;
+pub mod report;
+pub mod rules;
+pub mod schema;
+pub mod schools;
+pub mod seedrandom;
+pub mod sheet;
+pub mod sqlite;
+pub mod stats;
+pub mod terminal;
+pub mod validation;
+
+pub use config::{Engine, ExplosionMode, ExplosionRule, Pool, RollConfig};
+pub use error::{Error, Result, ValidationError};
+pub use fit::GoodnessOfFit;
+pub use output::{ProbabilityTable, ProbabilityTables};
+pub use pipeline::{RollPipeline, Stage};
+pub use rules::RuleSet;
+
+use std::collections::HashMap;
+
+/// Compute and validate the probability table for one configuration
+///
+/// Fails on the first validation error; see [`check_table`] to get them all.
+pub fn generate_table(config: &RollConfig, engine: Engine) -> Result<ProbabilityTable> {
+    let (table, errors) = check_table(config, engine);
+    match errors.into_iter().next() {
+        Some(error) => Err(error.into()),
+        None => Ok(table),
+    }
+}
+
+/// Compute the probability table for one configuration with every validation error
+///
+/// Checks the distribution, the cumulative table and any matching benchmark.
+/// Simulated histograms are also tested for goodness of fit against the exact
+/// distribution, with the result stored on the table (see [`check_simulated_table`]).
+pub fn check_table(config: &RollConfig, engine: Engine) -> (ProbabilityTable, Vec<ValidationError>) {
+    if engine == Engine::Simulation {
+        return check_simulated_table(config, &dice::simulate_roll_xky(config), config.simulation_rounds());
+    }
+
+    let pmf = exact::exact_distribution(config);
+    let mut errors = stats::pmf_errors(&pmf);
+    let cumulative = stats::calculate_cumulative_from_pmf(&pmf);
+    errors.extend(stats::cumulative_errors(&cumulative));
+
+    let table = ProbabilityTable::new(config, stats::calculate_statistics_from_pmf(&pmf), cumulative);
+    errors.extend(benchmarks::benchmark_errors(&table, None));
+    (table, errors)
+}
+
+/// Build and validate the table for an already simulated histogram of `total_count` rolls
+///
+/// Used for cached and seeded histograms, whose round counts may differ from
+/// the config's default. The goodness of fit is skipped for explosion chains
+/// too long to compute exactly in reasonable time ([`GoodnessOfFit::applies`]).
+pub fn check_simulated_table(
+    config: &RollConfig,
+    histogram: &HashMap<i32, usize>,
+    total_count: usize,
+) -> (ProbabilityTable, Vec<ValidationError>) {
+    let fit = GoodnessOfFit::applies(config)
+        .then(|| GoodnessOfFit::compare(histogram, total_count, &exact::exact_distribution(config)));
+
+    let mut errors = stats::distribution_errors(histogram, total_count);
+    errors.extend(fit.iter().flat_map(GoodnessOfFit::errors));
+    let cumulative = stats::calculate_cumulative_probabilities(histogram, total_count);
+    errors.extend(stats::cumulative_errors(&cumulative));
+
+    let mut table = ProbabilityTable::new(config, stats::calculate_statistics(histogram, total_count), cumulative);
+    table.goodness_of_fit = fit;
+    errors.extend(benchmarks::benchmark_errors(&table, Some(total_count)));
+    (table, errors)
+}
@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{config_label, LoggedRoll};
+use crate::config::{ExplosionMode, RollConfig};
+use crate::dice::{RollExpression, RollOptions};
+use crate::error::{Error, Result};
+use crate::exact::exact_distribution;
+use crate::stats::{calculate_lower_cumulative_from_pmf, probability_at_most};
+
+/// Default number of luckiest and unluckiest rolls reported
+pub const DEFAULT_TOP: usize = 5;
+
+/// z-score of two-sided 95% confidence bounds
+const Z_95: f64 = 1.959963984540054;
+
+/// A roll from a player's history
+///
+/// Lines are the bot's `RollResult`s with the player (and optionally the
+/// session and time) added alongside.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRoll {
+    pub player: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(flatten)]
+    pub roll: LoggedRoll,
+}
+
+/// Which rolls to score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LuckFilter {
+    /// Only rolls made with this explosion mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ExplosionMode>,
+    /// Only rolls with (true) or without (false) an emphasis threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emphasis: Option<bool>,
+}
+
+impl LuckFilter {
+    pub fn matches(&self, options: &RollOptions) -> bool {
+        self.mode.is_none_or(|mode| mode == options.explosion_mode)
+            && self.emphasis.is_none_or(|emphasis| emphasis == options.emphasis_threshold.is_some())
+    }
+}
+
+/// Mean percentile of a set of rolls, with 95% confidence bounds
+///
+/// 50 is average luck. The bounds are how far the mean of the same number of
+/// fair rolls of the same configurations would typically stray from 50.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LuckIndex {
+    pub rolls: usize,
+    pub index: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Luck of one player, overall and per session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerLuck {
+    pub player: String,
+    pub luck: LuckIndex,
+    /// In order of first roll; rolls without a session only count overall
+    pub sessions: Vec<SessionLuck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLuck {
+    pub session: String,
+    pub luck: LuckIndex,
+}
+
+/// One scored roll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollLuck {
+    pub player: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// Configuration the roll runs as, e.g. "10k5 skilled, +2"
+    pub label: String,
+    pub expression: RollExpression,
+    pub options: RollOptions,
+    pub total: i32,
+    /// Mid-P percentile of the total (0-100)
+    pub percentile: f64,
+}
+
+/// Luck scores of a roll history, for the bot to render
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LuckReport {
+    pub generated_at: String,
+    /// Rolls scored, after filtering
+    pub rolls: usize,
+    pub filter: LuckFilter,
+    /// Luckiest player first
+    pub players: Vec<PlayerLuck>,
+    pub luckiest: Vec<RollLuck>,
+    pub unluckiest: Vec<RollLuck>,
+}
+
+/// Mid-P percentile of a total: P(total < x) + P(total = x) / 2
+///
+/// Unlike P(total ≤ x) this averages exactly 0.5 over fair rolls of any
+/// distribution, so percentiles of different pools can be pooled.
+pub fn mid_p_percentile(lower: &BTreeMap<i32, f64>, total: i32) -> f64 {
+    (probability_at_most(lower, total) + probability_at_most(lower, total - 1)) / 2.0
+}
+
+/// Lower-tail CDF of a configuration and the variance of its mid-P percentile
+struct Tail {
+    lower: BTreeMap<i32, f64>,
+    variance: f64,
+}
+
+impl Tail {
+    fn new(config: &RollConfig) -> Self {
+        let pmf = exact_distribution(config);
+        let total: f64 = pmf.values().sum();
+        // Var of a mid-P percentile under fair rolls is (1 - Σp³) / 12
+        let cubes: f64 = pmf.values().map(|&p| (p / total).powi(3)).sum();
+        Self { lower: calculate_lower_cumulative_from_pmf(&pmf), variance: (1.0 - cubes) / 12.0 }
+    }
+}
+
+/// Tallies of one player, overall and per session
+struct PlayerTally {
+    player: String,
+    overall: Tally,
+    sessions: Vec<(String, Tally)>,
+}
+
+/// Running sum of percentiles and their variances
+#[derive(Default)]
+struct Tally {
+    rolls: usize,
+    sum: f64,
+    variance: f64,
+}
+
+impl Tally {
+    fn add(&mut self, percentile: f64, variance: f64) {
+        self.rolls += 1;
+        self.sum += percentile;
+        self.variance += variance;
+    }
+
+    fn index(&self) -> LuckIndex {
+        let n = self.rolls as f64;
+        let index = 100.0 * self.sum / n;
+        let margin = 100.0 * Z_95 * self.variance.sqrt() / n;
+        LuckIndex {
+            rolls: self.rolls,
+            index,
+            lower: (index - margin).max(0.0),
+            upper: (index + margin).min(100.0),
+        }
+    }
+}
+
+/// Score every roll of a history against its configuration's distribution
+///
+/// Each total gets its mid-P percentile within the exact distribution of the
+/// configuration it ran as. Percentiles are averaged per player and per
+/// session into a luck index, and the `top` luckiest and unluckiest rolls
+/// are listed (earliest first among ties).
+pub fn score_luck(history: &[HistoryRoll], filter: LuckFilter, top: usize) -> Result<LuckReport> {
+    // Step 1: Score each roll that passes the filter
+    let mut tails: HashMap<RollConfig, Tail> = HashMap::new();
+    let mut scored: Vec<(RollLuck, f64)> = Vec::new();
+    for (i, entry) in history.iter().enumerate() {
+        let roll = &entry.roll;
+        if !filter.matches(&roll.options) {
+            continue;
+        }
+        let config = roll.expression.config(&roll.options)
+            .map_err(|e| Error::InvalidData(format!("roll {}: {}", i + 1, e)))?;
+        let label = config_label(&config);
+        let tail = tails.entry(config).or_insert_with_key(Tail::new);
+
+        let luck = RollLuck {
+            player: entry.player.clone(),
+            session: entry.session.clone(),
+            timestamp: entry.timestamp.clone(),
+            label,
+            expression: roll.expression,
+            options: roll.options,
+            total: roll.total,
+            percentile: 100.0 * mid_p_percentile(&tail.lower, roll.total),
+        };
+        scored.push((luck, tail.variance));
+    }
+
+    // Step 2: Tally per player and per session, in order of first roll
+    let mut players: Vec<PlayerTally> = Vec::new();
+    for (luck, variance) in &scored {
+        let percentile = luck.percentile / 100.0;
+        let slot = match players.iter().position(|p| p.player == luck.player) {
+            Some(slot) => slot,
+            None => {
+                players.push(PlayerTally { player: luck.player.clone(), overall: Tally::default(), sessions: Vec::new() });
+                players.len() - 1
+            }
+        };
+        let tally = &mut players[slot];
+        tally.overall.add(percentile, *variance);
+
+        if let Some(session) = &luck.session {
+            match tally.sessions.iter_mut().find(|(s, _)| s == session) {
+                Some((_, session_tally)) => session_tally.add(percentile, *variance),
+                None => {
+                    let mut session_tally = Tally::default();
+                    session_tally.add(percentile, *variance);
+                    tally.sessions.push((session.clone(), session_tally));
+                }
+            }
+        }
+    }
+    let mut players: Vec<PlayerLuck> = players.into_iter()
+        .map(|tally| PlayerLuck {
+            player: tally.player,
+            luck: tally.overall.index(),
+            sessions: tally.sessions.into_iter()
+                .map(|(session, tally)| SessionLuck { session, luck: tally.index() })
+                .collect(),
+        })
+        .collect();
+    players.sort_by(|a, b| b.luck.index.total_cmp(&a.luck.index));
+
+    // Step 3: Extremes (stable sorts keep the earliest of equal rolls first)
+    let mut rolls: Vec<RollLuck> = scored.into_iter().map(|(luck, _)| luck).collect();
+    rolls.sort_by(|a, b| b.percentile.total_cmp(&a.percentile));
+    let luckiest = rolls.iter().take(top).cloned().collect();
+    rolls.sort_by(|a, b| a.percentile.total_cmp(&b.percentile));
+    let unluckiest = rolls.iter().take(top).cloned().collect();
+
+    Ok(LuckReport {
+        generated_at: Utc::now().to_rfc3339(),
+        rolls: rolls.len(),
+        filter,
+        players,
+        luckiest,
+        unluckiest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::execute_roll;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Fair rolls by one player, as the bot would log them
+    fn history(rng: &mut StdRng, player: &str, session: &str, options: RollOptions, count: usize) -> Vec<HistoryRoll> {
+        let expression = RollExpression { roll: 5, keep: 3, modifier: 0 };
+        (0..count)
+            .map(|_| {
+                let mut line = serde_json::to_value(execute_roll(expression, options, rng)).unwrap();
+                line["player"] = player.into();
+                line["session"] = session.into();
+                serde_json::from_value(line).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mid_p_percentile() {
+        let config = RollConfig::new(1, 1, ExplosionMode::Unskilled, false).unwrap();
+        let lower = calculate_lower_cumulative_from_pmf(&exact_distribution(&config));
+        assert!((mid_p_percentile(&lower, 1) - 0.05).abs() < 1e-12);
+        assert!((mid_p_percentile(&lower, 10) - 0.95).abs() < 1e-12);
+        // Averages 0.5 over the distribution
+        let mean = (1..=10).map(|t| mid_p_percentile(&lower, t)).sum::<f64>() / 10.0;
+        assert!((mean - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_luck_index() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let mut log = history(&mut rng, "Akodo", "s1", RollOptions::plain(ExplosionMode::Skilled, None), 300);
+        log.extend(history(&mut rng, "Akodo", "s2", RollOptions::plain(ExplosionMode::Mastery, Some(1)), 200));
+
+        // A player who always rolls the better of two totals
+        let fair = history(&mut rng, "Bayushi", "s1", RollOptions::plain(ExplosionMode::Skilled, None), 600);
+        log.extend(fair.chunks(2).map(|pair| pair.iter().max_by_key(|r| r.roll.total).unwrap().clone()));
+
+        let report = score_luck(&log, LuckFilter::default(), 3).unwrap();
+        assert_eq!(report.rolls, 800);
+        assert_eq!(report.players[0].player, "Bayushi");
+        assert!(report.players[0].luck.lower > 50.0);
+
+        let akodo = &report.players[1].luck;
+        assert!(akodo.lower < 50.0 && akodo.upper > 50.0, "{:?}", akodo);
+        let sessions: Vec<_> = report.players[1].sessions.iter().map(|s| (s.session.as_str(), s.luck.rolls)).collect();
+        assert_eq!(sessions, vec![("s1", 300), ("s2", 200)]);
+
+        assert_eq!(report.luckiest.len(), 3);
+        assert!(report.luckiest[0].percentile >= report.luckiest[2].percentile);
+        assert!(report.unluckiest[0].percentile <= report.unluckiest[2].percentile);
+        assert!(report.unluckiest[0].percentile < report.luckiest[0].percentile);
+    }
+
+    #[test]
+    fn test_filters() {
+        let mut rng = StdRng::seed_from_u64(22);
+        let mut log = history(&mut rng, "Doji", "s1", RollOptions::plain(ExplosionMode::Skilled, None), 10);
+        log.extend(history(&mut rng, "Doji", "s1", RollOptions::plain(ExplosionMode::Skilled, Some(2)), 20));
+        log.extend(history(&mut rng, "Doji", "s1", RollOptions::plain(ExplosionMode::Unskilled, None), 40));
+        let score = |mode, emphasis| score_luck(&log, LuckFilter { mode, emphasis }, DEFAULT_TOP).unwrap().rolls;
+
+        assert_eq!(score(None, None), 70);
+        assert_eq!(score(Some(ExplosionMode::Skilled), None), 30);
+        assert_eq!(score(Some(ExplosionMode::Skilled), Some(false)), 10);
+        assert_eq!(score(None, Some(true)), 20);
+        assert_eq!(score(Some(ExplosionMode::Mastery), None), 0);
+
+        // Rolls without a session only count overall
+        for roll in &mut log {
+            roll.session = rng.gen_bool(0.5).then(|| "s1".to_string());
+        }
+        let report = score_luck(&log, LuckFilter::default(), DEFAULT_TOP).unwrap();
+        assert!(report.players[0].sessions[0].luck.rolls < 70);
+    }
+}
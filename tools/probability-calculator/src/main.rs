@@ -1,58 +1,549 @@
-mod config;
-mod dice;
-mod stats;
-mod output;
-
-use std::time::Instant;
-use config::{generate_all_configs, RollConfig};
-use dice::simulate_roll_xky;
-use stats::{calculate_statistics, calculate_cumulative_probabilities, validate_distribution, validate_cumulative};
-use output::{create_probability_tables, write_json_file, format_file_size, ProbabilityTable};
-
-fn main() {
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use clap::{Args, Parser, Subcommand};
+use probability_calculator::audit::{audit_rolls, read_roll_log, LoggedRoll, DEFAULT_FDR, MIN_GROUP_ROLLS};
+use probability_calculator::binary::{binary_errors, write_binary_file, BinaryTables};
+use probability_calculator::cache::{CacheKey, CachedHistogram, HistogramCache, BLOCK_ROUNDS};
+use probability_calculator::checkpoint::{
+    run_fingerprint, Checkpoint, Checkpointer, CompletedTable, PartialHistogram, DEFAULT_CHECKPOINT_INTERVAL,
+};
+use probability_calculator::character::{character_sheet_markdown, generate_character_sheet, Character};
+use probability_calculator::conformance::{run_conformance, ScenarioFile};
+use probability_calculator::config::generate_configs;
+use probability_calculator::diff::{diff_tables, DEFAULT_Z};
+use probability_calculator::export::{export_tables, ExportFormat};
+use probability_calculator::luck::{score_luck, HistoryRoll, LuckFilter, LuckIndex, DEFAULT_TOP};
+use probability_calculator::json_schema::{schema_errors, tables_schema, tables_v2_schema, typescript_declarations};
+use probability_calculator::narrative::{generate_narrative_configs, simulate_narrative, NARRATIVE_ROUNDS};
+use probability_calculator::output::{
+    create_narrative_tables, create_probability_tables, write_json_file, format_file_size, NarrativeTable,
+    ProbabilityTable, ProbabilityTables,
+};
+use probability_calculator::replay::replay_log;
+use probability_calculator::report::{render_report, ReportFormat, ReportOptions, DEFAULT_HEATMAP_TNS, DEFAULT_POOLS};
+use probability_calculator::schema::{read_tables, ProbabilityTablesV2, DEFAULT_PRECISION, MAX_PRECISION, SCHEMA_VERSION};
+use probability_calculator::schools::{generate_school_sheets, SchoolsFile};
+use probability_calculator::sqlite::write_sqlite_file;
+use probability_calculator::terminal::{render_show, ShowQuery, DEFAULT_WIDTH};
+use probability_calculator::validation::ValidationReport;
+use probability_calculator::{
+    check_simulated_table, check_table, Engine, ExplosionMode, ExplosionRule, GoodnessOfFit, Pool, RollConfig,
+    RollPipeline, RuleSet, Stage, ValidationError,
+};
+
+/// Errors reported by the command line, with context
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+/// Default location of the generated tables, relative to this crate
+const DEFAULT_OUTPUT_DIR: &str = "../../packages/core/data";
+
+/// Precompute L5R Roll & Keep probability tables
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Table generation options (used when no subcommand is given)
+    #[command(flatten)]
+    generate: GenerateArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate per-school, per-rank probability sheets from a schools file
+    Schools {
+        /// Schools data file
+        #[arg(long, default_value = "../../data/schools.json")]
+        input: String,
+
+        /// Output file
+        #[arg(long, default_value = "../../packages/core/data/school-sheets.json")]
+        output: String,
+
+        /// Rule set whose Ten Dice Rule and emphasis the rolls use
+        #[arg(long, default_value = "4e")]
+        rules: RuleSet,
+    },
+
+    /// Generate L5R 5e narrative dice tables (ring d6 / skill d12)
+    Narrative {
+        /// Output file
+        #[arg(long, default_value = "../../packages/core/data/narrative-tables.json")]
+        output: String,
+    },
+
+    /// Check the shared conformance scenarios and write fixtures for the TypeScript tests
+    Conformance {
+        /// Scenario file
+        #[arg(long, default_value = "../../data/conformance/scenarios.json")]
+        input: String,
+
+        /// Fixture output file
+        #[arg(long, default_value = "../../packages/core/data/conformance-fixtures.json")]
+        output: String,
+    },
+
+    /// Write the JSON Schemas (and optionally TypeScript declarations) of the table files
+    Schema {
+        /// Directory for probability-tables.schema.json and probability-tables.v2.schema.json
+        #[arg(long, default_value = DEFAULT_OUTPUT_DIR)]
+        output_dir: String,
+
+        /// Also write TypeScript declarations of the table types here
+        #[arg(long)]
+        typescript: Option<String>,
+
+        /// Table files (v1 or v2) to validate against their schema
+        #[arg(long)]
+        check: Vec<String>,
+    },
+
+    /// Convert probability tables (schema v1 or v2) to schema v2
+    Migrate {
+        /// Tables to convert
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.json")]
+        input: String,
+
+        /// Output file
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.v2.json")]
+        output: String,
+
+        /// Decimals kept for probabilities
+        #[arg(long, default_value_t = DEFAULT_PRECISION, value_parser = clap::value_parser!(u32).range(1..=MAX_PRECISION as i64))]
+        precision: u32,
+    },
+
+    /// Convert probability tables to the compact binary format and check it against the JSON
+    Binary {
+        /// JSON tables (schema v1 or v2)
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.json")]
+        input: String,
+
+        /// Binary output file
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.bin")]
+        output: String,
+    },
+
+    /// Compare two table files, flagging changes beyond sampling error
+    Diff {
+        /// Tables before regeneration (schema v1 or v2)
+        old: String,
+
+        /// Tables after regeneration (schema v1 or v2)
+        new: String,
+
+        /// Standard errors a change must exceed to be flagged
+        #[arg(long, default_value_t = DEFAULT_Z)]
+        z: f64,
+
+        /// List every changed config, not only flagged ones
+        #[arg(long)]
+        all: bool,
+
+        /// Write the full JSON report here
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Chart one config, or two side by side, in the terminal
+    #[command(alias = "query")]
+    Show {
+        /// Configs as XkY[:mode][+e], e.g. 5k3 or 7k4:mastery+e (mode defaults to skilled)
+        #[arg(required = true, num_args = 1..=2)]
+        configs: Vec<ShowQuery>,
+
+        /// Mark this TN and show P(≥ TN)
+        #[arg(long)]
+        tn: Option<i32>,
+
+        /// Tables to read (schema v1 or v2)
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.json")]
+        input: String,
+
+        /// Chart width per config (default: 60, or 30 each side by side)
+        #[arg(long, value_parser = clap::value_parser!(u16).range(10..))]
+        width: Option<u16>,
+
+        /// Roll pipeline (JSON) the tables were generated with
+        #[arg(long)]
+        pipeline: Option<String>,
+    },
+
+    /// Render a self-contained HTML or Markdown report with SVG charts
+    Report {
+        /// Tables to chart (schema v1 or v2)
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.json")]
+        input: String,
+
+        /// Report file (defaults to the input with .report.html or .report.md)
+        #[arg(long)]
+        output: Option<String>,
+
+        /// Document format (html, markdown)
+        #[arg(long, default_value = "html")]
+        format: ReportFormat,
+
+        /// Pools with a histogram and success curves, e.g. 3k2,5k3
+        #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_POOLS)]
+        pools: Vec<Pool>,
+
+        /// TNs with an XkY heatmap
+        #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_HEATMAP_TNS)]
+        tns: Vec<i32>,
+
+        /// Explosion mode of the histograms and heatmaps
+        #[arg(long, default_value = "skilled")]
+        mode: ExplosionMode,
+
+        /// Roll pipeline (JSON) the tables were generated with
+        #[arg(long)]
+        pipeline: Option<String>,
+    },
+
+    /// Append probability tables to a SQLite database as a new run
+    Sqlite {
+        /// Tables to load (schema v1 or v2)
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.json")]
+        input: String,
+
+        /// Database file (created if missing)
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.db")]
+        output: String,
+    },
+
+    /// Export tables as long-format rows and statistics for analysis
+    Export {
+        /// Tables to export (schema v1 or v2)
+        #[arg(long, default_value = "../../packages/core/data/probability-tables.json")]
+        input: String,
+
+        /// Directory for probabilities.<ext> and statistics.<ext>
+        #[arg(long, default_value = "../../packages/core/data/export")]
+        output_dir: String,
+
+        /// File format (csv, parquet)
+        #[arg(long, default_value = "csv")]
+        format: ExportFormat,
+    },
+
+    /// Replay a bot roll log (JSONL of RollResult) from its DICE_SEED and check every roll
+    Replay {
+        /// Roll log, one RollResult per line, starting right after the seed was set
+        log: String,
+
+        /// Seed the bot was started with (DICE_SEED)
+        #[arg(long)]
+        seed: String,
+    },
+
+    /// Audit a bot roll log (JSONL of RollResult) for unfair dice
+    Audit {
+        /// Roll log, one RollResult per line
+        log: String,
+
+        /// False discovery rate for flagging tests
+        #[arg(long, default_value_t = DEFAULT_FDR)]
+        fdr: f64,
+
+        /// Write the full JSON report here
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Score each player's luck from a roll history (JSONL of RollResult with player and session)
+    Luck {
+        /// Roll history, one RollResult per line with `player` and optionally `session`
+        history: String,
+
+        /// Only score rolls made with this explosion mode
+        #[arg(long)]
+        mode: Option<ExplosionMode>,
+
+        /// Only score rolls with (true) or without (false) emphasis
+        #[arg(long)]
+        emphasis: Option<bool>,
+
+        /// Number of luckiest and unluckiest rolls to list
+        #[arg(long, default_value_t = DEFAULT_TOP)]
+        top: usize,
+
+        /// Write the JSON report here
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Generate a character's probability sheet (JSON and Markdown)
+    Character {
+        /// Character file
+        input: String,
+
+        /// Schools data file, used to apply technique modifiers
+        #[arg(long, default_value = "../../data/schools.json")]
+        schools: String,
+
+        /// JSON output file (defaults to <input>.sheet.json)
+        #[arg(long)]
+        output: Option<String>,
+
+        /// Markdown output file (defaults to <input>.sheet.md)
+        #[arg(long)]
+        markdown: Option<String>,
+
+        /// Rule set whose Ten Dice Rule and emphasis the rolls use
+        #[arg(long, default_value = "4e")]
+        rules: RuleSet,
+    },
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Generate tables for a custom explosion rule: faces at or above this value explode
+    #[arg(long, value_parser = clap::value_parser!(u8).range(2..=10))]
+    explode_on: Option<u8>,
+
+    /// Cap the number of extra rolls per exploding die (implies --explode-on 10 if not given)
+    #[arg(long)]
+    max_chain: Option<u8>,
+
+    /// Roll pipeline definition (JSON) applied on top of every configuration
+    #[arg(long)]
+    pipeline: Option<String>,
+
+    /// How to compute the distributions
+    #[arg(long, default_value = "simulation")]
+    engine: Engine,
+
+    /// Rule set: 4e, legacy (L5R 1e-3e) or 7th-sea (7th Sea 1e)
+    #[arg(long, default_value = "4e")]
+    rules: RuleSet,
+
+    /// Validate every configuration and write a JSON report here, instead of
+    /// stopping at the first failure (tables are only written if all pass)
+    #[arg(long)]
+    report: Option<String>,
+
+    /// JSON schema version to write: 1 (string keys, read by the TS loader) or 2 (dense arrays)
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=2))]
+    schema: u32,
+
+    /// Decimals kept for probabilities in schema v2
+    #[arg(long, default_value_t = DEFAULT_PRECISION, value_parser = clap::value_parser!(u32).range(1..=MAX_PRECISION as i64))]
+    precision: u32,
+
+    /// Cache simulated histograms in this directory and reuse them on later runs
+    #[arg(long)]
+    cache: Option<String>,
+
+    /// Seed the simulations so runs are reproducible (defaults to 0 with --cache)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Simulate this many more rounds for every cached histogram, tightening the estimates (a multiple of 50000)
+    #[arg(long, requires = "cache", value_parser = parse_extra_rounds)]
+    extra_rounds: Option<usize>,
+
+    /// Periodically save completed tables and partial histograms here (seeds the run, default 0)
+    #[arg(long)]
+    checkpoint: Option<String>,
+
+    /// Continue the run saved in --checkpoint instead of starting over
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+
+    /// Seconds between checkpoint writes
+    #[arg(long, default_value_t = DEFAULT_CHECKPOINT_INTERVAL)]
+    checkpoint_interval: u64,
+
+    /// Also append this run to a SQLite database (created if missing)
+    #[arg(long)]
+    sqlite: Option<String>,
+
+    /// Output file (defaults to probability-tables.json, with the custom rule
+    /// and pipeline name appended when given)
+    #[arg(long)]
+    output: Option<String>,
+}
+
+impl GenerateArgs {
+    /// The custom explosion rule requested on the command line, if any
+    fn custom_rule(&self) -> probability_calculator::Result<Option<ExplosionRule>> {
+        if self.explode_on.is_none() && self.max_chain.is_none() {
+            return Ok(None);
+        }
+        ExplosionRule::new(Some(self.explode_on.unwrap_or(10)), self.max_chain).map(Some)
+    }
+
+    /// Default output path, e.g. probability-tables-legacy-e8-c1-kakita.json
+    fn default_output(&self, custom_rule: Option<ExplosionRule>) -> String {
+        let mut name = String::from("probability-tables");
+        if self.rules != RuleSet::default() {
+            name.push_str(&format!("-{}", self.rules.name()));
+        }
+        if let Some(rule) = custom_rule {
+            name.push_str(&format!("-{}", rule.slug()));
+        }
+        if let Some(stem) = self.pipeline.as_deref().and_then(|p| Path::new(p).file_stem()) {
+            name.push_str(&format!("-{}", stem.to_string_lossy()));
+        }
+        format!("{}/{}.json", DEFAULT_OUTPUT_DIR, name)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    
     println!("🎲 L5R Probability Calculator");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     
-    // Generate all configurations
-    let configs = generate_all_configs();
+    let result = match cli.command {
+        None => run_generate(cli.generate),
+        Some(Command::Schools { input, output, rules }) => run_schools(&input, &output, rules),
+        Some(Command::Narrative { output }) => run_narrative(&output),
+        Some(Command::Conformance { input, output }) => run_conformance_check(&input, &output),
+        Some(Command::Schema { output_dir, typescript, check }) => run_schema(&output_dir, typescript.as_deref(), &check),
+        Some(Command::Migrate { input, output, precision }) => run_migrate(&input, &output, precision),
+        Some(Command::Binary { input, output }) => run_binary(&input, &output),
+        Some(Command::Export { input, output_dir, format }) => run_export(&input, &output_dir, format),
+        Some(Command::Sqlite { input, output }) => run_sqlite(&input, &output),
+        Some(Command::Diff { old, new, z, all, output }) => run_diff(&old, &new, z, all, output.as_deref()),
+        Some(Command::Show { configs, tn, input, width, pipeline }) => {
+            let width = width.map_or(DEFAULT_WIDTH / configs.len(), usize::from);
+            load_pipeline(pipeline.as_deref()).and_then(|pipeline| run_show(&configs, tn, &input, width, &pipeline))
+        }
+        Some(Command::Report { input, output, format, pools, tns, mode, pipeline }) => {
+            let output = output.unwrap_or_else(|| sibling_path(&input, &format!("report.{}", format.extension())));
+            load_pipeline(pipeline.as_deref()).and_then(|pipeline| {
+                let options = ReportOptions { pools, tns, explosion: mode.rule(), pipeline };
+                run_report(&input, &output, format, &options)
+            })
+        }
+        Some(Command::Replay { log, seed }) => run_replay(&log, &seed),
+        Some(Command::Audit { log, fdr, output }) => run_audit(&log, fdr, output.as_deref()),
+        Some(Command::Luck { history, mode, emphasis, top, output }) => {
+            run_luck(&history, LuckFilter { mode, emphasis }, top, output.as_deref())
+        }
+        Some(Command::Character { input, schools, output, markdown, rules }) => {
+            let output = output.unwrap_or_else(|| sibling_path(&input, "sheet.json"));
+            let markdown = markdown.unwrap_or_else(|| sibling_path(&input, "sheet.md"));
+            run_character(&input, &schools, &output, &markdown, rules)
+        }
+    };
+    
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Generate the probability tables
+fn run_generate(cli: GenerateArgs) -> CliResult {
+    // Generate the presets, or only the requested custom rule
+    let custom_rule = cli.custom_rule()?;
+    if cli.rules != RuleSet::default() {
+        println!("📜 Rule set: {}", cli.rules.name());
+    }
+    let configs = match custom_rule {
+        Some(rule) => {
+            println!("💥 Custom explosion rule: {}", rule);
+            generate_configs(&[rule], cli.rules.has_emphasis())
+        }
+        None => cli.rules.configs(),
+    };
+    
+    // Apply the roll pipeline to every configuration
+    let configs = match &cli.pipeline {
+        Some(path) => {
+            let pipeline = RollPipeline::from_json_file(path)
+                .map_err(|e| format!("Failed to load pipeline {}: {}", path, e))?;
+            println!("🧩 Roll pipeline: {}", pipeline.describe());
+            configs.into_iter().map(|c| c.with_pipeline(pipeline.clone())).collect()
+        }
+        None => configs,
+    };
+    if cli.engine == Engine::Exact {
+        println!("🧮 Exact engine");
+    } else if !configs.iter().all(GoodnessOfFit::applies) {
+        println!("⚠️  Explosion chains too long for the goodness-of-fit test; those tables are published without it");
+    }
     println!("📊 Generating {} probability tables...", configs.len());
     println!();
     
+    let cache = match &cli.cache {
+        Some(dir) => {
+            println!("🗃️  Histogram cache: {}", dir);
+            Some(HistogramCache::open(dir).map_err(|e| format!("Failed to open cache {}: {}", dir, e))?)
+        }
+        None => None,
+    };
+    let seed = cli.seed.or((cache.is_some() || cli.checkpoint.is_some()).then_some(0));
+    let keys: Vec<CacheKey> = configs.iter().map(|c| CacheKey::new(c, cli.rules, cli.engine, seed.unwrap_or(0))).collect();
+    let mut checkpointer = match &cli.checkpoint {
+        Some(path) => Some(open_checkpoint(path, &cli, &keys)?),
+        None => None,
+    };
+    let mut cache_stats = CacheStats::default();
+    let mut min_rounds = HashMap::new();
+    
     let start_time = Instant::now();
     let mut tables = Vec::with_capacity(configs.len());
+    let mut report = cli.report.as_ref().map(|_| ValidationReport::new(cli.engine, cli.rules));
     
-    // Process each configuration
-    for (index, config) in configs.iter().enumerate() {
+    // Replay the tables completed before the checkpoint
+    let resumed = checkpointer.as_mut().map(|c| c.checkpoint.completed.clone()).unwrap_or_default();
+    let mut partial = checkpointer.as_mut().and_then(|c| c.checkpoint.partial.take());
+    for (config, completed) in configs.iter().zip(resumed.iter().cloned()) {
+        if let Some(rounds) = completed.rounds {
+            record_rounds(&mut min_rounds, config, rounds);
+        }
+        accept_table(config, completed.table, completed.errors, report.as_mut(), &mut tables)?;
+    }
+    
+    // Process each remaining configuration
+    for (index, config) in configs.iter().enumerate().skip(resumed.len()) {
         let config_start = Instant::now();
         
         // Show progress
-        print_progress(index + 1, configs.len(), &config);
+        print_progress(index + 1, configs.len(), config);
         
-        // Run simulation
-        let histogram = simulate_roll_xky(&config);
-        let total_count = config.simulation_rounds();
-        
-        // Validate distribution
-        if let Err(e) = validate_distribution(&histogram, total_count) {
-            eprintln!("❌ Validation error for {:?}: {}", config, e);
-            std::process::exit(1);
-        }
-        
-        // Calculate statistics
-        let statistics = calculate_statistics(&histogram, total_count);
-        
-        // Calculate cumulative probabilities
-        let cumulative = calculate_cumulative_probabilities(&histogram, total_count);
+        // Compute the table, from a seeded (and possibly cached) histogram if asked
+        let (table, errors, rounds) = match seed {
+            Some(_) if cli.engine == Engine::Simulation => {
+                let cached = simulate_cached(
+                    config,
+                    keys[index].clone(),
+                    cache.as_ref(),
+                    cli.extra_rounds,
+                    partial.take(),
+                    checkpointer.as_mut(),
+                    &mut cache_stats,
+                )
+                .map_err(|e| format!("Failed to simulate {}k{} {}: {}", config.roll, config.keep, config.explosion, e))?;
+                record_rounds(&mut min_rounds, config, cached.rounds);
+                let (table, errors) = check_simulated_table(config, &cached.histogram(), cached.rounds);
+                (table, errors, Some(cached.rounds))
+            }
+            _ => {
+                let (table, errors) = check_table(config, cli.engine);
+                (table, errors, None)
+            }
+        };
         
-        // Validate cumulative
-        if let Err(e) = validate_cumulative(&cumulative) {
-            eprintln!("❌ Cumulative validation error for {:?}: {}", config, e);
-            std::process::exit(1);
+        if let Some(checkpointer) = checkpointer.as_mut() {
+            if report.is_some() || errors.is_empty() {
+                let completed =
+                    CompletedTable { key: keys[index].hash(), rounds, table: table.clone(), errors: errors.clone() };
+                checkpointer.complete(completed).map_err(|e| format!("Failed to write checkpoint: {}", e))?;
+            }
         }
-        
-        // Create table entry
-        let table = ProbabilityTable::new(&config, statistics, cumulative);
-        tables.push(table);
+        accept_table(config, table, errors, report.as_mut(), &mut tables)?;
         
         // Show timing for this config
         let elapsed = config_start.elapsed();
@@ -60,26 +551,61 @@ fn main() {
     }
     
     println!();
+    
+    // Write the validation report; stop before the tables if anything failed
+    if let (Some(report), Some(path)) = (&report, &cli.report) {
+        println!("🧾 Validation: {} passed, {} failed. Writing report to {}", report.passed, report.failed, path);
+        write_json_file(path, report).map_err(|e| format!("Failed to write report: {}", e))?;
+        if report.failed > 0 {
+            return Err(format!("{} of {} configurations failed validation", report.failed, report.total).into());
+        }
+    }
     println!("✅ All simulations complete!");
+    if cache.is_some() {
+        println!(
+            "🗃️  Cache: {} reused, {} extended, {} new",
+            cache_stats.reused, cache_stats.extended, cache_stats.new
+        );
+    }
     
-    // Create output structure
-    let probability_tables = create_probability_tables(tables);
+    // Create output structure, recording the rounds actually simulated
+    let mut probability_tables = create_probability_tables(tables, cli.engine, cli.rules);
+    for (rule, rounds) in min_rounds {
+        let metadata = &mut probability_tables.simulation_rounds;
+        match rule.preset() {
+            Some(ExplosionMode::Unskilled) => metadata.unskilled = rounds,
+            Some(ExplosionMode::Skilled) => metadata.skilled = rounds,
+            Some(ExplosionMode::Mastery) => metadata.mastery = rounds,
+            None => metadata.custom = Some(rounds),
+        }
+    }
     
     // Write to file
-    let output_path = "../../packages/core/data/probability-tables.json";
+    let output_path = cli.output.clone().unwrap_or_else(|| cli.default_output(custom_rule));
     println!("📝 Writing to {}", output_path);
     
-    if let Err(e) = write_json_file(output_path, &probability_tables) {
-        eprintln!("❌ Failed to write JSON: {}", e);
-        std::process::exit(1);
-    }
+    let written = match cli.schema {
+        1 => write_json_file(&output_path, &probability_tables),
+        _ => ProbabilityTablesV2::from_v1(&probability_tables, cli.precision)
+            .and_then(|v2| write_json_file(&output_path, &v2)),
+    };
+    written.map_err(|e| format!("Failed to write JSON: {}", e))?;
     
     // Get file size
-    if let Ok(metadata) = std::fs::metadata(output_path) {
+    if let Ok(metadata) = std::fs::metadata(&output_path) {
         let size = format_file_size(metadata.len());
         println!("📦 File size: {}", size);
     }
     
+    if let Some(path) = &cli.sqlite {
+        let v2 = ProbabilityTablesV2::from_v1(&probability_tables, cli.precision)?;
+        let run_id = write_sqlite_file(path, &v2).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        println!("🗄️  Appended run {} to {}", run_id, path);
+    }
+    if let Some(checkpointer) = checkpointer {
+        checkpointer.finish().map_err(|e| format!("Failed to remove checkpoint: {}", e))?;
+    }
+    
     // Show summary
     let total_elapsed = start_time.elapsed();
     println!();
@@ -90,14 +616,549 @@ fn main() {
     
     // Show some sample statistics
     show_sample_statistics(&probability_tables);
+    Ok(())
+}
+
+/// Histograms taken from the cache or checkpoint during a run
+#[derive(Default)]
+struct CacheStats {
+    reused: usize,
+    extended: usize,
+    new: usize,
+}
+
+/// Start a checkpoint for the run of `keys`, or load and check the one to resume
+fn open_checkpoint(path: &str, cli: &GenerateArgs, keys: &[CacheKey]) -> Result<Checkpointer, Box<dyn std::error::Error>> {
+    let interval = Duration::from_secs(cli.checkpoint_interval);
+    let fingerprint = run_fingerprint(keys, cli.extra_rounds);
+    if !cli.resume {
+        if Path::new(path).exists() {
+            return Err(format!("Checkpoint {} already exists; pass --resume to continue it or delete it", path).into());
+        }
+        println!("💾 Checkpointing to {} every {}s", path, cli.checkpoint_interval);
+        return Ok(Checkpointer::new(path, interval, Checkpoint::new(fingerprint)));
+    }
+    
+    let checkpoint = Checkpoint::load(path)
+        .and_then(|c| c.verify(keys, cli.extra_rounds).map(|_| c))
+        .map_err(|e| format!("Cannot resume from {}: {}", path, e))?;
+    print!("♻️  Resuming from {}: {} of {} tables done", path, checkpoint.completed.len(), keys.len());
+    match &checkpoint.partial {
+        Some(partial) => println!(", next at {}/{} rounds", partial.histogram.rounds, partial.target),
+        None => println!(),
+    }
+    Ok(Checkpointer::new(path, interval, checkpoint))
+}
+
+/// Keep the fewest rounds simulated for each explosion rule, for the output metadata
+fn record_rounds(min_rounds: &mut HashMap<ExplosionRule, usize>, config: &RollConfig, rounds: usize) {
+    let min = min_rounds.entry(config.explosion).or_insert(rounds);
+    *min = (*min).min(rounds);
+}
+
+/// Add a finished table, collecting its errors in report mode and failing on the first otherwise
+fn accept_table(
+    config: &RollConfig,
+    table: ProbabilityTable,
+    errors: Vec<ValidationError>,
+    report: Option<&mut ValidationReport>,
+    tables: &mut Vec<ProbabilityTable>,
+) -> CliResult {
+    match report {
+        Some(report) => {
+            if !errors.is_empty() {
+                print!("⚠️  {} error(s)", errors.len());
+            }
+            report.record(config, errors);
+        }
+        None => {
+            if let Some(error) = errors.into_iter().next() {
+                return Err(format!("{}k{} {}: {}", config.roll, config.keep, config.explosion, error).into());
+            }
+        }
+    }
+    tables.push(table);
+    Ok(())
+}
+
+/// Extra rounds must fill whole seeded blocks, so extended entries match a single longer run
+fn parse_extra_rounds(s: &str) -> Result<usize, String> {
+    let rounds: usize = s.parse().map_err(|e| format!("{}", e))?;
+    if rounds == 0 || !rounds.is_multiple_of(BLOCK_ROUNDS) {
+        return Err(format!("{} is not a positive multiple of {} (the cache's block size)", rounds, BLOCK_ROUNDS));
+    }
+    Ok(rounds)
+}
+
+/// The seeded histogram for `key`, reusing and extending the cached entry if there is one
+///
+/// New entries get the config's default rounds plus any extra rounds. A
+/// partial histogram from a checkpoint is continued instead of consulting the
+/// cache; before an extended entry overwrites the cached one the checkpoint is
+/// saved, so resuming never extends an entry twice.
+fn simulate_cached(
+    config: &RollConfig,
+    key: CacheKey,
+    cache: Option<&HistogramCache>,
+    extra_rounds: Option<usize>,
+    partial: Option<PartialHistogram>,
+    mut checkpointer: Option<&mut Checkpointer>,
+    stats: &mut CacheStats,
+) -> probability_calculator::Result<CachedHistogram> {
+    let extra = extra_rounds.unwrap_or(0);
+    let (mut histogram, target, extending) = match partial {
+        Some(partial) => (partial.histogram, partial.target, false),
+        None => {
+            let cached = match cache {
+                Some(cache) => cache.load(&key)?,
+                None => None,
+            };
+            match cached {
+                Some(histogram) if extra_rounds.is_none() => {
+                    stats.reused += 1;
+                    return Ok(histogram);
+                }
+                Some(histogram) => {
+                    stats.extended += 1;
+                    let target = histogram.rounds + extra;
+                    (histogram, target, true)
+                }
+                None => {
+                    stats.new += 1;
+                    (CachedHistogram::new(key), config.simulation_rounds() + extra, false)
+                }
+            }
+        }
+    };
+    
+    histogram.simulate_to(config, target, |h| match checkpointer.as_deref_mut() {
+        Some(checkpointer) => checkpointer.progress(target, h),
+        None => Ok(()),
+    })?;
+    if let Some(cache) = cache {
+        if let (Some(checkpointer), true) = (checkpointer, extending) {
+            checkpointer.save_partial(target, &histogram)?;
+        }
+        cache.store(&histogram)?;
+    }
+    Ok(histogram)
+}
+
+/// Generate school probability sheets
+fn run_schools(input: &str, output: &str, rules: RuleSet) -> CliResult {
+    let file = SchoolsFile::load(input)
+        .map_err(|e| format!("Failed to load schools from {}: {}", input, e))?;
+    println!("🏯 Generating sheets for {} schools × 5 ranks...", file.schools.len());
+    
+    let start_time = Instant::now();
+    let sheets = generate_school_sheets(&file, rules)?;
+    
+    println!("📝 Writing to {}", output);
+    write_json_file(output, &sheets).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    println!("⏱️  Total time: {:.2}s", start_time.elapsed().as_secs_f64());
+    Ok(())
+}
+
+/// Generate L5R 5e narrative dice tables
+fn run_narrative(output: &str) -> CliResult {
+    let configs = generate_narrative_configs();
+    println!("🎴 Simulating {} narrative dice configurations...", configs.len());
+    
+    let start_time = Instant::now();
+    let mut tables = Vec::with_capacity(configs.len());
+    for config in &configs {
+        let histogram = simulate_narrative(config, NARRATIVE_ROUNDS);
+        tables.push(NarrativeTable::new(config, &histogram));
+    }
+    
+    println!("📝 Writing to {}", output);
+    let narrative_tables = create_narrative_tables(tables, NARRATIVE_ROUNDS);
+    write_json_file(output, &narrative_tables).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    println!("⏱️  Total time: {:.2}s", start_time.elapsed().as_secs_f64());
+    
+    // Show the keep policies side by side for a typical check
+    println!("\n📈 Sample (Ring 3, Skill 2):");
+    for table in narrative_tables.tables.iter().filter(|t| t.ring == 3 && t.skill == 2) {
+        println!(
+            "  {:<16} successes {:.2}, opportunity {:.2}, strife {:.2}",
+            table.policy.name(), table.expected_successes, table.expected_opportunity, table.expected_strife
+        );
+    }
+    Ok(())
+}
+
+/// Run the conformance scenarios with both engines and write the fixtures
+fn run_conformance_check(input: &str, output: &str) -> CliResult {
+    let file = ScenarioFile::load(input)
+        .map_err(|e| format!("Failed to load scenarios from {}: {}", input, e))?;
+    println!("🧪 Checking {} scenarios and {} raise cases...", file.scenarios.len(), file.raises.len());
+    
+    let start_time = Instant::now();
+    let (fixtures, errors) = run_conformance(&file, true)?;
+    
+    for scenario in &fixtures.scenarios {
+        if let Some(fit) = &scenario.goodness_of_fit {
+            println!(
+                "  {:<48} mean {:>6.2}  χ² p = {:.3}, KS p = {:.3}",
+                scenario.name, scenario.statistics.mean, fit.chi_square_p_value, fit.ks_p_value
+            );
+        }
+    }
+    if !errors.is_empty() {
+        for error in &errors {
+            eprintln!("⚠️  {}", error);
+        }
+        return Err(format!("{} conformance check(s) failed", errors.len()).into());
+    }
+    
+    println!("📝 Writing to {}", output);
+    write_json_file(output, &fixtures).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    println!("⏱️  Total time: {:.2}s", start_time.elapsed().as_secs_f64());
+    Ok(())
+}
+
+/// Write the table schemas and check table files against them
+fn run_schema(output_dir: &str, typescript: Option<&str>, check: &[String]) -> CliResult {
+    let schemas = [
+        ("probability-tables.schema.json", tables_schema()),
+        ("probability-tables.v2.schema.json", tables_v2_schema()),
+    ];
+    for (name, schema) in &schemas {
+        let path = format!("{}/{}", output_dir, name);
+        println!("📝 Writing to {}", path);
+        write_json_file(&path, schema).map_err(|e| format!("Failed to write schema: {}", e))?;
+    }
+    if let Some(path) = typescript {
+        println!("📝 Writing TypeScript declarations to {}", path);
+        let declarations = typescript_declarations(&[schemas[0].1.clone(), schemas[1].1.clone()]);
+        if let Some(parent) = Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, declarations).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    
+    let mut failed = 0;
+    for file in check {
+        let json = std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+        let tables: serde_json::Value = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", file, e))?;
+        // Files without a schema_version are v1
+        let (version, schema) = match tables.get("schema_version") {
+            None => (1, &schemas[0].1),
+            Some(_) => (SCHEMA_VERSION, &schemas[1].1),
+        };
+        let errors = schema_errors(schema, &tables);
+        if errors.is_empty() {
+            println!("✅ {} matches schema v{}", file, version);
+            continue;
+        }
+        failed += 1;
+        println!("❌ {}: {} violation(s) of schema v{}", file, errors.len(), version);
+        for error in errors.iter().take(10) {
+            println!("   {}", error);
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} table file(s) do not match their schema", failed, check.len()).into());
+    }
+    Ok(())
+}
+
+/// Convert tables of either schema version to schema v2
+fn run_migrate(input: &str, output: &str, precision: u32) -> CliResult {
+    let mut tables = read_tables(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    tables.set_precision(precision);
+    println!("🔄 Migrating {} tables to schema v{} ({} decimals)...", tables.tables.len(), SCHEMA_VERSION, tables.precision);
+    
+    println!("📝 Writing to {}", output);
+    write_json_file(output, &tables).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    if let (Ok(before), Ok(after)) = (std::fs::metadata(input), std::fs::metadata(output)) {
+        println!("📦 File size: {} (was {})", format_file_size(after.len()), format_file_size(before.len()));
+    }
+    Ok(())
+}
+
+/// Encode JSON tables (schema v1 or v2) in the binary format, then load the file back and compare
+fn run_binary(input: &str, output: &str) -> CliResult {
+    let tables = read_tables(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    println!("🗜️  Encoding {} tables...", tables.tables.len());
+    
+    println!("📝 Writing to {}", output);
+    write_binary_file(output, &tables).map_err(|e| format!("Failed to write binary tables: {}", e))?;
+    
+    let binary = BinaryTables::open(output).map_err(|e| format!("Failed to load {}: {}", output, e))?;
+    let errors = binary_errors(&tables, &binary);
+    if let Some(error) = errors.first() {
+        return Err(format!("{} mismatch(es) with the JSON, first: {}", errors.len(), error).into());
+    }
+    
+    let binary_size = std::fs::metadata(output)?.len();
+    println!(
+        "📦 File size: {} (JSON: {})",
+        format_file_size(binary_size),
+        format_file_size(std::fs::metadata(input)?.len())
+    );
+    println!("✅ Every lookup matches the JSON");
+    Ok(())
+}
+
+/// Compare two table files and report what changed
+fn run_diff(old: &str, new: &str, z: f64, all: bool, output: Option<&str>) -> CliResult {
+    let old_tables = read_tables(old).map_err(|e| format!("Failed to read {}: {}", old, e))?;
+    let new_tables = read_tables(new).map_err(|e| format!("Failed to read {}: {}", new, e))?;
+    println!("🔍 Comparing {} ({} tables) with {} ({} tables)...", old, old_tables.tables.len(), new, new_tables.tables.len());
+    
+    let diff = diff_tables(&old_tables, &new_tables, z);
+    for change in &diff.metadata {
+        println!("🧾 {}: {} → {}", change.field, change.old, change.new);
+    }
+    for config in &diff.added {
+        println!("➕ {}", config);
+    }
+    for config in &diff.removed {
+        println!("➖ {}", config);
+    }
+    println!(
+        "📊 {} changed, {} unchanged, {} beyond sampling error (z > {})",
+        diff.changed.len(), diff.unchanged, diff.flagged, z
+    );
+    println!();
+    
+    for config in diff.changed.iter().filter(|c| all || c.is_flagged()) {
+        let marker = if config.is_flagged() { "⚠️ " } else { "  " };
+        let mut line = format!(
+            "{} {:<36} max Δ {:.6} at TN {}, max rel {:.2}% at TN {}",
+            marker, config.config, config.max_abs_change, config.max_abs_tn,
+            config.max_rel_change * 100.0, config.max_rel_tn
+        );
+        if let Some(mean) = config.statistics.iter().find(|s| s.field == "mean") {
+            line.push_str(&format!(", mean {:.3} → {:.3}", mean.old, mean.new));
+        }
+        if !config.flagged_tns.is_empty() {
+            line.push_str(&format!(", {} TN(s) flagged", config.flagged_tns.len()));
+        }
+        println!("{}", line);
+    }
+    
+    if diff.flagged > 0 {
+        println!();
+        println!("⚠️  {} config(s) changed by more than sampling error explains", diff.flagged);
+    } else {
+        println!("✅ Every change is within sampling error");
+    }
+    
+    if let Some(output) = output {
+        println!("📝 Writing to {}", output);
+        write_json_file(output, &diff).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Stages of a pipeline file, or none without one
+fn load_pipeline(path: Option<&str>) -> Result<Vec<Stage>, Box<dyn std::error::Error>> {
+    match path {
+        Some(path) => {
+            let pipeline = RollPipeline::from_json_file(path).map_err(|e| format!("Failed to load pipeline {}: {}", path, e))?;
+            Ok(pipeline.stages)
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Print terminal charts of the requested configs
+fn run_show(queries: &[ShowQuery], tn: Option<i32>, input: &str, width: usize, pipeline: &[Stage]) -> CliResult {
+    let tables = read_tables(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    let mut configs = Vec::with_capacity(queries.len());
+    for query in queries {
+        let table = query.find(&tables, pipeline).ok_or_else(|| format!("No table for {} in {}", query, input))?;
+        configs.push((query.to_string(), table));
+    }
+    
+    println!();
+    print!("{}", render_show(&configs, tn, width));
+    Ok(())
+}
+
+/// Render the report and write it
+fn run_report(input: &str, output: &str, format: ReportFormat, options: &ReportOptions) -> CliResult {
+    let tables = read_tables(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    println!("📊 Charting {} pools and {} TNs from {} tables...", options.pools.len(), options.tns.len(), tables.tables.len());
+    
+    let report = render_report(&tables, options, format);
+    println!("📝 Writing to {}", output);
+    std::fs::write(output, &report).map_err(|e| format!("Failed to write {}: {}", output, e))?;
+    println!("📦 File size: {}", format_file_size(report.len() as u64));
+    Ok(())
+}
+
+/// Append the tables to the database and show what it now holds
+fn run_sqlite(input: &str, output: &str) -> CliResult {
+    let tables = read_tables(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    println!("🗄️  Loading {} tables into {}...", tables.tables.len(), output);
+    
+    let run_id = write_sqlite_file(output, &tables).map_err(|e| format!("Failed to write {}: {}", output, e))?;
+    println!("✅ Appended run {}", run_id);
+    if let Ok(metadata) = std::fs::metadata(output) {
+        println!("📦 File size: {}", format_file_size(metadata.len()));
+    }
+    Ok(())
+}
+
+/// Write the long-format probability rows and the statistics table
+fn run_export(input: &str, output_dir: &str, format: ExportFormat) -> CliResult {
+    let tables = read_tables(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    println!("📤 Exporting {} tables as {}...", tables.tables.len(), format.extension());
+    
+    let paths = export_tables(&tables, output_dir, format).map_err(|e| format!("Failed to export tables: {}", e))?;
+    for path in paths {
+        let size = std::fs::metadata(&path)?.len();
+        println!("📝 Wrote {} ({})", path, format_file_size(size));
+    }
+    Ok(())
+}
+
+/// Replay a seeded roll log and report the first roll that differs
+fn run_replay(log: &str, seed: &str) -> CliResult {
+    let file = File::open(log).map_err(|e| format!("Failed to open {}: {}", log, e))?;
+    println!("🔁 Replaying {} with seed {:?}...", log, seed);
+    
+    let start_time = Instant::now();
+    let report = replay_log(seed, BufReader::new(file))?;
+    println!("⏱️  Total time: {:.2}s", start_time.elapsed().as_secs_f64());
+    
+    if let Some(mismatch) = &report.first_mismatch {
+        eprintln!("⚠️  Line {} differs", mismatch.line);
+        eprintln!("    logged:   {}", serde_json::to_string(&mismatch.logged)?);
+        eprintln!("    replayed: {}", serde_json::to_string(&mismatch.replayed)?);
+        return Err(format!("{} of {} rolls did not replay", report.mismatched, report.rolls).into());
+    }
+    println!("✅ All {} rolls replayed identically", report.rolls);
+    Ok(())
+}
+
+/// Test logged rolls against their expected distributions
+fn run_audit(log: &str, fdr: f64, output: Option<&str>) -> CliResult {
+    let file = File::open(log).map_err(|e| format!("Failed to open {}: {}", log, e))?;
+    let rolls: Vec<LoggedRoll> = read_roll_log(BufReader::new(file))
+        .map_err(|e| format!("Failed to read {}: {}", log, e))?;
+    println!("🕵️  Auditing {} rolls (false discovery rate {})...", rolls.len(), fdr);
+    
+    let report = audit_rolls(&rolls, fdr)?;
+    let tested: Vec<_> = report.groups.iter().filter(|g| !g.tests.is_empty()).collect();
+    println!(
+        "📊 {} configurations, {} with at least {} rolls tested",
+        report.groups.len(), tested.len(), MIN_GROUP_ROLLS
+    );
+    println!();
+    
+    for group in &tested {
+        let marker = if group.is_flagged() { "⚠️ " } else { "  " };
+        let tests = group.tests.iter()
+            .map(|t| format!("{} p = {:.3}", t.test, t.adjusted_p_value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{} {:<36} {:>6} rolls  mean {:>6.2} (expected {:>6.2})  {}",
+            marker, group.label, group.rolls, group.observed_mean, group.expected_mean, tests
+        );
+    }
+    println!();
+    
+    if report.flagged > 0 {
+        let comparisons: usize = tested.iter().map(|g| g.tests.len()).sum();
+        println!("⚠️  {} test(s) flagged after correcting for {} comparisons", report.flagged, comparisons);
+    } else {
+        println!("✅ No significant anomalies");
+    }
+    
+    if let Some(output) = output {
+        println!("📝 Writing to {}", output);
+        write_json_file(output, &report).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Score luck per player and session from a roll history
+fn run_luck(history: &str, filter: LuckFilter, top: usize, output: Option<&str>) -> CliResult {
+    let file = File::open(history).map_err(|e| format!("Failed to open {}: {}", history, e))?;
+    let rolls: Vec<HistoryRoll> = read_roll_log(BufReader::new(file))
+        .map_err(|e| format!("Failed to read {}: {}", history, e))?;
+    println!("🍀 Scoring luck over {} rolls...", rolls.len());
+    
+    let report = score_luck(&rolls, filter, top)?;
+    println!("📊 {} rolls by {} players match the filter", report.rolls, report.players.len());
+    println!();
+    
+    let show = |name: &str, luck: &LuckIndex| {
+        println!(
+            "{:<30} {:>6} rolls  luck {:>5.1}  (95% {:>5.1} - {:>5.1})",
+            name, luck.rolls, luck.index, luck.lower, luck.upper
+        );
+    };
+    for player in &report.players {
+        show(&player.player, &player.luck);
+        for session in &player.sessions {
+            show(&format!("  {}", session.session), &session.luck);
+        }
+    }
+    
+    for (title, rolls) in [("🌟 Luckiest rolls", &report.luckiest), ("💀 Unluckiest rolls", &report.unluckiest)] {
+        if rolls.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", title);
+        for roll in rolls {
+            println!("   {:<20} {:<30} {:>4}  (percentile {:.3})", roll.player, roll.label, roll.total, roll.percentile);
+        }
+    }
+    
+    if let Some(output) = output {
+        println!();
+        println!("📝 Writing to {}", output);
+        write_json_file(output, &report).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Generate a character probability sheet
+fn run_character(input: &str, schools: &str, output: &str, markdown: &str, rules: RuleSet) -> CliResult {
+    let character = Character::load(input)
+        .map_err(|e| format!("Failed to load character from {}: {}", input, e))?;
+    
+    // Techniques are optional: without a schools file the sheet uses the bare pools
+    let schools = match SchoolsFile::load(schools) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            println!("⚠️  No techniques applied ({}: {})", schools, e);
+            None
+        }
+    };
+    println!("👤 Generating sheet for {} ({} skills)...", character.name, character.skills.len());
+    
+    let start_time = Instant::now();
+    let sheet = generate_character_sheet(&character, schools.as_ref(), rules)?;
+    
+    println!("📝 Writing to {} and {}", output, markdown);
+    write_json_file(output, &sheet).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    std::fs::write(markdown, character_sheet_markdown(&sheet))
+        .map_err(|e| format!("Failed to write Markdown: {}", e))?;
+    println!("⏱️  Total time: {:.2}s", start_time.elapsed().as_secs_f64());
+    Ok(())
+}
+
+/// Path next to `input` with its extension replaced, e.g. yoshi.json → yoshi.sheet.md
+fn sibling_path(input: &str, extension: &str) -> String {
+    Path::new(input).with_extension(extension).to_string_lossy().into_owned()
 }
 
 fn print_progress(current: usize, total: usize, config: &RollConfig) {
     let percent = (current as f64 / total as f64) * 100.0;
-    let mode_str = match config.explosion_mode {
-        config::ExplosionMode::Unskilled => "u",
-        config::ExplosionMode::Skilled => "s",
-        config::ExplosionMode::Mastery => "m",
+    let mode_str = match config.explosion.preset() {
+        Some(ExplosionMode::Unskilled) => "u".to_string(),
+        Some(ExplosionMode::Skilled) => "s".to_string(),
+        Some(ExplosionMode::Mastery) => "m".to_string(),
+        None => config.explosion.slug(),
     };
     let emphasis_str = if config.emphasis { "+e" } else { "" };
     
@@ -113,21 +1174,21 @@ fn print_progress(current: usize, total: usize, config: &RollConfig) {
     );
 }
 
-fn show_sample_statistics(tables: &output::ProbabilityTables) {
+fn show_sample_statistics(tables: &ProbabilityTables) {
     println!();
     println!("📈 Sample Statistics:");
     println!();
     
     // Find some interesting examples
     let examples = [
-        (5, 3, config::ExplosionMode::Skilled, false, "5k3 skilled"),
-        (7, 4, config::ExplosionMode::Mastery, false, "7k4 mastery"),
-        (10, 10, config::ExplosionMode::Mastery, true, "10k10 mastery+emphasis"),
+        (5, 3, ExplosionMode::Skilled, false, "5k3 skilled"),
+        (7, 4, ExplosionMode::Mastery, false, "7k4 mastery"),
+        (10, 10, ExplosionMode::Mastery, true, "10k10 mastery+emphasis"),
     ];
     
     for (roll, keep, mode, emphasis, label) in examples {
         if let Some(table) = tables.tables.iter().find(|t| {
-            t.roll == roll && t.keep == keep && t.explosion_mode == mode && t.emphasis == emphasis
+            t.roll == roll && t.keep == keep && t.explosion_mode == Some(mode) && t.emphasis == emphasis
         }) {
             println!("  {} →", label);
             println!("    Mean:   {:.2}", table.statistics.mean);
@@ -140,6 +1201,11 @@ fn show_sample_statistics(tables: &output::ProbabilityTables) {
                 println!("    P(≥25): {:.1}%", prob * 100.0);
             }
             
+            // Show goodness of fit for simulated tables
+            if let Some(fit) = &table.goodness_of_fit {
+                println!("    Fit:    χ² p = {:.3}, KS p = {:.3}", fit.chi_square_p_value, fit.ks_p_value);
+            }
+            
             println!();
         }
     }
@@ -0,0 +1,345 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Simulation rounds per narrative dice configuration
+pub const NARRATIVE_ROUNDS: usize = 200_000;
+
+/// Symbols shown on one face of an L5R 5e die
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Face {
+    /// Successes (an explosive success counts as one)
+    pub success: u8,
+    /// Explosive success: keeping it rolls another die of the same type
+    pub explosive: bool,
+    pub opportunity: u8,
+    pub strife: u8,
+}
+
+impl Face {
+    const fn new(success: u8, explosive: bool, opportunity: u8, strife: u8) -> Self {
+        Self { success, explosive, opportunity, strife }
+    }
+
+    pub const BLANK: Face = Face::new(0, false, 0, 0);
+    pub const OPPORTUNITY: Face = Face::new(0, false, 1, 0);
+    pub const OPPORTUNITY_STRIFE: Face = Face::new(0, false, 1, 1);
+    pub const SUCCESS: Face = Face::new(1, false, 0, 0);
+    pub const SUCCESS_STRIFE: Face = Face::new(1, false, 0, 1);
+    pub const SUCCESS_OPPORTUNITY: Face = Face::new(1, false, 1, 0);
+    pub const EXPLOSIVE: Face = Face::new(1, true, 0, 0);
+    pub const EXPLOSIVE_STRIFE: Face = Face::new(1, true, 0, 1);
+}
+
+/// Ring die (d6) faces, per the 5e core rulebook
+pub const RING_FACES: [Face; 6] = [
+    Face::BLANK,
+    Face::OPPORTUNITY_STRIFE,
+    Face::OPPORTUNITY,
+    Face::SUCCESS_STRIFE,
+    Face::SUCCESS,
+    Face::EXPLOSIVE_STRIFE,
+];
+
+/// Skill die (d12) faces, per the 5e core rulebook
+pub const SKILL_FACES: [Face; 12] = [
+    Face::BLANK,
+    Face::BLANK,
+    Face::OPPORTUNITY,
+    Face::OPPORTUNITY,
+    Face::OPPORTUNITY,
+    Face::SUCCESS,
+    Face::SUCCESS,
+    Face::SUCCESS_STRIFE,
+    Face::SUCCESS_STRIFE,
+    Face::SUCCESS_OPPORTUNITY,
+    Face::EXPLOSIVE,
+    Face::EXPLOSIVE_STRIFE,
+];
+
+/// The two 5e die types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DieType {
+    Ring,
+    Skill,
+}
+
+impl DieType {
+    pub fn faces(&self) -> &'static [Face] {
+        match self {
+            DieType::Ring => &RING_FACES,
+            DieType::Skill => &SKILL_FACES,
+        }
+    }
+
+    fn roll<R: Rng>(&self, rng: &mut R) -> Face {
+        let faces = self.faces();
+        faces[rng.gen_range(0..faces.len())]
+    }
+}
+
+/// How the player chooses which dice to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeepPolicy {
+    /// Keep as many successes as possible, accepting strife
+    MaxSuccesses,
+    /// Keep successes and opportunities, but never a die with strife
+    AvoidStrife,
+    /// Keep opportunities first, then successes
+    MaxOpportunity,
+}
+
+impl KeepPolicy {
+    pub const ALL: [KeepPolicy; 3] = [KeepPolicy::MaxSuccesses, KeepPolicy::AvoidStrife, KeepPolicy::MaxOpportunity];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeepPolicy::MaxSuccesses => "max_successes",
+            KeepPolicy::AvoidStrife => "avoid_strife",
+            KeepPolicy::MaxOpportunity => "max_opportunity",
+        }
+    }
+
+    /// Preference order between faces; higher is kept first
+    fn priority(&self, face: &Face) -> (i32, i32, i32, i32) {
+        let success = face.success as i32;
+        let explosive = face.explosive as i32;
+        let opportunity = face.opportunity as i32;
+        let strife = -(face.strife as i32);
+        match self {
+            KeepPolicy::MaxSuccesses => (success, explosive, opportunity, strife),
+            KeepPolicy::AvoidStrife => (strife, success, explosive, opportunity),
+            KeepPolicy::MaxOpportunity => (opportunity, success, explosive, strife),
+        }
+    }
+
+    /// Whether a face is worth keeping at all
+    pub fn wants(&self, face: &Face) -> bool {
+        let useful = face.success > 0 || face.opportunity > 0;
+        match self {
+            KeepPolicy::MaxSuccesses => face.success > 0 || (face.opportunity > 0 && face.strife == 0),
+            KeepPolicy::AvoidStrife => useful && face.strife == 0,
+            KeepPolicy::MaxOpportunity => useful,
+        }
+    }
+}
+
+/// One 5e check: roll Ring + Skill dice, keep up to Ring dice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NarrativeConfig {
+    pub ring: u8,
+    pub skill: u8,
+    pub policy: KeepPolicy,
+}
+
+impl NarrativeConfig {
+    pub fn new(ring: u8, skill: u8, policy: KeepPolicy) -> Self {
+        Self { ring, skill, policy }
+    }
+}
+
+/// Symbols on the kept dice of one check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NarrativeResult {
+    pub successes: u32,
+    pub opportunity: u32,
+    pub strife: u32,
+}
+
+impl NarrativeResult {
+    fn add(&mut self, face: &Face) {
+        self.successes += face.success as u32;
+        self.opportunity += face.opportunity as u32;
+        self.strife += face.strife as u32;
+    }
+}
+
+/// Generate all 5e configurations (Ring 1-5, Skill 0-5, every policy)
+pub fn generate_narrative_configs() -> Vec<NarrativeConfig> {
+    let mut configs = Vec::new();
+    for ring in 1..=5 {
+        for skill in 0..=5 {
+            for policy in KeepPolicy::ALL {
+                configs.push(NarrativeConfig::new(ring, skill, policy));
+            }
+        }
+    }
+    configs
+}
+
+/// Choose which rolled dice to keep
+///
+/// Keeps up to `max_keep` dice the policy wants, best first. At least one die
+/// is always kept, as the rules require.
+pub fn choose_kept(faces: &[(DieType, Face)], max_keep: usize, policy: KeepPolicy) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..faces.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(policy.priority(&faces[i].1)));
+
+    let mut kept: Vec<usize> = order.iter()
+        .copied()
+        .filter(|&i| policy.wants(&faces[i].1))
+        .take(max_keep)
+        .collect();
+    if kept.is_empty() {
+        kept.extend(order.first());
+    }
+    kept
+}
+
+/// Simulate a single 5e check
+pub fn simulate_narrative_roll<R: Rng>(config: &NarrativeConfig, rng: &mut R) -> NarrativeResult {
+    // Step 1: Roll Ring and Skill dice
+    let rolled: Vec<(DieType, Face)> = (0..config.ring).map(|_| DieType::Ring)
+        .chain((0..config.skill).map(|_| DieType::Skill))
+        .map(|die| (die, die.roll(rng)))
+        .collect();
+
+    // Step 2: Keep up to Ring dice
+    let mut result = NarrativeResult::default();
+    let mut pending: Vec<(DieType, Face)> = Vec::new();
+    for i in choose_kept(&rolled, config.ring as usize, config.policy) {
+        result.add(&rolled[i].1);
+        pending.push(rolled[i]);
+    }
+
+    // Step 3: Each kept explosive success rolls another die of the same type,
+    // which may be kept in addition to the others
+    while let Some((die, face)) = pending.pop() {
+        if !face.explosive {
+            continue;
+        }
+        let extra = die.roll(rng);
+        if config.policy.wants(&extra) {
+            result.add(&extra);
+            pending.push((die, extra));
+        }
+    }
+
+    result
+}
+
+/// Aggregated results of many simulated checks
+#[derive(Debug, Clone)]
+pub struct NarrativeHistogram {
+    /// Number of checks with exactly `i` successes
+    pub successes: Vec<usize>,
+    pub total_opportunity: u64,
+    pub total_strife: u64,
+    pub rounds: usize,
+}
+
+impl NarrativeHistogram {
+    /// P(successes ≥ n) for n = 1 up to the highest observed count
+    pub fn success_at_least(&self) -> Vec<f64> {
+        let mut at_least = Vec::new();
+        let mut remaining = self.rounds - self.successes.first().copied().unwrap_or(0);
+        for count in self.successes.iter().skip(1) {
+            at_least.push(remaining as f64 / self.rounds as f64);
+            remaining -= count;
+        }
+        at_least
+    }
+
+    pub fn expected_successes(&self) -> f64 {
+        let total: usize = self.successes.iter().enumerate().map(|(n, c)| n * c).sum();
+        total as f64 / self.rounds as f64
+    }
+
+    pub fn expected_opportunity(&self) -> f64 {
+        self.total_opportunity as f64 / self.rounds as f64
+    }
+
+    pub fn expected_strife(&self) -> f64 {
+        self.total_strife as f64 / self.rounds as f64
+    }
+}
+
+/// Simulate a 5e configuration
+pub fn simulate_narrative(config: &NarrativeConfig, rounds: usize) -> NarrativeHistogram {
+    let mut rng = rand::thread_rng();
+    let mut histogram = NarrativeHistogram {
+        successes: Vec::new(),
+        total_opportunity: 0,
+        total_strife: 0,
+        rounds,
+    };
+
+    for _ in 0..rounds {
+        let result = simulate_narrative_roll(config, &mut rng);
+        let n = result.successes as usize;
+        if histogram.successes.len() <= n {
+            histogram.successes.resize(n + 1, 0);
+        }
+        histogram.successes[n] += 1;
+        histogram.total_opportunity += result.opportunity as u64;
+        histogram.total_strife += result.strife as u64;
+    }
+
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mean(faces: &[Face], f: impl Fn(&Face) -> u8) -> f64 {
+        faces.iter().map(|face| f(face) as f64).sum::<f64>() / faces.len() as f64
+    }
+
+    #[test]
+    fn test_face_definitions() {
+        assert_eq!(mean(&RING_FACES, |f| f.success), 0.5);
+        assert_eq!(mean(&RING_FACES, |f| f.strife), 0.5);
+        assert_eq!(mean(&RING_FACES, |f| f.opportunity), 2.0 / 6.0);
+
+        assert_eq!(mean(&SKILL_FACES, |f| f.success), 7.0 / 12.0);
+        assert_eq!(mean(&SKILL_FACES, |f| f.strife), 3.0 / 12.0);
+        assert_eq!(mean(&SKILL_FACES, |f| f.opportunity), 4.0 / 12.0);
+        assert_eq!(SKILL_FACES.iter().filter(|f| f.explosive).count(), 2);
+    }
+
+    #[test]
+    fn test_keep_policies() {
+        let rolled = [
+            (DieType::Ring, Face::SUCCESS_STRIFE),
+            (DieType::Ring, Face::OPPORTUNITY),
+            (DieType::Skill, Face::EXPLOSIVE),
+            (DieType::Skill, Face::BLANK),
+        ];
+
+        // Successes first, explosive preferred
+        assert_eq!(choose_kept(&rolled, 2, KeepPolicy::MaxSuccesses), vec![2, 0]);
+        // Strife is never kept
+        assert_eq!(choose_kept(&rolled, 2, KeepPolicy::AvoidStrife), vec![2, 1]);
+        assert_eq!(choose_kept(&rolled, 3, KeepPolicy::AvoidStrife), vec![2, 1]);
+        // Opportunity first
+        assert_eq!(choose_kept(&rolled, 1, KeepPolicy::MaxOpportunity), vec![1]);
+
+        // At least one die is kept, even if unwanted
+        let bad = [(DieType::Ring, Face::OPPORTUNITY_STRIFE), (DieType::Ring, Face::SUCCESS_STRIFE)];
+        assert_eq!(choose_kept(&bad, 2, KeepPolicy::AvoidStrife).len(), 1);
+    }
+
+    #[test]
+    fn test_single_ring_die() {
+        // One ring die: P(≥1 success) = 3/6, and explosions only add to it
+        let config = NarrativeConfig::new(1, 0, KeepPolicy::MaxSuccesses);
+        let histogram = simulate_narrative(&config, 100_000);
+        let at_least = histogram.success_at_least();
+
+        assert!((at_least[0] - 0.5).abs() < 0.01);
+        // P(≥2) = P(explosive) × P(extra die has a success) = 1/6 × 1/2
+        assert!((at_least[1] - 1.0 / 12.0).abs() < 0.01);
+        // Strife: 1/2 on the die, plus 0.4 per explosion chain (kept strife faces)
+        assert!((histogram.expected_strife() - (0.5 + 0.4 / 6.0)).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_avoid_strife_trades_successes() {
+        let greedy = simulate_narrative(&NarrativeConfig::new(3, 3, KeepPolicy::MaxSuccesses), 50_000);
+        let careful = simulate_narrative(&NarrativeConfig::new(3, 3, KeepPolicy::AvoidStrife), 50_000);
+
+        assert!(greedy.expected_successes() > careful.expected_successes());
+        assert!(greedy.expected_strife() > careful.expected_strife());
+    }
+}
@@ -2,39 +2,126 @@ use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use chrono::Utc;
 
-use crate::config::{ExplosionMode, RollConfig};
+use crate::config::{Engine, ExplosionMode, ExplosionRule, RollConfig};
+use crate::error::{Error, Result};
+use crate::fit::GoodnessOfFit;
+use crate::narrative::{Face, KeepPolicy, NarrativeConfig, NarrativeHistogram, RING_FACES, SKILL_FACES};
+use crate::pipeline::Stage;
+use crate::rules::{RuleSet, TenDiceRule};
 use crate::stats::{Statistics, PROBABILITY_CUTOFF};
 
 /// Root structure for the JSON output
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ProbabilityTables {
     pub version: String,
     pub generated_at: String,
+    /// Engine used to compute the distributions
+    #[serde(default)]
+    pub engine: Engine,
+    /// Rule set (edition) the tables were generated for
+    #[serde(default)]
+    pub rule_set: RuleSet,
+    /// Conversion to apply to pools above 10 dice before looking them up
+    #[serde(default)]
+    pub ten_dice_rule: TenDiceRule,
     pub simulation_rounds: SimulationRounds,
     pub probability_cutoff: f64,
     pub tables: Vec<ProbabilityTable>,
 }
 
 /// Simulation round counts per explosion mode
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SimulationRounds {
     pub unskilled: usize,
     pub skilled: usize,
     pub mastery: usize,
+    /// Rounds used for a non-preset explosion rule, if the run had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom: Option<usize>,
+}
+
+impl SimulationRounds {
+    /// Rounds simulated for tables with this explosion rule
+    pub fn for_rule(&self, rule: ExplosionRule) -> usize {
+        match rule.preset() {
+            Some(ExplosionMode::Unskilled) => self.unskilled,
+            Some(ExplosionMode::Skilled) => self.skilled,
+            Some(ExplosionMode::Mastery) => self.mastery,
+            None => self.custom.unwrap_or_else(|| rule.simulation_rounds()),
+        }
+    }
 }
 
 /// A single probability table entry
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "ProbabilityTableJson")]
 pub struct ProbabilityTable {
     pub roll: u8,
     pub keep: u8,
-    pub explosion_mode: ExplosionMode,
+    /// Preset name; absent when the table uses a custom explosion rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explosion_mode: Option<ExplosionMode>,
+    pub explosion: ExplosionRule,
     pub emphasis: bool,
+    /// Extra roll pipeline stages (emphasis excluded); absent for standard rolls
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipeline: Vec<Stage>,
     pub statistics: Statistics,
     pub cumulative_probability: BTreeMap<String, f64>,
+    /// Fit of the simulated histogram to the exact distribution; absent for exact tables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goodness_of_fit: Option<GoodnessOfFit>,
+}
+
+/// A table as read from JSON
+///
+/// Tables written before explosion rules existed only have `explosion_mode`;
+/// their rule is the preset's.
+#[derive(Deserialize, JsonSchema)]
+struct ProbabilityTableJson {
+    roll: u8,
+    keep: u8,
+    /// Preset name; absent when the table uses a custom explosion rule
+    #[serde(default)]
+    explosion_mode: Option<ExplosionMode>,
+    /// Explosion rule; absent in tables written before custom rules, which use the preset's
+    #[serde(default)]
+    explosion: Option<ExplosionRule>,
+    emphasis: bool,
+    /// Extra roll pipeline stages (emphasis excluded); absent for standard rolls
+    #[serde(default)]
+    pipeline: Vec<Stage>,
+    statistics: Statistics,
+    cumulative_probability: BTreeMap<String, f64>,
+    /// Fit of the simulated histogram to the exact distribution; absent for exact tables
+    #[serde(default)]
+    goodness_of_fit: Option<GoodnessOfFit>,
+}
+
+impl TryFrom<ProbabilityTableJson> for ProbabilityTable {
+    type Error = Error;
+
+    fn try_from(json: ProbabilityTableJson) -> Result<Self> {
+        let explosion = json.explosion.or(json.explosion_mode.map(|mode| mode.rule())).ok_or_else(|| {
+            Error::InvalidData(format!("{}k{}: table has neither explosion nor explosion_mode", json.roll, json.keep))
+        })?;
+
+        Ok(Self {
+            roll: json.roll,
+            keep: json.keep,
+            explosion_mode: json.explosion_mode,
+            explosion,
+            emphasis: json.emphasis,
+            pipeline: json.pipeline,
+            statistics: json.statistics,
+            cumulative_probability: json.cumulative_probability,
+            goodness_of_fit: json.goodness_of_fit,
+        })
+    }
 }
 
 impl ProbabilityTable {
@@ -52,34 +139,170 @@ impl ProbabilityTable {
         Self {
             roll: config.roll,
             keep: config.keep,
-            explosion_mode: config.explosion_mode,
+            explosion_mode: config.explosion.preset(),
+            explosion: config.explosion,
             emphasis: config.emphasis,
+            pipeline: config.pipeline.stages.clone(),
             statistics,
             cumulative_probability,
+            goodness_of_fit: None,
+        }
+    }
+}
+
+impl ProbabilityTable {
+    /// P(total ≥ TN) for every TN from the first stored TN to the last
+    ///
+    /// Returns the first TN and the run. TNs the table skips get the value of
+    /// the next stored TN, which is what a lookup of them returns anyway.
+    pub fn dense_cumulative(&self) -> Result<(i32, Vec<f64>)> {
+        let mut cumulative = Vec::with_capacity(self.cumulative_probability.len());
+        for (tn, &p) in &self.cumulative_probability {
+            let tn = tn.parse::<i32>()
+                .map_err(|_| Error::InvalidData(format!("{}k{}: bad TN '{}'", self.roll, self.keep, tn)))?;
+            cumulative.push((tn, p));
+        }
+        // String keys sort "10" before "2"
+        cumulative.sort_by_key(|&(tn, _)| tn);
+        
+        let first_tn = cumulative.first().map_or(0, |&(tn, _)| tn);
+        let mut dense: Vec<f64> = cumulative.first().map(|&(_, p)| p).into_iter().collect();
+        for window in cumulative.windows(2) {
+            let ((tn, _), (next, p)) = (window[0], window[1]);
+            dense.extend(std::iter::repeat_n(p, (next - tn) as usize));
         }
+        Ok((first_tn, dense))
+    }
+
+    /// P(total ≥ TN), using the smallest stored total at or above the TN
+    pub fn probability_at_least(&self, tn: i32) -> f64 {
+        self.cumulative_probability.iter()
+            .filter_map(|(k, &p)| k.parse::<i32>().ok().map(|k| (k, p)))
+            .filter(|&(k, _)| k >= tn)
+            .min_by_key(|&(k, _)| k)
+            .map_or(0.0, |(_, p)| p)
     }
 }
 
 /// Create the full probability tables structure
-pub fn create_probability_tables(tables: Vec<ProbabilityTable>) -> ProbabilityTables {
+pub fn create_probability_tables(
+    tables: Vec<ProbabilityTable>,
+    engine: Engine,
+    rule_set: RuleSet,
+) -> ProbabilityTables {
+    let custom = tables.iter()
+        .find(|t| t.explosion_mode.is_none())
+        .map(|t| t.explosion.simulation_rounds());
+    
     ProbabilityTables {
         version: "1.0.0".to_string(),
         generated_at: Utc::now().to_rfc3339(),
+        engine,
+        rule_set,
+        ten_dice_rule: rule_set.ten_dice_rule(),
         simulation_rounds: SimulationRounds {
             unskilled: ExplosionMode::Unskilled.simulation_rounds(),
             skilled: ExplosionMode::Skilled.simulation_rounds(),
             mastery: ExplosionMode::Mastery.simulation_rounds(),
+            custom,
         },
         probability_cutoff: PROBABILITY_CUTOFF,
         tables,
     }
 }
 
-/// Write probability tables to JSON file
-pub fn write_json_file<P: AsRef<Path>>(
+/// Tables for `configs` as a generation run with `engine` writes them
+#[cfg(test)]
+pub(crate) fn fixture_tables(configs: &[RollConfig], engine: Engine) -> ProbabilityTables {
+    let tables = configs.iter()
+        .map(|c| match engine {
+            Engine::Exact => crate::generate_table(c, engine).unwrap(),
+            // A simulated table may fail its fit check by chance
+            Engine::Simulation => crate::check_table(c, engine).0,
+        })
+        .collect();
+    create_probability_tables(tables, engine, RuleSet::default())
+}
+
+/// [`fixture_tables`] migrated to schema v2
+#[cfg(test)]
+pub(crate) fn fixture_tables_v2(configs: &[RollConfig], engine: Engine, precision: u32) -> crate::schema::ProbabilityTablesV2 {
+    crate::schema::ProbabilityTablesV2::from_v1(&fixture_tables(configs, engine), precision).unwrap()
+}
+
+/// Root structure for L5R 5e narrative dice tables
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NarrativeTables {
+    pub version: String,
+    pub generated_at: String,
+    pub dice_system: String,
+    pub simulation_rounds: usize,
+    /// Face definitions used for the simulation
+    pub faces: NarrativeFaces,
+    pub tables: Vec<NarrativeTable>,
+}
+
+/// Ring and skill die faces
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NarrativeFaces {
+    pub ring: Vec<Face>,
+    pub skill: Vec<Face>,
+}
+
+/// A single narrative dice table entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NarrativeTable {
+    pub ring: u8,
+    pub skill: u8,
+    pub policy: KeepPolicy,
+    pub expected_successes: f64,
+    pub expected_opportunity: f64,
+    pub expected_strife: f64,
+    /// P(successes >= N) for each N
+    pub success_at_least: BTreeMap<String, f64>,
+}
+
+impl NarrativeTable {
+    pub fn new(config: &NarrativeConfig, histogram: &NarrativeHistogram) -> Self {
+        let success_at_least = histogram.success_at_least()
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, p)| p > PROBABILITY_CUTOFF)
+            .map(|(i, p)| ((i + 1).to_string(), p))
+            .collect();
+        
+        Self {
+            ring: config.ring,
+            skill: config.skill,
+            policy: config.policy,
+            expected_successes: histogram.expected_successes(),
+            expected_opportunity: histogram.expected_opportunity(),
+            expected_strife: histogram.expected_strife(),
+            success_at_least,
+        }
+    }
+}
+
+/// Create the full narrative tables structure
+pub fn create_narrative_tables(tables: Vec<NarrativeTable>, rounds: usize) -> NarrativeTables {
+    NarrativeTables {
+        version: "1.0.0".to_string(),
+        generated_at: Utc::now().to_rfc3339(),
+        dice_system: "l5r5e".to_string(),
+        simulation_rounds: rounds,
+        faces: NarrativeFaces {
+            ring: RING_FACES.to_vec(),
+            skill: SKILL_FACES.to_vec(),
+        },
+        tables,
+    }
+}
+
+/// Write probability tables (or any other output structure) to a JSON file
+pub fn write_json_file<P: AsRef<Path>, T: Serialize>(
     path: P,
-    tables: &ProbabilityTables,
-) -> Result<(), Box<dyn std::error::Error>> {
+    tables: &T,
+) -> Result<()> {
     let json = serde_json::to_string_pretty(tables)?;
     
     // Create parent directories if they don't exist
@@ -111,10 +334,9 @@ pub fn format_file_size(bytes: u64) -> String {
 mod tests {
     use super::*;
 
-
     #[test]
     fn test_create_probability_table() {
-        let config = RollConfig::new(5, 3, ExplosionMode::Skilled, false);
+        let config = RollConfig::new(5, 3, ExplosionMode::Skilled, false).unwrap();
         
         let stats = Statistics {
             mean: 25.5,
@@ -135,7 +357,8 @@ mod tests {
         
         assert_eq!(table.roll, 5);
         assert_eq!(table.keep, 3);
-        assert_eq!(table.explosion_mode, ExplosionMode::Skilled);
+        assert_eq!(table.explosion_mode, Some(ExplosionMode::Skilled));
+        assert_eq!(table.explosion, ExplosionMode::Skilled.rule());
         assert!(!table.emphasis);
         assert_eq!(table.statistics.mean, 25.5);
     }
@@ -146,5 +369,54 @@ mod tests {
         assert_eq!(format_file_size(2048), "2.0 KB");
         assert_eq!(format_file_size(1_500_000), "1.43 MB");
     }
-}
 
+    #[test]
+    fn test_custom_rule_table() {
+        let rule = ExplosionRule::new(Some(8), Some(1)).unwrap();
+        let config = RollConfig::new(3, 2, rule, false).unwrap();
+        
+        let stats = Statistics {
+            mean: 12.0,
+            stddev: 4.0,
+            median: 12,
+            percentile_25: 9,
+            percentile_75: 15,
+            min: 2,
+            max: 36,
+        };
+        
+        let table = ProbabilityTable::new(&config, stats, BTreeMap::new());
+        assert_eq!(table.explosion_mode, None);
+        
+        let tables = create_probability_tables(vec![table], Engine::Simulation, RuleSet::Legacy);
+        assert_eq!(tables.simulation_rounds.custom, Some(rule.simulation_rounds()));
+        
+        // The rule is serialized alongside the table, the preset name is omitted
+        let json = serde_json::to_value(&tables).unwrap();
+        assert_eq!(json["tables"][0]["explosion"]["threshold"], 8);
+        assert_eq!(json["tables"][0]["explosion"]["max_chain"], 1);
+        assert!(json["tables"][0].get("explosion_mode").is_none());
+        assert!(json["tables"][0].get("pipeline").is_none());
+        
+        // The rule set and its Ten Dice Rule are recorded in the metadata
+        assert_eq!(json["rule_set"], "legacy");
+        assert_eq!(json["ten_dice_rule"]["rolled_per_kept"], 1);
+    }
+
+    #[test]
+    fn test_read_tables_without_explosion() {
+        // Table as written before explosion rules: only the preset name
+        let json = r#"{
+            "roll": 2, "keep": 1, "explosion_mode": "mastery", "emphasis": false,
+            "statistics": {"mean": 8.0, "stddev": 4.0, "median": 8, "percentile_25": 5,
+                           "percentile_75": 10, "min": 1, "max": 40},
+            "cumulative_probability": {"0": 1.0, "10": 0.4}
+        }"#;
+        let table: ProbabilityTable = serde_json::from_str(json).unwrap();
+        assert_eq!(table.explosion, ExplosionMode::Mastery.rule());
+        
+        // Neither field: the rule is unknown
+        let json = json.replace(r#""explosion_mode": "mastery", "#, "");
+        assert!(serde_json::from_str::<ProbabilityTable>(&json).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Which dice a keep stage selects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeepWhich {
+    #[default]
+    Highest, // Standard Roll & Keep
+    Lowest,  // Disadvantage-style effects
+}
+
+/// A single roll-modifying stage
+///
+/// Stages run after the dice are rolled with the config's explosion rule.
+/// Dice rerolled by a stage are fresh exploding dice: earlier die stages are
+/// not applied to them again, matching how emphasis works in dice.ts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum Stage {
+    /// Reroll every non-exploded die showing `at_most` or less, once (emphasis)
+    Reroll { at_most: u8 },
+    /// Treat non-exploded dice showing `from` as showing `to` (e.g. 1s as 2s)
+    Remap { from: u8, to: u8 },
+    /// Reroll the `count` lowest dice once and use the new results
+    RerollLowest { count: u8 },
+    /// Choose which dice are kept (default: highest)
+    Keep { which: KeepWhich },
+    /// Add `bonus` to every kept die
+    KeptDieBonus { bonus: i32 },
+    /// Add `bonus` to the total
+    Modifier { bonus: i32 },
+}
+
+/// Pipeline phases; stages must appear in phase order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Die,   // Per-die changes before keeping
+    Pool,  // Changes that depend on the whole pool
+    Keep,  // Selection of kept dice
+    Total, // Changes to the kept total
+}
+
+impl Stage {
+    pub fn phase(&self) -> Phase {
+        match self {
+            Stage::Reroll { .. } | Stage::Remap { .. } => Phase::Die,
+            Stage::RerollLowest { .. } => Phase::Pool,
+            Stage::Keep { .. } => Phase::Keep,
+            Stage::KeptDieBonus { .. } | Stage::Modifier { .. } => Phase::Total,
+        }
+    }
+}
+
+/// Composable roll pipeline: roll → reroll → keep → modify
+///
+/// The empty pipeline is the standard roll: keep the highest dice and sum them.
+/// Emphasis is not part of the pipeline; `RollConfig::stages` prepends it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(try_from = "RollPipelineJson")]
+pub struct RollPipeline {
+    pub stages: Vec<Stage>,
+}
+
+/// A pipeline as read from JSON, checked by [`RollPipeline::validate`]
+#[derive(Deserialize)]
+struct RollPipelineJson {
+    stages: Vec<Stage>,
+}
+
+impl TryFrom<RollPipelineJson> for RollPipeline {
+    type Error = Error;
+
+    fn try_from(json: RollPipelineJson) -> Result<Self> {
+        RollPipeline::new(json.stages)
+    }
+}
+
+impl RollPipeline {
+    pub fn new(stages: Vec<Stage>) -> Result<Self> {
+        let pipeline = Self { stages };
+        pipeline.validate()?;
+        Ok(pipeline)
+    }
+
+    /// Load a pipeline definition from a JSON file
+    pub fn from_json_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let pipeline: RollPipelineJson = serde_json::from_str(&json)?;
+        Self::new(pipeline.stages)
+    }
+
+    /// Check stage order and parameters
+    ///
+    /// Both engines rely on this: stages must be in phase order, with at most
+    /// one pool stage and one keep stage.
+    pub fn validate(&self) -> Result<()> {
+        let mut last_phase = Phase::Die;
+
+        for stage in &self.stages {
+            let phase = stage.phase();
+            if phase < last_phase {
+                return Err(Error::InvalidPipeline(format!("stage {:?} must come before {:?} stages", stage, last_phase)));
+            }
+            if phase == last_phase && matches!(phase, Phase::Pool | Phase::Keep) {
+                return Err(Error::InvalidPipeline(format!("only one {:?} stage is allowed", phase)));
+            }
+
+            match *stage {
+                Stage::Reroll { at_most } if !(1..=10).contains(&at_most) => {
+                    return Err(Error::InvalidPipeline(format!("reroll threshold must be 1-10, got {}", at_most)));
+                }
+                Stage::Remap { from, to } if !(1..=10).contains(&from) || !(1..=10).contains(&to) => {
+                    return Err(Error::InvalidPipeline(format!("remap faces must be 1-10, got {} → {}", from, to)));
+                }
+                Stage::RerollLowest { count: 0 } => {
+                    return Err(Error::InvalidPipeline("reroll_lowest count must be at least 1".to_string()));
+                }
+                _ => {}
+            }
+
+            last_phase = phase;
+        }
+
+        Ok(())
+    }
+
+    /// Which dice are kept
+    pub fn keep_which(&self) -> KeepWhich {
+        self.stages.iter()
+            .find_map(|s| match s {
+                Stage::Keep { which } => Some(*which),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Number of lowest dice rerolled by the pool stage (0 if none)
+    pub fn reroll_lowest(&self) -> u8 {
+        self.stages.iter()
+            .find_map(|s| match s {
+                Stage::RerollLowest { count } => Some(*count),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    /// Total added after keeping `keep` dice
+    pub fn total_bonus(&self, keep: u8) -> i32 {
+        self.stages.iter()
+            .map(|s| match s {
+                Stage::KeptDieBonus { bonus } => bonus * keep as i32,
+                Stage::Modifier { bonus } => *bonus,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Short human-readable summary, e.g. `1s as 2s, reroll lowest 1, +1/die`
+    pub fn describe(&self) -> String {
+        self.stages.iter()
+            .map(|s| match s {
+                Stage::Reroll { at_most } => format!("reroll≤{}", at_most),
+                Stage::Remap { from, to } => format!("{}s as {}s", from, to),
+                Stage::RerollLowest { count } => format!("reroll lowest {}", count),
+                Stage::Keep { which: KeepWhich::Highest } => "keep highest".to_string(),
+                Stage::Keep { which: KeepWhich::Lowest } => "keep lowest".to_string(),
+                Stage::KeptDieBonus { bonus } => format!("{:+}/die", bonus),
+                Stage::Modifier { bonus } => format!("{:+}", bonus),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_from_json() {
+        let json = r#"{
+            "stages": [
+                { "stage": "remap", "from": 1, "to": 2 },
+                { "stage": "reroll_lowest", "count": 1 },
+                { "stage": "keep", "which": "lowest" },
+                { "stage": "kept_die_bonus", "bonus": 1 }
+            ]
+        }"#;
+
+        let pipeline: RollPipeline = serde_json::from_str(json).unwrap();
+        assert!(pipeline.validate().is_ok());
+        assert_eq!(pipeline.keep_which(), KeepWhich::Lowest);
+        assert_eq!(pipeline.reroll_lowest(), 1);
+        assert_eq!(pipeline.total_bonus(3), 3);
+    }
+
+    #[test]
+    fn test_pipeline_phase_order() {
+        // Keep before a die stage is rejected
+        let bad = RollPipeline::new(vec![
+            Stage::Keep { which: KeepWhich::Highest },
+            Stage::Reroll { at_most: 1 },
+        ]);
+        assert!(bad.is_err());
+
+        // Two keep stages are rejected
+        let bad = RollPipeline::new(vec![
+            Stage::Keep { which: KeepWhich::Highest },
+            Stage::Keep { which: KeepWhich::Lowest },
+        ]);
+        assert!(bad.is_err());
+
+        // Multiple die and total stages are fine
+        let good = RollPipeline::new(vec![
+            Stage::Reroll { at_most: 2 },
+            Stage::Remap { from: 1, to: 2 },
+            Stage::KeptDieBonus { bonus: 1 },
+            Stage::Modifier { bonus: -5 },
+        ]);
+        assert!(good.is_ok());
+
+        // Pipelines read from JSON are checked the same way
+        let json = r#"{"stages": [{"stage": "keep", "which": "highest"}, {"stage": "reroll", "at_most": 1}]}"#;
+        assert!(serde_json::from_str::<RollPipeline>(json).is_err());
+        let json = r#"{"stages": [{"stage": "reroll", "at_most": 0}]}"#;
+        assert!(serde_json::from_str::<RollPipeline>(json).is_err());
+    }
+
+    #[test]
+    fn test_default_pipeline() {
+        let pipeline = RollPipeline::default();
+        assert!(pipeline.stages.is_empty());
+        assert_eq!(pipeline.keep_which(), KeepWhich::Highest);
+        assert_eq!(pipeline.total_bonus(5), 0);
+    }
+}
@@ -0,0 +1,100 @@
+use std::io::BufRead;
+use serde::{Deserialize, Serialize};
+
+use crate::dice::{execute_roll, RollResult};
+use crate::error::{Error, Result};
+use crate::seedrandom::SeedRandom;
+
+/// A logged roll that the replay did not reproduce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayMismatch {
+    /// 1-based line in the log
+    pub line: usize,
+    pub logged: RollResult,
+    pub replayed: RollResult,
+}
+
+/// Outcome of replaying a roll log
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub rolls: usize,
+    pub mismatched: usize,
+    /// Later rolls usually differ too once the dice streams diverge
+    pub first_mismatch: Option<ReplayMismatch>,
+}
+
+/// Replay a JSONL log of `RollResult`s made after seeding the bot with `seed`
+///
+/// The log must hold every roll since the seed was set, in order: each roll
+/// consumes random numbers the next one depends on. Blank lines are skipped,
+/// and fields other than `RollResult`'s (e.g. `player`, `timestamp`) ignored.
+pub fn replay_log<R: BufRead>(seed: &str, log: R) -> Result<ReplayReport> {
+    let mut rng = SeedRandom::new(seed);
+    let mut report = ReplayReport::default();
+
+    for (index, line) in log.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |e: serde_json::Error| Error::InvalidData(format!("line {}: {}", index + 1, e));
+        let logged: RollResult = serde_json::from_str(&line).map_err(invalid)?;
+
+        let replayed = execute_roll(logged.expression, logged.options, &mut rng);
+        report.rolls += 1;
+        if replayed != logged {
+            report.mismatched += 1;
+            if report.first_mismatch.is_none() {
+                report.first_mismatch = Some(ReplayMismatch { line: index + 1, logged, replayed });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExplosionMode;
+    use crate::dice::{RollExpression, RollOptions};
+
+    #[test]
+    fn test_replay_log() {
+        let expression = RollExpression { roll: 5, keep: 3, modifier: 0 };
+        let mut rng = SeedRandom::new("session-1");
+        let mut log = String::new();
+        for _ in 0..20 {
+            let options = RollOptions { explosion_mode: ExplosionMode::Skilled, target_number: None, called_raises: None, emphasis_threshold: None };
+            let result = execute_roll(expression, options, &mut rng);
+            log.push_str(&serde_json::to_string(&result).unwrap());
+            log.push('\n');
+        }
+
+        let report = replay_log("session-1", log.as_bytes()).unwrap();
+        assert_eq!((report.rolls, report.mismatched), (20, 0));
+
+        // The wrong seed reproduces nothing
+        let report = replay_log("session-2", log.as_bytes()).unwrap();
+        assert_eq!(report.first_mismatch.unwrap().line, 1);
+    }
+
+    #[test]
+    fn test_replay_ignores_extra_fields() {
+        let expression = RollExpression { roll: 4, keep: 2, modifier: 1 };
+        let options = RollOptions { explosion_mode: ExplosionMode::Skilled, target_number: Some(15), called_raises: None, emphasis_threshold: None };
+        let mut rng = SeedRandom::new("session-1");
+        let mut log = String::new();
+        for i in 0..5 {
+            let mut line = serde_json::to_value(execute_roll(expression, options, &mut rng)).unwrap();
+            line["player"] = "kakita".into();
+            line["session"] = "s1".into();
+            line["timestamp"] = format!("2024-05-01T20:0{}:00Z", i).into();
+            log.push_str(&line.to_string());
+            log.push('\n');
+        }
+
+        let report = replay_log("session-1", log.as_bytes()).unwrap();
+        assert_eq!((report.rolls, report.mismatched), (5, 0));
+    }
+}
@@ -0,0 +1,547 @@
+use std::fmt::Write;
+
+use crate::config::{ExplosionMode, ExplosionRule, Pool};
+use crate::pipeline::Stage;
+use crate::schema::{ProbabilityTableV2, ProbabilityTablesV2};
+
+/// Pools charted when none are requested
+pub const DEFAULT_POOLS: [Pool; 4] = [
+    Pool { roll: 3, keep: 2 },
+    Pool { roll: 5, keep: 3 },
+    Pool { roll: 7, keep: 4 },
+    Pool { roll: 10, keep: 5 },
+];
+
+/// TNs of the XkY heatmaps when none are requested
+pub const DEFAULT_HEATMAP_TNS: [i32; 4] = [15, 20, 25, 30];
+
+/// Charts stop where P(total ≥ TN) drops below this
+const TAIL_CUTOFF: f64 = 0.001;
+
+/// Series colours of the success curves
+const PALETTE: [&str; 8] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f"];
+
+// Chart geometry, in SVG user units
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 260.0;
+const MARGIN_LEFT: f64 = 48.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 16.0;
+const MARGIN_BOTTOM: f64 = 36.0;
+const CELL: f64 = 44.0;
+
+/// Report document format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+        }
+    }
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "html" => Ok(ReportFormat::Html),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            _ => Err(format!("Unknown report format '{}' (expected html or markdown)", s)),
+        }
+    }
+}
+
+/// What the report charts
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// Pools with a histogram and a success curve each
+    pub pools: Vec<Pool>,
+    /// TNs with an XkY heatmap each
+    pub tns: Vec<i32>,
+    /// Explosion rule of the histograms and heatmaps (curves show every rule)
+    pub explosion: ExplosionRule,
+    /// Pipeline the charted tables were generated with (empty for plain rolls)
+    pub pipeline: Vec<Stage>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            pools: DEFAULT_POOLS.to_vec(),
+            tns: DEFAULT_HEATMAP_TNS.to_vec(),
+            explosion: ExplosionMode::Skilled.rule(),
+            pipeline: Vec::new(),
+        }
+    }
+}
+
+/// A titled part of the report: a short text and its charts
+struct Section {
+    title: String,
+    text: String,
+    charts: Vec<String>,
+}
+
+/// Escape text for SVG and HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Lowest total and last TN worth charting (P ≥ TN above the tail cutoff)
+fn chart_range(table: &ProbabilityTableV2) -> (i32, i32) {
+    let last = table.at_least.iter()
+        .rposition(|&p| p >= TAIL_CUTOFF)
+        .map_or(table.statistics.max, |i| table.min_tn + i as i32);
+    (table.statistics.min, last.max(table.statistics.min))
+}
+
+/// Spacing of the x-axis ticks for a TN range
+fn tick_step(lo: i32, hi: i32) -> i32 {
+    match hi - lo {
+        0..=20 => 2,
+        21..=60 => 5,
+        61..=120 => 10,
+        _ => 20,
+    }
+}
+
+/// Linear map from a TN range onto the plot width
+struct XScale {
+    lo: i32,
+    hi: i32,
+}
+
+impl XScale {
+    fn plot_width() -> f64 {
+        WIDTH - MARGIN_LEFT - MARGIN_RIGHT
+    }
+
+    /// Width of one TN
+    fn step(&self) -> f64 {
+        Self::plot_width() / (self.hi - self.lo + 1) as f64
+    }
+
+    /// Left edge of a TN's slot
+    fn left(&self, tn: i32) -> f64 {
+        MARGIN_LEFT + (tn - self.lo) as f64 * self.step()
+    }
+
+    /// Centre of a TN's slot
+    fn center(&self, tn: i32) -> f64 {
+        self.left(tn) + self.step() / 2.0
+    }
+}
+
+/// y coordinate of a fraction of `max`
+fn y_of(value: f64, max: f64) -> f64 {
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    HEIGHT - MARGIN_BOTTOM - plot_height * (value / max).clamp(0.0, 1.0)
+}
+
+/// Opening tag, axes and tick labels shared by the histograms and curves
+fn axes(svg: &mut String, label: &str, x: &XScale, y_max: f64, y_ticks: &[f64]) {
+    let bottom = HEIGHT - MARGIN_BOTTOM;
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{w}" height="{h}" role="img" aria-label="{label}" font-family="sans-serif" font-size="11">"#,
+        w = WIDTH,
+        h = HEIGHT,
+        label = escape(label),
+    );
+    for &tick in y_ticks {
+        let y = y_of(tick, y_max);
+        let _ = write!(
+            svg,
+            r##"<line x1="{x1}" y1="{y:.1}" x2="{x2}" y2="{y:.1}" stroke="#ddd"/><text x="{tx}" y="{ty:.1}" text-anchor="end">{pct}%</text>"##,
+            x1 = MARGIN_LEFT,
+            x2 = WIDTH - MARGIN_RIGHT,
+            tx = MARGIN_LEFT - 4.0,
+            ty = y + 4.0,
+            pct = format_percent(tick),
+        );
+    }
+
+    let step = tick_step(x.lo, x.hi);
+    let first = x.lo + (step - x.lo.rem_euclid(step)) % step;
+    for tn in (first..=x.hi).step_by(step as usize) {
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+            x.center(tn),
+            bottom + 14.0,
+            tn
+        );
+    }
+    let _ = write!(
+        svg,
+        r##"<line x1="{l}" y1="{b}" x2="{r}" y2="{b}" stroke="#333"/><text x="{cx}" y="{ly}" text-anchor="middle">TN / total</text>"##,
+        l = MARGIN_LEFT,
+        r = WIDTH - MARGIN_RIGHT,
+        b = bottom,
+        cx = MARGIN_LEFT + XScale::plot_width() / 2.0,
+        ly = HEIGHT - 4.0,
+    );
+}
+
+/// Percentage with as many decimals as small values need
+fn format_percent(p: f64) -> String {
+    let pct = p * 100.0;
+    if pct == 0.0 || pct >= 10.0 {
+        format!("{:.0}", pct)
+    } else {
+        format!("{:.1}", pct)
+    }
+}
+
+/// Bar chart of P(total = x), with the interquartile range shaded and the median marked
+pub fn histogram_svg(label: &str, table: &ProbabilityTableV2) -> String {
+    let (lo, hi) = chart_range(table);
+    let x = XScale { lo, hi };
+    let max = (lo..=hi).map(|tn| table.probability_exactly(tn)).fold(0.0, f64::max).max(f64::EPSILON);
+    let statistics = &table.statistics;
+
+    let mut svg = String::new();
+    axes(&mut svg, &format!("{} distribution", label), &x, max, &[0.0, max / 2.0, max]);
+
+    // Interquartile range behind the bars
+    let (q1, q3) = (statistics.percentile_25.max(lo), statistics.percentile_75.min(hi));
+    let _ = write!(
+        svg,
+        r##"<rect x="{:.1}" y="{}" width="{:.1}" height="{}" fill="#fde9c9" fill-opacity="0.7"><title>IQR {}–{}</title></rect>"##,
+        x.left(q1),
+        MARGIN_TOP,
+        x.left(q3) + x.step() - x.left(q1),
+        HEIGHT - MARGIN_TOP - MARGIN_BOTTOM,
+        statistics.percentile_25,
+        statistics.percentile_75,
+    );
+
+    for tn in lo..=hi {
+        let p = table.probability_exactly(tn);
+        if p <= 0.0 {
+            continue;
+        }
+        let y = y_of(p, max);
+        let _ = write!(
+            svg,
+            r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#1f77b4"><title>{}: {}%</title></rect>"##,
+            x.left(tn) + 0.5,
+            y,
+            (x.step() - 1.0).max(0.5),
+            HEIGHT - MARGIN_BOTTOM - y,
+            tn,
+            format_percent(p),
+        );
+    }
+
+    if (lo..=hi).contains(&statistics.median) {
+        let cx = x.center(statistics.median);
+        let _ = write!(
+            svg,
+            r##"<line x1="{cx:.1}" y1="{top}" x2="{cx:.1}" y2="{bottom}" stroke="#d62728" stroke-dasharray="4 3"/><text x="{tx:.1}" y="{ty}" fill="#d62728">median {median}</text>"##,
+            top = MARGIN_TOP,
+            bottom = HEIGHT - MARGIN_BOTTOM,
+            tx = cx + 4.0,
+            ty = MARGIN_TOP + 10.0,
+            median = statistics.median,
+        );
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// P(total ≥ TN) curves of several tables on one chart, with a legend
+pub fn success_curve_svg(label: &str, series: &[(String, &ProbabilityTableV2)]) -> String {
+    let lo = series.iter().map(|(_, t)| chart_range(t).0).min().unwrap_or(0).clamp(0, 1);
+    let hi = series.iter().map(|(_, t)| chart_range(t).1).max().unwrap_or(10);
+    let x = XScale { lo, hi };
+
+    let mut svg = String::new();
+    axes(&mut svg, &format!("{} success by TN", label), &x, 1.0, &[0.0, 0.25, 0.5, 0.75, 1.0]);
+
+    for (i, (name, table)) in series.iter().enumerate() {
+        let colour = PALETTE[i % PALETTE.len()];
+        let points: Vec<String> = (lo..=hi)
+            .map(|tn| format!("{:.1},{:.1}", x.center(tn), y_of(table.probability_at_least(tn), 1.0)))
+            .collect();
+        let dash = if table.emphasis { r#" stroke-dasharray="6 3""# } else { "" };
+        let _ = write!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"{}><title>{}</title></polyline>"#,
+            points.join(" "),
+            colour,
+            dash,
+            escape(name),
+        );
+
+        // Legend, top right
+        let ly = MARGIN_TOP + 8.0 + i as f64 * 14.0;
+        let lx = WIDTH - MARGIN_RIGHT - 170.0;
+        let _ = write!(
+            svg,
+            r#"<line x1="{lx}" y1="{ly}" x2="{lx2}" y2="{ly}" stroke="{colour}" stroke-width="2"{dash}/><text x="{tx}" y="{ty}">{name}</text>"#,
+            lx2 = lx + 20.0,
+            tx = lx + 26.0,
+            ty = ly + 4.0,
+            name = escape(name),
+        );
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Blend from white (0) to dark blue (1)
+fn heat_colour(p: f64) -> String {
+    let (from, to) = ((247.0, 251.0, 255.0), (8.0, 48.0, 107.0));
+    let p = p.clamp(0.0, 1.0);
+    let mix = |a: f64, b: f64| (a + (b - a) * p).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+/// Grid of P(total ≥ TN) for every XkY pool (rows: rolled, columns: kept)
+pub fn heatmap_svg(
+    tables: &ProbabilityTablesV2,
+    explosion: ExplosionRule,
+    emphasis: bool,
+    pipeline: &[Stage],
+    tn: i32,
+) -> String {
+    let (left, top) = (40.0, 36.0);
+    let size = left + CELL * 10.0 + 8.0;
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {s} {h}" width="{s}" height="{h}" role="img" aria-label="XkY at TN {tn}" font-family="sans-serif" font-size="11">"#,
+        s = size,
+        h = top + CELL * 10.0 + 8.0,
+    );
+    let _ = write!(svg, r#"<text x="{}" y="12" text-anchor="middle">kept</text>"#, left + CELL * 5.0);
+    let _ = write!(svg, r#"<text x="12" y="{}" text-anchor="middle" transform="rotate(-90 12 {})">rolled</text>"#, top + CELL * 5.0, top + CELL * 5.0);
+
+    for i in 1..=10u8 {
+        let offset = (i - 1) as f64 * CELL + CELL / 2.0;
+        let _ = write!(svg, r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#, left + offset, top - 6.0, i);
+        let _ = write!(svg, r#"<text x="{}" y="{:.1}" text-anchor="end">{}</text>"#, left - 6.0, top + offset + 4.0, i);
+    }
+
+    for roll in 1..=10u8 {
+        for keep in 1..=roll {
+            let Some(table) = tables.find(roll, keep, explosion, emphasis, pipeline) else { continue };
+            let p = table.probability_at_least(tn);
+            let (x, y) = (left + (keep - 1) as f64 * CELL, top + (roll - 1) as f64 * CELL);
+            let text_colour = if p > 0.5 { "#fff" } else { "#000" };
+            let _ = write!(
+                svg,
+                r##"<rect x="{x}" y="{y}" width="{c}" height="{c}" fill="{fill}" stroke="#fff"><title>{roll}k{keep}: {pct}%</title></rect><text x="{tx}" y="{ty}" text-anchor="middle" fill="{text_colour}">{pct}</text>"##,
+                c = CELL,
+                fill = heat_colour(p),
+                pct = format_percent(p),
+                tx = x + CELL / 2.0,
+                ty = y + CELL / 2.0 + 4.0,
+            );
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// "skilled", "mastery + emphasis", ...
+fn variant_label(table: &ProbabilityTableV2) -> String {
+    if table.emphasis {
+        format!("{} + emphasis", table.explosion)
+    } else {
+        table.explosion.to_string()
+    }
+}
+
+/// Build the report sections
+fn sections(tables: &ProbabilityTablesV2, options: &ReportOptions) -> Vec<Section> {
+    let mut sections = Vec::new();
+
+    for pool in &options.pools {
+        let variants: Vec<&ProbabilityTableV2> = tables.tables.iter()
+            .filter(|t| t.roll == pool.roll && t.keep == pool.keep && t.pipeline == options.pipeline)
+            .collect();
+        let main = tables.find(pool.roll, pool.keep, options.explosion, false, &options.pipeline);
+        let Some(main) = main.or(variants.first().copied()) else {
+            continue;
+        };
+
+        let statistics = &main.statistics;
+        let text = format!(
+            "{} {}: mean {:.1}, standard deviation {:.1}, median {} (middle half {}–{}), range {}–{}.",
+            pool,
+            variant_label(main),
+            statistics.mean,
+            statistics.stddev,
+            statistics.median,
+            statistics.percentile_25,
+            statistics.percentile_75,
+            statistics.min,
+            statistics.max,
+        );
+        let series: Vec<(String, &ProbabilityTableV2)> = variants.iter().map(|t| (variant_label(t), *t)).collect();
+        sections.push(Section {
+            title: pool.to_string(),
+            text,
+            charts: vec![
+                histogram_svg(&format!("{} {}", pool, variant_label(main)), main),
+                success_curve_svg(&pool.to_string(), &series),
+            ],
+        });
+    }
+
+    if tables.tables.iter().any(|t| t.explosion == options.explosion && !t.emphasis && t.pipeline == options.pipeline) {
+        sections.push(Section {
+            title: "Pools by TN".to_string(),
+            text: format!(
+                "Chance in percent of making each TN with every XkY pool ({}, no emphasis).",
+                options.explosion
+            ),
+            charts: options.tns.iter()
+                .map(|&tn| heatmap_svg(tables, options.explosion, false, &options.pipeline, tn))
+                .collect(),
+        });
+    }
+
+    sections
+}
+
+/// One-line description of where the tables came from
+fn source_line(tables: &ProbabilityTablesV2) -> String {
+    format!(
+        "{} tables, {} engine, {} rules, generated {} by {} {}",
+        tables.tables.len(),
+        tables.engine.name(),
+        tables.rule_set.name(),
+        tables.generated_at,
+        tables.generator,
+        tables.generator_version,
+    )
+}
+
+/// Render the report as a self-contained HTML page (inline SVG and CSS, no scripts)
+pub fn render_html(tables: &ProbabilityTablesV2, options: &ReportOptions) -> String {
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>L5R Roll &amp; Keep probabilities</title>\n",
+        "<style>body{font-family:sans-serif;max-width:700px;margin:2em auto;color:#222}",
+        "svg{display:block;margin:1em 0;max-width:100%;height:auto}</style>\n",
+        "</head>\n<body>\n<h1>L5R Roll &amp; Keep probabilities</h1>\n",
+    ));
+    let _ = writeln!(html, "<p>{}</p>", escape(&source_line(tables)));
+
+    for section in sections(tables, options) {
+        let _ = writeln!(html, "<h2>{}</h2>\n<p>{}</p>", escape(&section.title), escape(&section.text));
+        for chart in &section.charts {
+            html.push_str(chart);
+            html.push('\n');
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render the report as Markdown with inline SVG
+pub fn render_markdown(tables: &ProbabilityTablesV2, options: &ReportOptions) -> String {
+    let mut md = String::from("# L5R Roll & Keep probabilities\n\n");
+    let _ = writeln!(md, "{}\n", source_line(tables));
+
+    for section in sections(tables, options) {
+        let _ = writeln!(md, "## {}\n\n{}\n", section.title, section.text);
+        for chart in &section.charts {
+            let _ = writeln!(md, "{}\n", chart);
+        }
+    }
+
+    md
+}
+
+/// Render the report in the requested format
+pub fn render_report(tables: &ProbabilityTablesV2, options: &ReportOptions, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Html => render_html(tables, options),
+        ReportFormat::Markdown => render_markdown(tables, options),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::generate_configs;
+    use crate::output::fixture_tables_v2;
+    use crate::schema::DEFAULT_PRECISION;
+    use crate::Engine;
+
+    fn skilled_tables() -> ProbabilityTablesV2 {
+        let rules = [ExplosionMode::Unskilled.rule(), ExplosionMode::Skilled.rule()];
+        let configs: Vec<_> = generate_configs(&rules, true).into_iter().filter(|c| c.roll <= 5).collect();
+        fixture_tables_v2(&configs, Engine::Exact, DEFAULT_PRECISION)
+    }
+
+    #[test]
+    fn test_html_report() {
+        let tables = skilled_tables();
+        let html = render_html(&tables, &ReportOptions::default());
+
+        // 3k2 and 5k3 are in the tables; 7k4 and 10k5 are skipped
+        assert!(html.contains("<h2>3k2</h2>") && html.contains("<h2>5k3</h2>"));
+        assert!(!html.contains("<h2>7k4</h2>"));
+        assert_eq!(html.matches("<svg").count(), 2 * 2 + DEFAULT_HEATMAP_TNS.len());
+
+        // Self-contained: no scripts, stylesheets or remote images
+        assert!(!html.contains("<script") && !html.contains("<link") && !html.contains("<img"));
+        assert_eq!(html.matches("http").count(), html.matches("http://www.w3.org/2000/svg").count());
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let tables = skilled_tables();
+        let options = ReportOptions { pools: vec![Pool { roll: 5, keep: 3 }], tns: vec![20], ..Default::default() };
+        let md = render_markdown(&tables, &options);
+
+        assert!(md.starts_with("# L5R Roll & Keep probabilities"));
+        assert!(md.contains("## 5k3\n\n5k3 skilled: mean"));
+        assert!(md.contains("## Pools by TN"));
+        // Every emphasis and explosion variant of the pool is on the curve chart
+        for label in ["unskilled", "unskilled + emphasis", "skilled", "skilled + emphasis"] {
+            assert!(md.contains(&format!("<title>{}</title>", label)), "{}", label);
+        }
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let tables = skilled_tables();
+        let svg = heatmap_svg(&tables, ExplosionMode::Skilled.rule(), false, &[], 5);
+
+        // One cell per pool up to 5k5, and 1k1 makes TN 5 60% of the time
+        assert_eq!(svg.matches("<rect").count(), 15);
+        assert!(svg.contains("<title>1k1: 60%</title>"));
+        assert_eq!(heat_colour(0.0), "#f7fbff");
+        assert_eq!(heat_colour(1.0), "#08306b");
+    }
+
+    #[test]
+    fn test_histogram_marks_median() {
+        let tables = skilled_tables();
+        let table = tables.find(3, 2, ExplosionMode::Unskilled.rule(), false, &[]).unwrap();
+        let svg = histogram_svg("3k2", table);
+
+        assert!(svg.contains(&format!("median {}", table.statistics.median)));
+        assert!(svg.contains(&format!("IQR {}–{}", table.statistics.percentile_25, table.statistics.percentile_75)));
+        // Unskilled 3k2 totals run from 2 to 20
+        assert_eq!(svg.matches("fill=\"#1f77b4\"").count(), 19);
+    }
+}
@@ -0,0 +1,208 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{generate_all_configs, generate_configs, ExplosionMode, RollConfig};
+
+/// Roll & Keep rule set (edition) used for a generation run
+///
+/// A rule set decides which explosion presets exist, whether emphasis
+/// applies, and how pools above 10 dice are converted. Tables only cover
+/// pools up to 10k10, so the conversion is recorded in the table metadata for
+/// consumers to apply before looking a pool up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum RuleSet {
+    #[default]
+    #[serde(rename = "4e")]
+    FourthEdition, // L5R 4th Edition
+    #[serde(rename = "legacy")]
+    Legacy,        // L5R 1st-3rd Edition
+    #[serde(rename = "7th-sea")]
+    SeventhSea,    // 7th Sea 1st Edition
+}
+
+impl RuleSet {
+    /// Name used on the command line and in the JSON tables
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleSet::FourthEdition => "4e",
+            RuleSet::Legacy => "legacy",
+            RuleSet::SeventhSea => "7th-sea",
+        }
+    }
+
+    /// Explosion presets available under this rule set
+    ///
+    /// Mastery (9s explode) is a 4e addition; earlier editions and 7th Sea
+    /// only distinguish unskilled and skilled rolls.
+    pub fn explosion_modes(&self) -> &'static [ExplosionMode] {
+        match self {
+            RuleSet::FourthEdition => &ExplosionMode::ALL,
+            RuleSet::Legacy | RuleSet::SeventhSea => &[ExplosionMode::Unskilled, ExplosionMode::Skilled],
+        }
+    }
+
+    /// Whether emphasis (reroll non-exploded 1s) exists
+    pub fn has_emphasis(&self) -> bool {
+        match self {
+            RuleSet::FourthEdition | RuleSet::Legacy => true,
+            RuleSet::SeventhSea => false,
+        }
+    }
+
+    /// Conversion applied to pools above 10 dice
+    pub fn ten_dice_rule(&self) -> TenDiceRule {
+        match self {
+            RuleSet::FourthEdition => TenDiceRule::FOURTH_EDITION,
+            RuleSet::Legacy => TenDiceRule::LEGACY,
+            RuleSet::SeventhSea => TenDiceRule::SEVENTH_SEA,
+        }
+    }
+
+    /// Preset configurations to generate under this rule set
+    pub fn configs(&self) -> Vec<RollConfig> {
+        if *self == RuleSet::FourthEdition {
+            return generate_all_configs();
+        }
+        let rules: Vec<_> = self.explosion_modes().iter().map(|mode| mode.rule()).collect();
+        generate_configs(&rules, self.has_emphasis())
+    }
+}
+
+impl std::str::FromStr for RuleSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4e" => Ok(RuleSet::FourthEdition),
+            "legacy" => Ok(RuleSet::Legacy),
+            "7th-sea" => Ok(RuleSet::SeventhSea),
+            _ => Err(format!("Unknown rule set '{}' (expected 4e, legacy or 7th-sea)", s)),
+        }
+    }
+}
+
+/// How pools above 10 rolled or kept dice are converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct TenDiceRule {
+    /// Excess rolled dice needed for one extra kept die
+    pub rolled_per_kept: u8,
+    /// Bonus for excess rolled dice left over after conversion
+    pub leftover_bonus: i32,
+    /// Bonus for each kept die above 10
+    pub bonus_per_kept: i32,
+}
+
+impl Default for TenDiceRule {
+    fn default() -> Self {
+        Self::FOURTH_EDITION
+    }
+}
+
+/// Result of the Ten Dice Rule conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenDiceConversion {
+    pub roll: u8,
+    pub keep: u8,
+    pub bonus: i32,
+}
+
+impl TenDiceRule {
+    /// L5R 4e p. 76: every 2 excess rolled dice become 1 kept die (an odd
+    /// leftover is +2), then every excess kept die is +2.
+    /// Mirrors applyTenDiceRule in packages/core/src/dice/dice.ts.
+    pub const FOURTH_EDITION: TenDiceRule = TenDiceRule { rolled_per_kept: 2, leftover_bonus: 2, bonus_per_kept: 2 };
+
+    /// L5R 1e-3e core rulebooks, "The Ten Dice Rule": each rolled die above
+    /// 10 becomes a kept die, then every kept die above 10 is +2
+    /// (e.g. 12k9 → 10k11 → 10k10+2).
+    pub const LEGACY: TenDiceRule = TenDiceRule { rolled_per_kept: 1, leftover_bonus: 0, bonus_per_kept: 2 };
+
+    /// 7th Sea 1e Player's Guide, rolling more than ten dice: each rolled die
+    /// above 10 becomes a kept die; kept dice above 10 are lost, with no bonus
+    /// (e.g. 12k9 → 10k10).
+    pub const SEVENTH_SEA: TenDiceRule = TenDiceRule { rolled_per_kept: 1, leftover_bonus: 0, bonus_per_kept: 0 };
+
+    /// Convert a pool so that no more than 10 dice are rolled or kept
+    pub fn apply(&self, roll: u32, keep: u32) -> TenDiceConversion {
+        let mut roll = roll;
+        let mut keep = keep;
+        let mut bonus = 0;
+
+        if roll > 10 {
+            let excess_rolled = roll - 10;
+            let per_kept = self.rolled_per_kept as u32;
+            keep += excess_rolled / per_kept;
+            bonus += (excess_rolled % per_kept) as i32 * self.leftover_bonus;
+            roll = 10;
+        }
+
+        if keep > 10 {
+            bonus += (keep - 10) as i32 * self.bonus_per_kept;
+            keep = 10;
+        }
+
+        TenDiceConversion { roll: roll as u8, keep: keep as u8, bonus }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ten_dice_rule() {
+        let rule = TenDiceRule::FOURTH_EDITION;
+        // Within limits: unchanged
+        assert_eq!(rule.apply(6, 3), TenDiceConversion { roll: 6, keep: 3, bonus: 0 });
+        // 12k4 → 10k5
+        assert_eq!(rule.apply(12, 4), TenDiceConversion { roll: 10, keep: 5, bonus: 0 });
+        // 13k4 → 10k5+2
+        assert_eq!(rule.apply(13, 4), TenDiceConversion { roll: 10, keep: 5, bonus: 2 });
+        // 14k12 → 10k10+8 (2 kept from rolled, 4 excess kept)
+        assert_eq!(rule.apply(14, 12), TenDiceConversion { roll: 10, keep: 10, bonus: 8 });
+    }
+
+    #[test]
+    fn test_legacy_ten_dice_rules() {
+        // 13k4 → 10k7 under the one-for-one conversion
+        assert_eq!(TenDiceRule::LEGACY.apply(13, 4), TenDiceConversion { roll: 10, keep: 7, bonus: 0 });
+        assert_eq!(TenDiceRule::LEGACY.apply(14, 12), TenDiceConversion { roll: 10, keep: 10, bonus: 12 });
+        // 7th Sea drops kept dice above 10
+        assert_eq!(TenDiceRule::SEVENTH_SEA.apply(14, 12), TenDiceConversion { roll: 10, keep: 10, bonus: 0 });
+    }
+
+    /// The rulebook conversions cited on the constants, one set per edition
+    const TEN_DICE_REFERENCES: &[(RuleSet, u32, u32, TenDiceConversion)] = &[
+        // 4e p. 76: two excess rolled dice per kept die, an odd one is +2
+        (RuleSet::FourthEdition, 12, 9, TenDiceConversion { roll: 10, keep: 10, bonus: 0 }),
+        (RuleSet::FourthEdition, 13, 9, TenDiceConversion { roll: 10, keep: 10, bonus: 2 }),
+        // 1e-3e: one kept die per excess rolled die, excess kept dice +2
+        (RuleSet::Legacy, 12, 9, TenDiceConversion { roll: 10, keep: 10, bonus: 2 }),
+        (RuleSet::Legacy, 10, 12, TenDiceConversion { roll: 10, keep: 10, bonus: 4 }),
+        // 7th Sea 1e: one kept die per excess rolled die, excess kept dice lost
+        (RuleSet::SeventhSea, 12, 9, TenDiceConversion { roll: 10, keep: 10, bonus: 0 }),
+        (RuleSet::SeventhSea, 12, 4, TenDiceConversion { roll: 10, keep: 6, bonus: 0 }),
+    ];
+
+    #[test]
+    fn test_ten_dice_references() {
+        for &(rules, roll, keep, expected) in TEN_DICE_REFERENCES {
+            assert_eq!(rules.ten_dice_rule().apply(roll, keep), expected, "{} {}k{}", rules.name(), roll, keep);
+        }
+    }
+
+    #[test]
+    fn test_rule_set_configs() {
+        // 55 pools × 3 modes × 2 emphasis states
+        assert_eq!(RuleSet::FourthEdition.configs().len(), 330);
+        // No mastery
+        assert_eq!(RuleSet::Legacy.configs().len(), 220);
+        // No mastery, no emphasis
+        let seventh_sea = RuleSet::SeventhSea.configs();
+        assert_eq!(seventh_sea.len(), 110);
+        assert!(seventh_sea.iter().all(|c| !c.emphasis));
+
+        assert_eq!("7th-sea".parse::<RuleSet>(), Ok(RuleSet::SeventhSea));
+        assert_eq!(serde_json::to_string(&RuleSet::FourthEdition).unwrap(), "\"4e\"");
+    }
+}
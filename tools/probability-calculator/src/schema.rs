@@ -0,0 +1,284 @@
+use std::path::Path;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{Engine, ExplosionMode, ExplosionRule};
+use crate::error::{Error, Result};
+use crate::fit::GoodnessOfFit;
+use crate::output::{ProbabilityTables, SimulationRounds};
+use crate::pipeline::Stage;
+use crate::rules::{RuleSet, TenDiceRule};
+use crate::stats::Statistics;
+
+/// Current schema version of the JSON tables
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Name written as the generator of v2 tables
+pub const GENERATOR: &str = "probability-calculator";
+
+/// Version of this crate, written as the generator version
+pub const GENERATOR_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Default number of decimals kept for probabilities and statistics
+pub const DEFAULT_PRECISION: u32 = 6;
+
+/// Most decimals an f64 probability can meaningfully keep
+pub const MAX_PRECISION: u32 = 15;
+
+/// Schema v2 of the probability tables
+///
+/// Unlike v1, each table's P(total ≥ TN) is a dense array starting at
+/// `min_tn`, values are rounded to `precision` decimals, and the file
+/// records the schema and generator versions.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProbabilityTablesV2 {
+    pub schema_version: u32,
+    pub generator: String,
+    pub generator_version: String,
+    pub generated_at: String,
+    pub engine: Engine,
+    pub rule_set: RuleSet,
+    pub ten_dice_rule: TenDiceRule,
+    pub simulation_rounds: SimulationRounds,
+    pub probability_cutoff: f64,
+    /// Decimals kept for probabilities, means and standard deviations
+    pub precision: u32,
+    pub tables: Vec<ProbabilityTableV2>,
+}
+
+/// A single v2 table
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProbabilityTableV2 {
+    pub roll: u8,
+    pub keep: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explosion_mode: Option<ExplosionMode>,
+    pub explosion: ExplosionRule,
+    pub emphasis: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipeline: Vec<Stage>,
+    pub statistics: Statistics,
+    /// TN of the first entry of `at_least`
+    pub min_tn: i32,
+    /// P(total ≥ min_tn + i) for each i
+    pub at_least: Vec<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goodness_of_fit: Option<GoodnessOfFit>,
+}
+
+impl ProbabilityTableV2 {
+    /// P(total ≥ TN); TNs below the array are certain, above it 0
+    pub fn probability_at_least(&self, tn: i32) -> f64 {
+        let offset = (tn as i64 - self.min_tn as i64).max(0) as usize;
+        self.at_least.get(offset).copied().unwrap_or(0.0)
+    }
+
+    /// P(total = TN)
+    pub fn probability_exactly(&self, tn: i32) -> f64 {
+        (self.probability_at_least(tn) - self.probability_at_least(tn + 1)).max(0.0)
+    }
+}
+
+/// Round to `precision` decimals
+pub(crate) fn round_to(value: f64, precision: u32) -> f64 {
+    let scale = 10f64.powi(precision as i32);
+    (value * scale).round() / scale
+}
+
+impl ProbabilityTablesV2 {
+    /// Convert v1 tables, rounding to `precision` decimals
+    pub fn from_v1(tables: &ProbabilityTables, precision: u32) -> Result<Self> {
+        let mut converted = Vec::with_capacity(tables.tables.len());
+        for table in &tables.tables {
+            let (min_tn, at_least) = table.dense_cumulative()?;
+            converted.push(ProbabilityTableV2 {
+                roll: table.roll,
+                keep: table.keep,
+                explosion_mode: table.explosion_mode,
+                explosion: table.explosion,
+                emphasis: table.emphasis,
+                pipeline: table.pipeline.clone(),
+                statistics: table.statistics.clone(),
+                min_tn,
+                at_least,
+                goodness_of_fit: table.goodness_of_fit.clone(),
+            });
+        }
+
+        let mut v2 = Self {
+            schema_version: SCHEMA_VERSION,
+            generator: GENERATOR.to_string(),
+            generator_version: GENERATOR_VERSION.to_string(),
+            generated_at: tables.generated_at.clone(),
+            engine: tables.engine,
+            rule_set: tables.rule_set,
+            ten_dice_rule: tables.ten_dice_rule,
+            simulation_rounds: tables.simulation_rounds.clone(),
+            probability_cutoff: tables.probability_cutoff,
+            precision: MAX_PRECISION,
+            tables: converted,
+        };
+        v2.set_precision(precision);
+        Ok(v2)
+    }
+
+    /// Table for a pool, explosion rule, emphasis and pipeline (empty for plain rolls)
+    pub fn find(
+        &self,
+        roll: u8,
+        keep: u8,
+        explosion: ExplosionRule,
+        emphasis: bool,
+        pipeline: &[Stage],
+    ) -> Option<&ProbabilityTableV2> {
+        self.tables.iter().find(|t| {
+            t.roll == roll && t.keep == keep && t.explosion == explosion && t.emphasis == emphasis && t.pipeline == pipeline
+        })
+    }
+
+    /// Round every probability, mean and stddev to `precision` decimals
+    ///
+    /// Precision can only be lowered: asking for more than the tables hold
+    /// keeps their current precision.
+    pub fn set_precision(&mut self, precision: u32) {
+        self.precision = self.precision.min(precision);
+        for table in &mut self.tables {
+            for p in &mut table.at_least {
+                *p = round_to(*p, self.precision);
+            }
+            table.statistics.mean = round_to(table.statistics.mean, self.precision);
+            table.statistics.stddev = round_to(table.statistics.stddev, self.precision);
+        }
+    }
+}
+
+/// Parse tables of either schema version, converting v1 to v2
+///
+/// v1 files have no `schema_version`; their values are kept at full precision.
+pub fn parse_tables(json: &str) -> Result<ProbabilityTablesV2> {
+    let value: Value = serde_json::from_str(json)?;
+    match value.get("schema_version").map(Value::as_u64) {
+        None => {
+            let v1: ProbabilityTables = serde_json::from_value(value)?;
+            ProbabilityTablesV2::from_v1(&v1, MAX_PRECISION)
+        }
+        Some(Some(version)) if version == SCHEMA_VERSION as u64 => Ok(serde_json::from_value(value)?),
+        Some(version) => Err(Error::InvalidData(format!(
+            "unsupported schema version {} (expected 1 or {})",
+            version.map_or_else(|| value["schema_version"].to_string(), |v| v.to_string()),
+            SCHEMA_VERSION
+        ))),
+    }
+}
+
+/// Read a tables file of either schema version
+pub fn read_tables<P: AsRef<Path>>(path: P) -> Result<ProbabilityTablesV2> {
+    parse_tables(&std::fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RollConfig;
+    use crate::output::fixture_tables;
+    use crate::Engine;
+
+    fn v1_tables() -> ProbabilityTables {
+        let configs = [
+            RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap(),
+            RollConfig::new(4, 4, ExplosionMode::Unskilled, true).unwrap(),
+        ];
+        fixture_tables(&configs, Engine::Exact)
+    }
+
+    #[test]
+    fn test_dense_arrays() {
+        let mut v1 = v1_tables();
+        // A skipped TN takes the value of the next stored one
+        v1.tables[0].cumulative_probability.remove("12");
+        let v2 = ProbabilityTablesV2::from_v1(&v1, MAX_PRECISION).unwrap();
+
+        let table = &v2.tables[0];
+        assert_eq!(table.min_tn, 0);
+        assert_eq!(table.at_least[..3], [1.0, 1.0, 1.0]);
+        for tn in -2..=table.at_least.len() as i32 + 2 {
+            let expected = v1.tables[0].probability_at_least(tn);
+            assert!((table.probability_at_least(tn) - expected).abs() < 1e-14, "TN {}", tn);
+        }
+        assert_eq!(table.at_least[12], table.at_least[13]);
+
+        // Arrays are in numeric order, not "10" < "2"
+        assert!(table.at_least.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn test_precision() {
+        let v1 = v1_tables();
+        let mut v2 = ProbabilityTablesV2::from_v1(&v1, 3).unwrap();
+        assert_eq!(v2.precision, 3);
+        let table = &v2.tables[1];
+        assert!(table.at_least.iter().all(|&p| (p * 1000.0 - (p * 1000.0).round()).abs() < 1e-9));
+        assert!((table.statistics.mean - v1.tables[1].statistics.mean).abs() <= 0.0005);
+
+        // Rounded values print without float noise
+        let json = serde_json::to_string(&v2).unwrap();
+        assert!(!json.contains("00000000"), "{}", json);
+
+        // Precision cannot be raised back
+        v2.set_precision(6);
+        assert_eq!(v2.precision, 3);
+    }
+
+    /// The first two tables of the committed v1 file, which predates
+    /// `explosion`, `engine` and `rule_set`
+    const BASELINE_V1: &str = r#"{
+        "version": "1.0.0",
+        "generated_at": "2025-12-26T11:47:36.206470+00:00",
+        "simulation_rounds": {"unskilled": 200000, "skilled": 300000, "mastery": 500000},
+        "probability_cutoff": 1e-06,
+        "tables": [
+            {"roll": 1, "keep": 1, "explosion_mode": "unskilled", "emphasis": false, "statistics": {"mean": 5.49511, "stddev": 2.8734502062677194, "median": 5, "percentile_25": 3, "percentile_75": 8, "min": 1, "max": 10}, "cumulative_probability": {"0": 1.0, "1": 1.0, "10": 0.09967, "2": 0.89966, "3": 0.798265, "4": 0.699805, "5": 0.5994550000000001, "6": 0.49978500000000003, "7": 0.399405, "8": 0.29941, "9": 0.199655}},
+            {"roll": 1, "keep": 1, "explosion_mode": "unskilled", "emphasis": true, "statistics": {"mean": 5.95825, "stddev": 2.613638639425887, "median": 6, "percentile_25": 4, "percentile_75": 8, "min": 1, "max": 10}, "cumulative_probability": {"0": 1.0, "1": 1.0, "10": 0.11072, "2": 0.9900850000000001, "3": 0.8811350000000001, "4": 0.7714150000000001, "5": 0.6625000000000001, "6": 0.55127, "7": 0.44049, "8": 0.33065, "9": 0.21998499999999999}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_migrates_baseline_v1() {
+        let mut v2 = parse_tables(BASELINE_V1).unwrap();
+        v2.set_precision(DEFAULT_PRECISION);
+        assert_eq!(v2.engine, Engine::Simulation);
+        assert_eq!(v2.rule_set, RuleSet::default());
+        assert_eq!(v2.tables.len(), 2);
+
+        let table = &v2.tables[1];
+        assert_eq!(table.explosion_mode, Some(ExplosionMode::Unskilled));
+        assert_eq!(table.explosion, ExplosionRule::NONE);
+        assert!(table.emphasis);
+        assert_eq!(table.statistics.mean, 5.95825);
+        assert_eq!(table.min_tn, 0);
+        assert_eq!(table.at_least, [1.0, 1.0, 0.990085, 0.881135, 0.771415, 0.6625, 0.55127, 0.44049, 0.33065, 0.219985, 0.11072]);
+
+        // The migrated file reads back as v2
+        let reread = parse_tables(&serde_json::to_string(&v2).unwrap()).unwrap();
+        assert_eq!(reread.tables[0].at_least, v2.tables[0].at_least);
+    }
+
+    #[test]
+    fn test_reads_both_versions() {
+        let v1 = v1_tables();
+        let from_v1 = parse_tables(&serde_json::to_string_pretty(&v1).unwrap()).unwrap();
+        assert_eq!(from_v1.schema_version, SCHEMA_VERSION);
+        assert_eq!(from_v1.generator_version, GENERATOR_VERSION);
+        assert_eq!(from_v1.tables.len(), 2);
+
+        let v2 = ProbabilityTablesV2::from_v1(&v1, DEFAULT_PRECISION).unwrap();
+        let from_v2 = parse_tables(&serde_json::to_string_pretty(&v2).unwrap()).unwrap();
+        assert_eq!(from_v2.precision, DEFAULT_PRECISION);
+        assert_eq!(from_v2.tables[0].at_least, v2.tables[0].at_least);
+
+        let mut future = serde_json::to_value(&v2).unwrap();
+        future["schema_version"] = 3.into();
+        assert!(parse_tables(&future.to_string()).is_err());
+    }
+}
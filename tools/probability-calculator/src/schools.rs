@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+
+use crate::config::{ExplosionMode, ExplosionRule, RollConfig};
+use crate::error::{Error, Result};
+use crate::pipeline::{RollPipeline, Stage};
+use crate::rules::RuleSet;
+use crate::sheet::{ModifiedRoll, RollSheet};
+
+/// Root structure of data/schools.json
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchoolsFile {
+    pub version: String,
+    pub schools: Vec<School>,
+}
+
+/// A school and its rank techniques
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct School {
+    pub id: String,
+    pub name: String,
+    pub clan: String,
+    #[serde(rename = "type")]
+    pub school_type: String,
+    pub traits: SchoolTraits,
+    /// Techniques keyed by rank ("rank1" to "rank5")
+    pub techniques: BTreeMap<String, Technique>,
+}
+
+/// Starting benefits of a school
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchoolTraits {
+    pub benefit: String,
+    pub honor: f64,
+    /// Starting skills, e.g. "Iaijutsu 3" or "Artisan: Any 1"
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub outfit: Vec<String>,
+}
+
+/// A rank technique: rules text plus its effect on rolls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Technique {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub modifiers: Vec<TechniqueModifier>,
+}
+
+/// A structured roll modifier granted by a technique
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TechniqueModifier {
+    /// Kind of roll this applies to, e.g. "attack", "damage", "iaijutsu"
+    pub applies_to: String,
+    #[serde(flatten)]
+    pub effect: ModifierEffect,
+}
+
+/// What a technique does to a roll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModifierEffect {
+    /// Flat bonus to the total
+    FlatBonus { bonus: i32 },
+    /// Bonus equal to a skill rank times `multiplier` (e.g. twice Iaijutsu)
+    SkillBonus { skill: String, multiplier: i32 },
+    /// Extra rolled and/or kept dice
+    ExtraDice {
+        #[serde(default)]
+        rolled: u8,
+        #[serde(default)]
+        kept: u8,
+    },
+    /// Replace the explosion rule (e.g. 9s explode)
+    Explosion {
+        threshold: Option<u8>,
+        #[serde(default)]
+        max_chain: Option<u8>,
+    },
+    /// Free raises: raises that do not increase the TN
+    FreeRaises { count: u8 },
+}
+
+/// Split a skill entry such as "Iaijutsu 3" into its name and rank
+pub fn parse_skill_entry(entry: &str) -> (&str, Option<u8>) {
+    match entry.rsplit_once(' ') {
+        Some((name, rank)) => match rank.parse() {
+            Ok(rank) => (name, Some(rank)),
+            Err(_) => (entry, None),
+        },
+        None => (entry, None),
+    }
+}
+
+/// Parse a technique key ("rank1" to "rank5") into its rank
+fn technique_rank(key: &str) -> Option<u8> {
+    key.strip_prefix("rank")
+        .and_then(|r| r.parse().ok())
+        .filter(|r| (1..=5).contains(r))
+}
+
+impl SchoolsFile {
+    /// Load and validate a schools file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let file: SchoolsFile = serde_json::from_str(&json)?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Check ids, technique ranks and modifier parameters
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+
+        for school in &self.schools {
+            if school.id.is_empty() || school.name.is_empty() {
+                return Err(Error::InvalidData("Schools need an id and a name".to_string()));
+            }
+            if !ids.insert(school.id.as_str()) {
+                return Err(Error::InvalidData(format!("Duplicate school id '{}'", school.id)));
+            }
+            school.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl School {
+    fn validate(&self) -> Result<()> {
+        let skills: HashSet<&str> = self.traits.skills.iter()
+            .map(|s| parse_skill_entry(s).0)
+            .collect();
+
+        for (key, technique) in &self.techniques {
+            if technique_rank(key).is_none() {
+                return Err(Error::InvalidData(format!("{}: technique key '{}' must be rank1-rank5", self.id, key)));
+            }
+
+            for modifier in &technique.modifiers {
+                if modifier.applies_to.is_empty() {
+                    return Err(Error::InvalidData(format!("{} {}: modifier without applies_to", self.id, key)));
+                }
+
+                match &modifier.effect {
+                    ModifierEffect::SkillBonus { skill, .. } if !skills.contains(skill.as_str()) => {
+                        return Err(Error::InvalidData(format!("{} {}: '{}' is not a school skill", self.id, key, skill)));
+                    }
+                    ModifierEffect::Explosion { threshold: Some(t), .. } if !(2..=10).contains(t) => {
+                        return Err(Error::InvalidData(format!("{} {}: explosion threshold must be 2-10, got {}", self.id, key, t)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Techniques known at an Insight Rank, lowest rank first
+    pub fn techniques_up_to(&self, rank: u8) -> Vec<&Technique> {
+        let mut techniques: Vec<(u8, &Technique)> = self.techniques.iter()
+            .filter_map(|(key, t)| technique_rank(key).map(|r| (r, t)))
+            .filter(|&(r, _)| r <= rank)
+            .collect();
+        techniques.sort_by_key(|&(r, _)| r);
+        techniques.into_iter().map(|(_, t)| t).collect()
+    }
+}
+
+/// Typical Trait and Skill ranks at an Insight Rank, used for school sheets
+pub fn typical_ranks(rank: u8) -> (u8, u8) {
+    (rank + 1, rank + 1)
+}
+
+/// Apply technique modifiers to a Trait + Skill roll
+///
+/// `skill_rank` resolves skill bonuses; the rule set's Ten Dice Rule is
+/// applied after extra dice are added, and kept dice beyond the rolled ones
+/// (e.g. "+0k1" techniques) are dropped. Emphasis only applies if the rule
+/// set has it.
+pub fn build_modified_roll<'a>(
+    roll: u8,
+    keep: u8,
+    explosion: ExplosionRule,
+    emphasis: bool,
+    rules: RuleSet,
+    modifiers: impl IntoIterator<Item = &'a ModifierEffect>,
+    skill_rank: impl Fn(&str) -> u8,
+) -> Result<ModifiedRoll> {
+    let mut roll = roll as u32;
+    let mut keep = keep as u32;
+    let mut explosion = explosion;
+    let mut bonus = 0;
+    let mut free_raises = 0;
+
+    for effect in modifiers {
+        match effect {
+            ModifierEffect::FlatBonus { bonus: b } => bonus += b,
+            ModifierEffect::SkillBonus { skill, multiplier } => bonus += skill_rank(skill) as i32 * multiplier,
+            ModifierEffect::ExtraDice { rolled, kept } => {
+                roll += *rolled as u32;
+                keep += *kept as u32;
+            }
+            ModifierEffect::Explosion { threshold, max_chain } => {
+                explosion = ExplosionRule::new(*threshold, *max_chain)?;
+            }
+            ModifierEffect::FreeRaises { count } => free_raises += count,
+        }
+    }
+
+    let converted = rules.ten_dice_rule().apply(roll, keep);
+    bonus += converted.bonus;
+    let keep = converted.keep.min(converted.roll);
+
+    let pipeline = match bonus {
+        0 => RollPipeline::default(),
+        _ => RollPipeline { stages: vec![Stage::Modifier { bonus }] },
+    };
+    let pool = match bonus {
+        0 => format!("{}k{}", converted.roll, keep),
+        _ => format!("{}k{}{:+}", converted.roll, keep, bonus),
+    };
+
+    Ok(ModifiedRoll {
+        config: RollConfig::new(converted.roll, keep, explosion, emphasis && rules.has_emphasis())?.with_pipeline(pipeline),
+        free_raises,
+        pool,
+    })
+}
+
+/// Sheet for one Insight Rank of a school
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankSheet {
+    pub rank: u8,
+    pub trait_rank: u8,
+    pub skill_rank: u8,
+    pub techniques: Vec<String>,
+    pub rolls: Vec<RollSheet>,
+}
+
+/// Sheets for every rank of a school
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchoolSheet {
+    pub id: String,
+    pub name: String,
+    pub ranks: Vec<RankSheet>,
+}
+
+/// Root structure of the school sheets output
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchoolSheets {
+    pub generated_at: String,
+    /// Rule set whose Ten Dice Rule and emphasis the rolls use
+    #[serde(default)]
+    pub rule_set: RuleSet,
+    pub schools: Vec<SchoolSheet>,
+}
+
+/// Build the sheet for one school at one rank
+///
+/// Uses the typical Trait and Skill for the rank and a skilled roll. Every
+/// roll kind a known technique applies to gets its own entry, next to the
+/// unmodified baseline.
+pub fn generate_rank_sheet(school: &School, rank: u8, rules: RuleSet) -> Result<RankSheet> {
+    let (trait_rank, skill_rank) = typical_ranks(rank);
+    let techniques = school.techniques_up_to(rank);
+    let roll = trait_rank + skill_rank;
+    let skilled = ExplosionMode::Skilled.rule();
+
+    let kinds: BTreeSet<&str> = techniques.iter()
+        .flat_map(|t| t.modifiers.iter().map(|m| m.applies_to.as_str()))
+        .collect();
+
+    let baseline = build_modified_roll(roll, trait_rank, skilled, false, rules, [], |_| skill_rank)?;
+    let mut rolls = vec![RollSheet::simulate("baseline", &baseline)];
+
+    // Skill bonuses use the typical skill rank, or the school's starting rank if higher
+    let bonus_skill_rank = |skill: &str| {
+        let starting = school.traits.skills.iter()
+            .map(|entry| parse_skill_entry(entry))
+            .find(|&(name, _)| name == skill)
+            .and_then(|(_, rank)| rank)
+            .unwrap_or(0);
+        starting.max(skill_rank)
+    };
+
+    for kind in kinds {
+        let effects = techniques.iter()
+            .flat_map(|t| t.modifiers.iter())
+            .filter(|m| m.applies_to == kind)
+            .map(|m| &m.effect);
+        let modified = build_modified_roll(roll, trait_rank, skilled, false, rules, effects, bonus_skill_rank)?;
+        rolls.push(RollSheet::simulate(kind, &modified));
+    }
+
+    Ok(RankSheet {
+        rank,
+        trait_rank,
+        skill_rank,
+        techniques: techniques.iter().map(|t| t.name.clone()).collect(),
+        rolls,
+    })
+}
+
+/// Generate per-school, per-rank probability sheets
+pub fn generate_school_sheets(file: &SchoolsFile, rules: RuleSet) -> Result<SchoolSheets> {
+    let mut schools = Vec::with_capacity(file.schools.len());
+    for school in &file.schools {
+        schools.push(SchoolSheet {
+            id: school.id.clone(),
+            name: school.name.clone(),
+            ranks: (1..=5).map(|rank| generate_rank_sheet(school, rank, rules)).collect::<Result<_>>()?,
+        });
+    }
+
+    Ok(SchoolSheets {
+        generated_at: Utc::now().to_rfc3339(),
+        rule_set: rules,
+        schools,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KAKITA: &str = r#"{
+        "version": "1.0.0",
+        "schools": [{
+            "id": "crane-kakita-bushi",
+            "name": "Kakita Bushi",
+            "clan": "Crane",
+            "type": "Bushi",
+            "traits": {
+                "benefit": "+1 Reflexes",
+                "honor": 6.5,
+                "skills": ["Artisan: Any 1", "Iaijutsu 3", "Kenjutsu 2"]
+            },
+            "techniques": {
+                "rank1": {
+                    "name": "The Way of the Crane",
+                    "description": "Add twice your Iaijutsu Skill Rank to attack rolls.",
+                    "modifiers": [
+                        { "applies_to": "attack", "type": "skill_bonus", "skill": "Iaijutsu", "multiplier": 2 }
+                    ]
+                },
+                "rank2": {
+                    "name": "Test Technique",
+                    "description": "House-rule test data.",
+                    "modifiers": [
+                        { "applies_to": "attack", "type": "extra_dice", "rolled": 1, "kept": 1 },
+                        { "applies_to": "damage", "type": "explosion", "threshold": 9 },
+                        { "applies_to": "attack", "type": "free_raises", "count": 1 }
+                    ]
+                }
+            }
+        }]
+    }"#;
+
+    #[test]
+    fn test_load_and_validate() {
+        let file: SchoolsFile = serde_json::from_str(KAKITA).unwrap();
+        assert!(file.validate().is_ok());
+
+        let school = &file.schools[0];
+        assert_eq!(school.techniques_up_to(1).len(), 1);
+        assert_eq!(school.techniques_up_to(5).len(), 2);
+        assert_eq!(parse_skill_entry("Artisan: Any 1"), ("Artisan: Any", Some(1)));
+        assert_eq!(parse_skill_entry("Iaijutsu"), ("Iaijutsu", None));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let mut file: SchoolsFile = serde_json::from_str(KAKITA).unwrap();
+        file.schools.push(file.schools[0].clone());
+        assert!(file.validate().unwrap_err().to_string().contains("Duplicate"));
+
+        // Skill bonus for a skill the school does not teach
+        let bad = KAKITA.replace(r#""skill": "Iaijutsu""#, r#""skill": "Sailing""#);
+        let file: SchoolsFile = serde_json::from_str(&bad).unwrap();
+        assert!(file.validate().is_err());
+
+        // Bad technique key
+        let bad = KAKITA.replace(r#""rank2""#, r#""rank9""#);
+        let file: SchoolsFile = serde_json::from_str(&bad).unwrap();
+        assert!(file.validate().is_err());
+    }
+
+    #[test]
+    fn test_build_modified_roll() {
+        let file: SchoolsFile = serde_json::from_str(KAKITA).unwrap();
+        let school = &file.schools[0];
+        let effects: Vec<&ModifierEffect> = school.techniques_up_to(5).iter()
+            .flat_map(|t| t.modifiers.iter())
+            .filter(|m| m.applies_to == "attack")
+            .map(|m| &m.effect)
+            .collect();
+
+        // 6k3 skilled, Iaijutsu 3: +6, one extra rolled and kept die, one free raise
+        let skilled = ExplosionMode::Skilled.rule();
+        let roll = build_modified_roll(6, 3, skilled, false, RuleSet::default(), effects, |_| 3).unwrap();
+        assert_eq!(roll.pool, "7k4+6");
+        assert_eq!(roll.free_raises, 1);
+        assert_eq!(roll.config.pipeline.total_bonus(4), 6);
+
+        // Ten Dice Rule after extra dice: 11k6 → 10k6+2
+        let extra = ModifierEffect::ExtraDice { rolled: 1, kept: 0 };
+        let roll = build_modified_roll(10, 6, skilled, false, RuleSet::default(), [&extra], |_| 0).unwrap();
+        assert_eq!(roll.pool, "10k6+2");
+        
+        // The run's rule set decides the conversion: 1e-3e keeps the excess die
+        let roll = build_modified_roll(10, 6, skilled, true, RuleSet::Legacy, [&extra], |_| 0).unwrap();
+        assert_eq!(roll.pool, "10k7");
+        assert!(roll.config.emphasis);
+        let roll = build_modified_roll(10, 6, skilled, true, RuleSet::SeventhSea, [&extra], |_| 0).unwrap();
+        assert!(!roll.config.emphasis);
+        
+        // "+0k1" techniques are allowed; the extra kept die is dropped when every die is kept
+        let kept = ModifierEffect::ExtraDice { rolled: 0, kept: 1 };
+        let roll = build_modified_roll(3, 3, skilled, false, RuleSet::default(), [&kept], |_| 0).unwrap();
+        assert_eq!(roll.pool, "3k3");
+        let roll = build_modified_roll(4, 2, skilled, false, RuleSet::default(), [&kept], |_| 0).unwrap();
+        assert_eq!(roll.pool, "4k3");
+    }
+
+    #[test]
+    fn test_rank_sheet() {
+        let file: SchoolsFile = serde_json::from_str(KAKITA).unwrap();
+        let sheet = generate_rank_sheet(&file.schools[0], 2, RuleSet::default()).unwrap();
+
+        // Baseline, attack and damage
+        let kinds: Vec<&str> = sheet.rolls.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(kinds, ["baseline", "attack", "damage"]);
+        // 6k3 plus one rolled and kept die, twice the starting Iaijutsu 3
+        assert_eq!(sheet.rolls[1].pool, "7k4+6");
+        assert_eq!(sheet.rolls[2].explosion, ExplosionRule::new(Some(9), None).unwrap());
+
+        // The technique makes the attack roll better than the baseline at every TN
+        for (base, attack) in sheet.rolls[0].tns.iter().zip(&sheet.rolls[1].tns) {
+            assert!(attack.success >= base.success - 0.01);
+            // A free raise makes the one-raise odds equal the plain success odds
+            assert_eq!(attack.one_raise, attack.success);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+/// Port of the default ARC4 generator of the `seedrandom` npm package (3.0.5)
+///
+/// `SeedRandom::new(seed)` followed by `next_f64()` returns the same sequence
+/// as `seedrandom(seed)()` in packages/core/src/dice/dice.ts, bit for bit, so
+/// rolls made by the bot with a known DICE_SEED can be replayed offline.
+#[derive(Debug, Clone)]
+pub struct SeedRandom {
+    i: u8,
+    j: u8,
+    s: [u8; 256],
+}
+
+/// Bytes in the initial numerator of a double (2^48)
+const CHUNKS: usize = 6;
+/// Doubles are filled up to 52 significant bits
+const SIGNIFICANCE: u64 = 1 << 52;
+/// One past the 53 bits a double holds exactly
+const OVERFLOW: u64 = 1 << 53;
+
+impl SeedRandom {
+    /// Seed the generator from a string, as `seedrandom(seed)`
+    pub fn new(seed: &str) -> Self {
+        let key = mix_key(seed);
+        let mut rng = Self::from_key(&key);
+
+        // RC4-drop[256]: discard the first 256 bytes
+        rng.bytes(256);
+        rng
+    }
+
+    /// Standard RC4 key scheduling; an empty key is treated as [0]
+    fn from_key(key: &[u8]) -> Self {
+        let key = if key.is_empty() { &[0][..] } else { key };
+        let mut s = [0u8; 256];
+        for (i, value) in s.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+
+        let mut j: u8 = 0;
+        for i in 0..256 {
+            j = j.wrapping_add(key[i % key.len()]).wrapping_add(s[i]);
+            s.swap(i, j as usize);
+        }
+
+        Self { i: 0, j: 0, s }
+    }
+
+    /// Next `count` output bytes as one big-endian number (ARC4.g)
+    fn bytes(&mut self, count: usize) -> u64 {
+        let mut r: u64 = 0;
+        for _ in 0..count {
+            self.i = self.i.wrapping_add(1);
+            let t = self.s[self.i as usize];
+            self.j = self.j.wrapping_add(t);
+            self.s.swap(self.i as usize, self.j as usize);
+            let index = self.s[self.i as usize].wrapping_add(self.s[self.j as usize]);
+            // Only the low 64 bits matter: callers ask for at most 6 bytes
+            r = r.wrapping_mul(256).wrapping_add(self.s[index as usize] as u64);
+        }
+        r
+    }
+
+    /// Next double in [0, 1) with 52 bits of randomness, as `prng()`
+    pub fn next_f64(&mut self) -> f64 {
+        let mut n = self.bytes(CHUNKS);
+        // The denominator is always a power of two: 2^exponent
+        let mut exponent: i32 = 48;
+        let mut x: u64 = 0;
+
+        // Shift in bytes until all significant digits are filled
+        while n < SIGNIFICANCE {
+            n = (n + x) * 256;
+            exponent += 8;
+            x = self.bytes(1);
+        }
+        // Drop low bits rather than round up when adding the last byte
+        while n >= OVERFLOW {
+            n /= 2;
+            exponent -= 1;
+            x >>= 1;
+        }
+
+        (n + x) as f64 / 2f64.powi(exponent)
+    }
+
+    /// Next 32-bit signed integer, as `prng.int32()`
+    pub fn next_i32(&mut self) -> i32 {
+        self.bytes(4) as u32 as i32
+    }
+
+    /// Next double with 32 bits of randomness, as `prng.quick()`
+    pub fn next_quick(&mut self) -> f64 {
+        self.bytes(4) as f64 / 4_294_967_296.0
+    }
+}
+
+/// Hash a seed string into an ARC4 key (seedrandom's `mixkey`)
+///
+/// Works on UTF-16 code units, like `charCodeAt`. Key slots not yet written
+/// count as 0, which is what JavaScript's `undefined * 19` amounts to here.
+fn mix_key(seed: &str) -> Vec<u8> {
+    let mut key: Vec<u8> = Vec::new();
+    let mut smear: u32 = 0;
+
+    for (j, unit) in seed.encode_utf16().enumerate() {
+        let slot = j & 255;
+        if slot == key.len() {
+            key.push(0);
+        }
+        smear ^= key[slot] as u32 * 19;
+        key[slot] = (smear.wrapping_add(unit as u32) & 255) as u8;
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_seedrandom() {
+        // From the seedrandom README
+        let mut rng = SeedRandom::new("hello.");
+        assert_eq!(rng.next_f64(), 0.9282578795792454);
+        assert_eq!(rng.next_f64(), 0.3752569768646784);
+        assert_eq!(rng.next_f64(), 0.7316977468919549);
+
+        // Empty and non-ASCII seeds
+        assert_eq!(SeedRandom::new("").next_f64(), 0.23144008215179881);
+        assert_eq!(SeedRandom::new("ñandú 🎲").next_f64(), 0.46458601688095796);
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SeedRandom::new("dice");
+        let mut b = SeedRandom::new("dice");
+        let mut c = SeedRandom::new("dicE");
+
+        let first: Vec<f64> = (0..100).map(|_| a.next_f64()).collect();
+        assert_eq!(first, (0..100).map(|_| b.next_f64()).collect::<Vec<_>>());
+        assert_ne!(first, (0..100).map(|_| c.next_f64()).collect::<Vec<_>>());
+        assert!(first.iter().all(|&x| (0.0..1.0).contains(&x)));
+    }
+
+    #[test]
+    fn test_mix_key() {
+        // A single unit is copied; repeated slots are smeared
+        assert_eq!(mix_key("A"), vec![65]);
+        assert_eq!(mix_key(""), Vec::<u8>::new());
+        assert_eq!(mix_key(&"a".repeat(300)).len(), 256);
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ExplosionRule, RollConfig};
+use crate::dice::simulate_roll_xky;
+use crate::stats::{calculate_cumulative_probabilities, calculate_statistics, probability_at_least};
+
+/// Target numbers reported on probability sheets
+pub const SHEET_TNS: [i32; 7] = [5, 10, 15, 20, 25, 30, 40];
+
+/// A roll built from a base pool and technique modifiers
+#[derive(Debug, Clone)]
+pub struct ModifiedRoll {
+    pub config: RollConfig,
+    pub free_raises: u8,
+    /// Pool label after the Ten Dice Rule, e.g. "10k7+4"
+    pub pool: String,
+}
+
+/// Success odds at one TN
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TnProbability {
+    pub tn: i32,
+    /// P(total ≥ TN)
+    pub success: f64,
+    /// P(success with one raise), after free raises
+    pub one_raise: f64,
+}
+
+/// Odds for one roll on a probability sheet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollSheet {
+    /// What is rolled, e.g. "attack" or "Kenjutsu (Katana)"
+    pub label: String,
+    pub pool: String,
+    pub explosion: ExplosionRule,
+    pub emphasis: bool,
+    pub free_raises: u8,
+    pub mean: f64,
+    pub median: i32,
+    pub tns: Vec<TnProbability>,
+}
+
+impl RollSheet {
+    /// Simulate a modified roll and tabulate it at the sheet TNs
+    pub fn simulate(label: &str, roll: &ModifiedRoll) -> Self {
+        let histogram = simulate_roll_xky(&roll.config);
+        let total_count = roll.config.simulation_rounds();
+        let statistics = calculate_statistics(&histogram, total_count);
+        let cumulative = calculate_cumulative_probabilities(&histogram, total_count);
+
+        // Each free raise offsets the +5 TN of one raise
+        let raise_cost = 5 * (1 - roll.free_raises as i32).max(0);
+        let tns = SHEET_TNS.iter()
+            .map(|&tn| TnProbability {
+                tn,
+                success: probability_at_least(&cumulative, tn),
+                one_raise: probability_at_least(&cumulative, tn + raise_cost),
+            })
+            .collect();
+
+        Self {
+            label: label.to_string(),
+            pool: roll.pool.clone(),
+            explosion: roll.config.explosion,
+            emphasis: roll.config.emphasis,
+            free_raises: roll.free_raises,
+            mean: statistics.mean,
+            median: statistics.median,
+            tns,
+        }
+    }
+}
+
+/// Render sheet rows as a Markdown table of success odds
+pub fn markdown_table(rolls: &[RollSheet]) -> String {
+    let mut md = String::from("| Roll | Pool | Mean |");
+    for tn in SHEET_TNS {
+        md.push_str(&format!(" TN {} |", tn));
+    }
+    md.push_str("\n|------|------|-----:|");
+    md.push_str(&"-----:|".repeat(SHEET_TNS.len()));
+    md.push('\n');
+
+    for roll in rolls {
+        let mode = match roll.explosion.preset() {
+            Some(mode) if mode.rule().threshold == Some(10) => String::new(),
+            _ => format!(" ({})", roll.explosion),
+        };
+        md.push_str(&format!("| {} | {}{} | {:.1} |", roll.label, roll.pool, mode, roll.mean));
+        for tn in &roll.tns {
+            md.push_str(&format!(" {:.0}% |", tn.success * 100.0));
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExplosionMode;
+
+    #[test]
+    fn test_roll_sheet() {
+        let roll = ModifiedRoll {
+            config: RollConfig::new(3, 2, ExplosionMode::Unskilled, false).unwrap(),
+            free_raises: 0,
+            pool: "3k2".to_string(),
+        };
+        let sheet = RollSheet::simulate("test", &roll);
+
+        assert_eq!(sheet.tns.len(), SHEET_TNS.len());
+        // Unskilled 3k2 nearly always makes TN 5 and can never make TN 40
+        assert!(sheet.tns[0].success > 0.95);
+        assert_eq!(sheet.tns.last().unwrap().success, 0.0);
+        // One raise at TN 5 is the same as TN 10
+        assert_eq!(sheet.tns[0].one_raise, sheet.tns[1].success);
+
+        let md = markdown_table(&[sheet]);
+        assert!(md.starts_with("| Roll | Pool | Mean | TN 5 |"));
+        assert!(md.contains("| test | 3k2 (unskilled) |"));
+    }
+}
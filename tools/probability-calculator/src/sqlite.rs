@@ -0,0 +1,283 @@
+use std::path::Path;
+use rusqlite::{params, Connection};
+
+use crate::error::{Error, Result};
+use crate::pipeline::RollPipeline;
+use crate::schema::ProbabilityTablesV2;
+
+/// Version of the database layout, stored as `PRAGMA user_version`
+pub const DATABASE_VERSION: i32 = 1;
+
+/// Tables, indexes and views of the database
+///
+/// Every generation run appends one `runs` row; its tables become `configs`
+/// rows with one `statistics` row and one `probabilities` row per TN.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    generated_at TEXT NOT NULL,
+    generator TEXT NOT NULL,
+    generator_version TEXT NOT NULL,
+    engine TEXT NOT NULL,
+    rule_set TEXT NOT NULL,
+    ten_dice_rule TEXT NOT NULL,
+    simulation_rounds TEXT NOT NULL,
+    probability_cutoff REAL NOT NULL,
+    precision INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS configs (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    roll INTEGER NOT NULL,
+    keep INTEGER NOT NULL,
+    mode TEXT NOT NULL,
+    explode_on INTEGER,
+    max_chain INTEGER,
+    emphasis INTEGER NOT NULL,
+    pipeline TEXT NOT NULL,
+    stages TEXT
+);
+CREATE INDEX IF NOT EXISTS configs_pool ON configs(run_id, roll, keep);
+
+CREATE TABLE IF NOT EXISTS statistics (
+    config_id INTEGER PRIMARY KEY REFERENCES configs(id) ON DELETE CASCADE,
+    mean REAL NOT NULL,
+    stddev REAL NOT NULL,
+    median INTEGER NOT NULL,
+    percentile_25 INTEGER NOT NULL,
+    percentile_75 INTEGER NOT NULL,
+    min INTEGER NOT NULL,
+    max INTEGER NOT NULL,
+    chi_square_p_value REAL,
+    ks_p_value REAL
+);
+
+CREATE TABLE IF NOT EXISTS probabilities (
+    config_id INTEGER NOT NULL REFERENCES configs(id) ON DELETE CASCADE,
+    tn INTEGER NOT NULL,
+    at_least REAL NOT NULL,
+    PRIMARY KEY (config_id, tn)
+) WITHOUT ROWID;
+
+CREATE VIEW IF NOT EXISTS success_by_tn AS
+SELECT c.run_id, c.roll, c.keep, c.mode, c.emphasis, c.pipeline, p.tn, p.at_least
+FROM probabilities p
+JOIN configs c ON c.id = p.config_id;
+
+CREATE VIEW IF NOT EXISTS best_pool_for_tn AS
+SELECT run_id, mode, emphasis, pipeline, tn, roll, keep, at_least
+FROM (
+    SELECT s.*, ROW_NUMBER() OVER (
+        PARTITION BY run_id, mode, emphasis, pipeline, tn
+        ORDER BY roll, keep
+    ) AS rank
+    FROM success_by_tn s
+    WHERE at_least >= 0.5
+)
+WHERE rank = 1;
+";
+
+/// Open (or create) a tables database
+///
+/// Files created by another layout version are rejected rather than migrated.
+pub fn open_database<P: AsRef<Path>>(path: P) -> Result<Connection> {
+    let connection = Connection::open(path)?;
+    let version: i32 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version != 0 && version != DATABASE_VERSION {
+        return Err(Error::InvalidData(format!(
+            "unsupported database version {} (expected {})",
+            version, DATABASE_VERSION
+        )));
+    }
+
+    connection.pragma_update(None, "foreign_keys", true)?;
+    connection.execute_batch(SCHEMA)?;
+    connection.pragma_update(None, "user_version", DATABASE_VERSION)?;
+    Ok(connection)
+}
+
+/// Enum or struct as the text stored in the database
+///
+/// Strings (e.g. the rule set `"4e"`) are stored bare, anything else as JSON.
+fn to_text<T: serde::Serialize>(value: &T) -> Result<String> {
+    Ok(match serde_json::to_value(value)? {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    })
+}
+
+/// Append the tables as a new run, returning its id
+pub fn insert_run(connection: &mut Connection, tables: &ProbabilityTablesV2) -> Result<i64> {
+    let transaction = connection.transaction()?;
+
+    transaction.execute(
+        "INSERT INTO runs (generated_at, generator, generator_version, engine, rule_set, ten_dice_rule,
+                           simulation_rounds, probability_cutoff, precision)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            tables.generated_at,
+            tables.generator,
+            tables.generator_version,
+            to_text(&tables.engine)?,
+            to_text(&tables.rule_set)?,
+            to_text(&tables.ten_dice_rule)?,
+            to_text(&tables.simulation_rounds)?,
+            tables.probability_cutoff,
+            tables.precision,
+        ],
+    )?;
+    let run_id = transaction.last_insert_rowid();
+
+    {
+        let mut insert_config = transaction.prepare(
+            "INSERT INTO configs (run_id, roll, keep, mode, explode_on, max_chain, emphasis, pipeline, stages)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        let mut insert_statistics = transaction.prepare(
+            "INSERT INTO statistics (config_id, mean, stddev, median, percentile_25, percentile_75, min, max,
+                                     chi_square_p_value, ks_p_value)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        let mut insert_probability = transaction.prepare(
+            "INSERT INTO probabilities (config_id, tn, at_least) VALUES (?1, ?2, ?3)",
+        )?;
+
+        for table in &tables.tables {
+            let stages = if table.pipeline.is_empty() { None } else { Some(serde_json::to_string(&table.pipeline)?) };
+            let config_id = insert_config.insert(params![
+                run_id,
+                table.roll,
+                table.keep,
+                table.explosion.to_string(),
+                table.explosion.threshold,
+                table.explosion.max_chain,
+                table.emphasis,
+                RollPipeline { stages: table.pipeline.clone() }.describe(),
+                stages,
+            ])?;
+
+            let statistics = &table.statistics;
+            let fit = table.goodness_of_fit.as_ref();
+            insert_statistics.execute(params![
+                config_id,
+                statistics.mean,
+                statistics.stddev,
+                statistics.median,
+                statistics.percentile_25,
+                statistics.percentile_75,
+                statistics.min,
+                statistics.max,
+                fit.map(|f| f.chi_square_p_value),
+                fit.map(|f| f.ks_p_value),
+            ])?;
+
+            for (i, at_least) in table.at_least.iter().enumerate() {
+                insert_probability.execute(params![config_id, table.min_tn + i as i32, at_least])?;
+            }
+        }
+    }
+
+    transaction.commit()?;
+    Ok(run_id)
+}
+
+/// Append the tables to the database at `path`, creating it if needed
+pub fn write_sqlite_file<P: AsRef<Path>>(path: P, tables: &ProbabilityTablesV2) -> Result<i64> {
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut connection = open_database(path)?;
+    insert_run(&mut connection, tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExplosionMode, RollConfig};
+    use crate::output::fixture_tables_v2;
+    use crate::pipeline::Stage;
+    use crate::schema::DEFAULT_PRECISION;
+    use crate::Engine;
+
+    fn sample_tables() -> ProbabilityTablesV2 {
+        let pipeline = RollPipeline::new(vec![Stage::Modifier { bonus: 1 }]).unwrap();
+        let configs = [
+            RollConfig::new(1, 1, ExplosionMode::Unskilled, false).unwrap(),
+            RollConfig::new(2, 1, ExplosionMode::Unskilled, false).unwrap(),
+            RollConfig::new(2, 2, ExplosionMode::Unskilled, false).unwrap(),
+            RollConfig::new(2, 2, ExplosionMode::Skilled, false).unwrap().with_pipeline(pipeline),
+        ];
+        fixture_tables_v2(&configs, Engine::Exact, DEFAULT_PRECISION)
+    }
+
+    #[test]
+    fn test_runs_append() {
+        let mut connection = open_database(":memory:").unwrap();
+        let tables = sample_tables();
+        assert_eq!(insert_run(&mut connection, &tables).unwrap(), 1);
+        assert_eq!(insert_run(&mut connection, &tables).unwrap(), 2);
+
+        let configs: i64 = connection.query_row("SELECT COUNT(*) FROM configs WHERE run_id = 2", [], |r| r.get(0)).unwrap();
+        assert_eq!(configs, 4);
+        let rule_set: String = connection.query_row("SELECT rule_set FROM runs WHERE id = 1", [], |r| r.get(0)).unwrap();
+        assert_eq!(rule_set, "4e");
+
+        let (pipeline, stages): (String, String) = connection
+            .query_row("SELECT pipeline, stages FROM configs WHERE mode = 'skilled'", [], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap();
+        assert_eq!(pipeline, "+1");
+        assert!(stages.contains("modifier"));
+    }
+
+    #[test]
+    fn test_success_by_tn() {
+        let mut connection = open_database(":memory:").unwrap();
+        let tables = sample_tables();
+        let run_id = insert_run(&mut connection, &tables).unwrap();
+
+        let at_least: f64 = connection
+            .query_row(
+                "SELECT at_least FROM success_by_tn WHERE run_id = ?1 AND roll = 1 AND keep = 1 AND tn = 8",
+                [run_id],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert!((at_least - 0.3).abs() < 1e-9);
+
+        let rows: i64 = connection.query_row("SELECT COUNT(*) FROM success_by_tn", [], |r| r.get(0)).unwrap();
+        assert_eq!(rows as usize, tables.tables.iter().map(|t| t.at_least.len()).sum::<usize>());
+    }
+
+    #[test]
+    fn test_best_pool_for_tn() {
+        let mut connection = open_database(":memory:").unwrap();
+        insert_run(&mut connection, &sample_tables()).unwrap();
+
+        let best = |tn: i32| -> Option<(u8, u8)> {
+            connection
+                .query_row(
+                    "SELECT roll, keep FROM best_pool_for_tn WHERE mode = 'unskilled' AND pipeline = '' AND tn = ?1",
+                    [tn],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )
+                .ok()
+        };
+        // 1k1 makes TN 5 60% of the time; TN 8 needs 2k1 (51%), TN 11 2k2 (55%), TN 19 nothing here
+        assert_eq!(best(5), Some((1, 1)));
+        assert_eq!(best(8), Some((2, 1)));
+        assert_eq!(best(11), Some((2, 2)));
+        assert_eq!(best(19), None);
+    }
+
+    #[test]
+    fn test_rejects_other_versions() {
+        let path = std::env::temp_dir().join(format!("l5r-sqlite-{}.db", std::process::id()));
+        let connection = open_database(&path).unwrap();
+        connection.pragma_update(None, "user_version", DATABASE_VERSION + 1).unwrap();
+        drop(connection);
+
+        assert!(open_database(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
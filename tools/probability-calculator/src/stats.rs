@@ -1,12 +1,21 @@
 use std::collections::{BTreeMap, HashMap};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::error::ValidationError;
+
 /// Probability cutoff threshold (ε = 1e-6)
-/// Only store TN values where P(total ≥ TN) ≥ this threshold
+/// Only store TN values where P(total ≥ TN) > this threshold (entries with P ≤ ε are dropped)
 pub const PROBABILITY_CUTOFF: f64 = 1e-6;
 
+/// Allowed deviation of a simulated distribution's total from 1.0
+pub const HISTOGRAM_TOLERANCE: f64 = 1e-9;
+
+/// Allowed deviation of an exact distribution's total from 1.0 (truncated tails)
+pub const PMF_TOLERANCE: f64 = 1e-6;
+
 /// Statistical measures for a distribution
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Statistics {
     pub mean: f64,
     pub stddev: f64,
@@ -106,21 +115,124 @@ pub fn calculate_cumulative_probabilities(
     // Add entry for 0 (always 1.0 - everything is ≥ 0)
     cumulative_map.insert(0, 1.0);
     
-    // Apply probability cutoff: remove entries with P < ε
-    cumulative_map.retain(|_, &mut p| p >= PROBABILITY_CUTOFF);
+    // Apply probability cutoff: remove entries with P ≤ ε
+    cumulative_map.retain(|_, &mut p| p > PROBABILITY_CUTOFF);
+    
+    cumulative_map
+}
+
+/// Calculate statistics from an exact probability distribution
+///
+/// `max` is the highest total whose P(total ≥ max) is still above the
+/// probability cutoff, so it lines up with the cumulative table.
+pub fn calculate_statistics_from_pmf(pmf: &BTreeMap<i32, f64>) -> Statistics {
+    let total: f64 = pmf.values().sum();
+    let mean = pmf.iter().map(|(&v, &p)| v as f64 * p).sum::<f64>() / total;
+    let variance = pmf.iter()
+        .map(|(&v, &p)| {
+            let diff = v as f64 - mean;
+            diff * diff * p
+        })
+        .sum::<f64>() / total;
+    
+    let percentile = |target: f64| {
+        let mut cumulative = 0.0;
+        for (&value, &p) in pmf {
+            cumulative += p / total;
+            if cumulative >= target - 1e-12 {
+                return value;
+            }
+        }
+        *pmf.keys().next_back().unwrap()
+    };
+    
+    let min = *pmf.keys().next().unwrap();
+    let max = calculate_cumulative_from_pmf(pmf).keys().next_back().copied().unwrap_or(min).max(min);
+    
+    Statistics {
+        mean,
+        stddev: variance.sqrt(),
+        median: percentile(0.50),
+        percentile_25: percentile(0.25),
+        percentile_75: percentile(0.75),
+        min,
+        max,
+    }
+}
+
+/// Convert an exact probability distribution to P(total ≥ TN), with cutoff applied
+pub fn calculate_cumulative_from_pmf(pmf: &BTreeMap<i32, f64>) -> BTreeMap<i32, f64> {
+    let total: f64 = pmf.values().sum();
+    let mut cumulative_map: BTreeMap<i32, f64> = BTreeMap::new();
+    let mut cumulative = 0.0;
+    
+    for (&value, &prob) in pmf.iter().rev() {
+        cumulative += prob / total;
+        cumulative_map.insert(value, cumulative.min(1.0));
+    }
+    
+    cumulative_map.insert(0, 1.0);
+    cumulative_map.retain(|_, &mut p| p > PROBABILITY_CUTOFF);
+    
+    cumulative_map
+}
+
+/// Convert an exact probability distribution to P(total ≤ x), without cutoff
+///
+/// Every total of the distribution is kept, so the lower tail of a roll can be
+/// looked up with [`probability_at_most`] however unlikely it was.
+pub fn calculate_lower_cumulative_from_pmf(pmf: &BTreeMap<i32, f64>) -> BTreeMap<i32, f64> {
+    let total: f64 = pmf.values().sum();
+    let mut cumulative_map: BTreeMap<i32, f64> = BTreeMap::new();
+    let mut cumulative = 0.0;
+    
+    for (&value, &prob) in pmf {
+        cumulative += prob / total;
+        cumulative_map.insert(value, cumulative.min(1.0));
+    }
     
     cumulative_map
 }
 
-/// Validate that a histogram represents a valid probability distribution
-pub fn validate_distribution(histogram: &HashMap<i32, usize>, total_count: usize) -> Result<(), String> {
+/// Look up P(total ≤ x) in a lower cumulative table
+///
+/// Uses the largest stored total at or below `x`; totals below the table give 0.
+pub fn probability_at_most(lower: &BTreeMap<i32, f64>, x: i32) -> f64 {
+    lower.range(..=x).next_back().map_or(0.0, |(_, &p)| p)
+}
+
+/// Check that an exact distribution sums to 1.0 (up to truncated explosion tails)
+pub fn pmf_errors(pmf: &BTreeMap<i32, f64>) -> Vec<ValidationError> {
+    let total_probability: f64 = pmf.values().sum();
+    
+    if (total_probability - 1.0).abs() > PMF_TOLERANCE {
+        return vec![ValidationError::ProbabilitySum { sum: total_probability, tolerance: PMF_TOLERANCE }];
+    }
+    
+    Vec::new()
+}
+
+/// Validate that an exact distribution sums to 1.0, stopping at the first error
+pub fn validate_pmf(pmf: &BTreeMap<i32, f64>) -> Result<(), ValidationError> {
+    first_error(pmf_errors(pmf))
+}
+
+/// Look up P(total ≥ TN) in a cumulative table
+///
+/// Tables only hold totals that occurred, so this uses the smallest stored
+/// total at or above the TN. TNs beyond the table (below the cutoff) give 0.
+pub fn probability_at_least(cumulative: &BTreeMap<i32, f64>, tn: i32) -> f64 {
+    cumulative.range(tn..).next().map_or(0.0, |(_, &p)| p)
+}
+
+/// Check that a histogram represents a valid probability distribution
+pub fn distribution_errors(histogram: &HashMap<i32, usize>, total_count: usize) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    
     // Check that sum of counts equals total
     let sum_counts: usize = histogram.values().sum();
     if sum_counts != total_count {
-        return Err(format!(
-            "Count sum mismatch: {} != {}",
-            sum_counts, total_count
-        ));
+        errors.push(ValidationError::CountMismatch { expected: total_count, actual: sum_counts });
     }
     
     // Check that total probability ≈ 1.0
@@ -128,41 +240,54 @@ pub fn validate_distribution(histogram: &HashMap<i32, usize>, total_count: usize
         .map(|&count| count as f64 / total_count as f64)
         .sum();
     
-    if (total_probability - 1.0).abs() > 1e-9 {
-        return Err(format!(
-            "Probability sum not 1.0: {}",
-            total_probability
-        ));
+    if (total_probability - 1.0).abs() > HISTOGRAM_TOLERANCE {
+        errors.push(ValidationError::ProbabilitySum { sum: total_probability, tolerance: HISTOGRAM_TOLERANCE });
     }
     
-    Ok(())
+    errors
+}
+
+/// Validate that a histogram represents a valid probability distribution,
+/// stopping at the first error
+pub fn validate_distribution(histogram: &HashMap<i32, usize>, total_count: usize) -> Result<(), ValidationError> {
+    first_error(distribution_errors(histogram, total_count))
 }
 
-/// Validate that cumulative probabilities are monotonically decreasing
-pub fn validate_cumulative(cumulative: &BTreeMap<i32, f64>) -> Result<(), String> {
+/// Check that cumulative probabilities are in [0, 1] and monotonically decreasing
+///
+/// Every offending TN is reported.
+pub fn cumulative_errors(cumulative: &BTreeMap<i32, f64>) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
     let mut prev_prob = 1.0;
     const TOLERANCE: f64 = 1e-9;
     
     for (&tn, &prob) in cumulative.iter() {
         if prob > prev_prob + TOLERANCE {
-            return Err(format!(
-                "Non-monotonic cumulative at TN {}: {} > {}",
-                tn, prob, prev_prob
-            ));
+            errors.push(ValidationError::NonMonotonic { tn, probability: prob, previous: prev_prob });
         }
         
         // Allow for floating point precision errors
-        if prob < -TOLERANCE || prob > 1.0 + TOLERANCE {
-            return Err(format!(
-                "Invalid probability at TN {}: {}",
-                tn, prob
-            ));
+        if !(-TOLERANCE..=1.0 + TOLERANCE).contains(&prob) {
+            errors.push(ValidationError::OutOfRange { tn, probability: prob });
         }
         
         prev_prob = prob;
     }
     
-    Ok(())
+    errors
+}
+
+/// Validate that cumulative probabilities are monotonically decreasing,
+/// stopping at the first error
+pub fn validate_cumulative(cumulative: &BTreeMap<i32, f64>) -> Result<(), ValidationError> {
+    first_error(cumulative_errors(cumulative))
+}
+
+fn first_error(errors: Vec<ValidationError>) -> Result<(), ValidationError> {
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
 }
 
 #[cfg(test)]
@@ -222,7 +347,10 @@ mod tests {
         assert!(validate_distribution(&histogram, 400).is_ok());
         
         // Should fail with wrong total
-        assert!(validate_distribution(&histogram, 500).is_err());
+        assert_eq!(
+            validate_distribution(&histogram, 500),
+            Err(ValidationError::CountMismatch { expected: 500, actual: 400 })
+        );
     }
 
     #[test]
@@ -243,6 +371,16 @@ mod tests {
         bad_cumulative.insert(20, 0.75); // Goes up!
         
         assert!(validate_cumulative(&bad_cumulative).is_err());
+        
+        // Every offending TN is collected
+        bad_cumulative.insert(30, 0.25);
+        bad_cumulative.insert(40, 1.5);
+        let errors = cumulative_errors(&bad_cumulative);
+        assert_eq!(errors, vec![
+            ValidationError::NonMonotonic { tn: 20, probability: 0.75, previous: 0.5 },
+            ValidationError::NonMonotonic { tn: 40, probability: 1.5, previous: 0.25 },
+            ValidationError::OutOfRange { tn: 40, probability: 1.5 },
+        ]);
     }
 
     #[test]
@@ -254,10 +392,52 @@ mod tests {
         let cumulative = calculate_cumulative_probabilities(&histogram, 1_000_000);
         
         // P(≥ 100) = 0.000001, should be filtered by cutoff
-        assert!(cumulative.get(&100).is_none());
+        assert!(!cumulative.contains_key(&100));
         
         // P(≥ 10) = 1.0, should be kept
         assert_eq!(cumulative.get(&10), Some(&1.0));
     }
-}
 
+    #[test]
+    fn test_statistics_from_pmf() {
+        let mut pmf = BTreeMap::new();
+        pmf.insert(10, 0.25);
+        pmf.insert(20, 0.5);
+        pmf.insert(30, 0.25);
+        
+        let stats = calculate_statistics_from_pmf(&pmf);
+        assert!((stats.mean - 20.0).abs() < 1e-12);
+        assert_eq!(stats.median, 20);
+        assert_eq!(stats.percentile_25, 10);
+        assert_eq!(stats.percentile_75, 20);
+        assert_eq!((stats.min, stats.max), (10, 30));
+        
+        let cumulative = calculate_cumulative_from_pmf(&pmf);
+        assert_eq!(cumulative.get(&0), Some(&1.0));
+        assert!((cumulative[&20] - 0.75).abs() < 1e-12);
+        assert!(validate_pmf(&pmf).is_ok());
+        
+        // TNs between stored totals use the next stored total
+        assert!((probability_at_least(&cumulative, 15) - 0.75).abs() < 1e-12);
+        assert_eq!(probability_at_least(&cumulative, 5), 1.0);
+        assert_eq!(probability_at_least(&cumulative, 31), 0.0);
+    }
+
+    #[test]
+    fn test_lower_cumulative_from_pmf() {
+        let mut pmf = BTreeMap::new();
+        pmf.insert(10, 0.25);
+        pmf.insert(20, 0.5);
+        pmf.insert(30, 0.25);
+        
+        let lower = calculate_lower_cumulative_from_pmf(&pmf);
+        assert_eq!(lower.len(), 3);
+        assert!((lower[&20] - 0.75).abs() < 1e-12);
+        assert_eq!(lower[&30], 1.0);
+        
+        // Totals between stored ones use the previous stored total
+        assert!((probability_at_most(&lower, 25) - 0.75).abs() < 1e-12);
+        assert_eq!(probability_at_most(&lower, 9), 0.0);
+        assert_eq!(probability_at_most(&lower, 99), 1.0);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::config::{ExplosionMode, Pool};
+use crate::pipeline::Stage;
+use crate::schema::{ProbabilityTableV2, ProbabilityTablesV2};
+
+/// Chart width, in columns, of a single config
+pub const DEFAULT_WIDTH: usize = 60;
+
+/// Rows of each chart
+const CHART_HEIGHT: usize = 8;
+
+/// Charts stop where P(total ≥ TN) drops below this
+const TAIL_CUTOFF: f64 = 0.001;
+
+/// Columns between side-by-side panels
+const GAP: usize = 4;
+
+/// Left gutter holding the y-axis labels
+const GUTTER: usize = 7;
+
+/// Partial block characters, from empty to full (eighths)
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A config to show, written `XkY[:mode][+e]`, e.g. `5k3`, `7k4:mastery+e`
+///
+/// The mode defaults to skilled; `+e` turns emphasis on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowQuery {
+    pub pool: Pool,
+    pub mode: ExplosionMode,
+    pub emphasis: bool,
+}
+
+impl std::fmt::Display for ShowQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}{}", self.pool, self.mode.name(), if self.emphasis { " + emphasis" } else { "" })
+    }
+}
+
+impl std::str::FromStr for ShowQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (rest, emphasis) = match s.strip_suffix("+e") {
+            Some(rest) => (rest, true),
+            None => (s, false),
+        };
+        let (pool, mode) = match rest.split_once(':') {
+            Some((pool, mode)) => (pool, mode.parse()?),
+            None => (rest, ExplosionMode::Skilled),
+        };
+        Ok(ShowQuery { pool: pool.parse()?, mode, emphasis })
+    }
+}
+
+impl ShowQuery {
+    /// The table this query asks for, among those generated with `pipeline`
+    pub fn find<'a>(&self, tables: &'a ProbabilityTablesV2, pipeline: &[Stage]) -> Option<&'a ProbabilityTableV2> {
+        tables.find(self.pool.roll, self.pool.keep, self.mode.rule(), self.emphasis, pipeline)
+    }
+}
+
+/// Totals shown on the x axis, grouped into at most `width` columns
+struct Columns {
+    lo: i32,
+    /// Totals per column
+    bin: i32,
+    count: usize,
+}
+
+impl Columns {
+    fn new(lo: i32, hi: i32, width: usize) -> Self {
+        let span = (hi - lo + 1).max(1);
+        let bin = (span + width as i32 - 1) / width as i32;
+        Self { lo, bin, count: ((span + bin - 1) / bin) as usize }
+    }
+
+    /// Totals in column `i`
+    fn totals(&self, i: usize) -> std::ops::Range<i32> {
+        let start = self.lo + i as i32 * self.bin;
+        start..start + self.bin
+    }
+
+    /// Column holding a total, if it is on the chart
+    fn column_of(&self, total: i32) -> Option<usize> {
+        let i = (total - self.lo).div_euclid(self.bin);
+        (0..self.count as i32).contains(&i).then_some(i as usize)
+    }
+}
+
+/// Range of totals worth charting for all the tables
+fn shared_range(tables: &[&ProbabilityTableV2]) -> (i32, i32) {
+    let lo = tables.iter().map(|t| t.statistics.min).min().unwrap_or(0);
+    let hi = tables.iter()
+        .map(|t| {
+            t.at_least.iter()
+                .rposition(|&p| p >= TAIL_CUTOFF)
+                .map_or(t.statistics.max, |i| t.min_tn + i as i32)
+        })
+        .max()
+        .unwrap_or(lo);
+    (lo, hi.max(lo))
+}
+
+/// Rows of vertical bars for column values, scaled to `max`
+fn bar_rows(values: &[f64], max: f64, max_label: &str) -> Vec<String> {
+    let eighths: Vec<usize> = values.iter()
+        .map(|&v| ((v / max).clamp(0.0, 1.0) * (CHART_HEIGHT * 8) as f64).round() as usize)
+        .collect();
+
+    (0..CHART_HEIGHT)
+        .map(|row| {
+            let floor = (CHART_HEIGHT - 1 - row) * 8;
+            let label = match row {
+                0 => format!("{:>5} ", max_label),
+                r if r == CHART_HEIGHT - 1 => format!("{:>5} ", "0%"),
+                _ => " ".repeat(GUTTER - 1),
+            };
+            let bars: String = eighths.iter().map(|&e| BLOCKS[e.saturating_sub(floor).min(8)]).collect();
+            format!("{}│{}", label, bars)
+        })
+        .collect()
+}
+
+/// Axis, markers (▲ TN, ◆ median, ━ interquartile range) and x labels
+fn axis_rows(columns: &Columns, table: &ProbabilityTableV2, tn: Option<i32>) -> Vec<String> {
+    let statistics = &table.statistics;
+    let tn_column = tn.and_then(|tn| columns.column_of(tn));
+    let median_column = columns.column_of(statistics.median);
+
+    let markers: String = (0..columns.count)
+        .map(|i| {
+            let totals = columns.totals(i);
+            if Some(i) == tn_column {
+                '▲'
+            } else if Some(i) == median_column {
+                '◆'
+            } else if totals.start <= statistics.percentile_75 && totals.end > statistics.percentile_25 {
+                '━'
+            } else {
+                ' '
+            }
+        })
+        .collect();
+
+    let lo = columns.lo.to_string();
+    let hi = (columns.lo + columns.count as i32 * columns.bin - 1).to_string();
+    let padding = columns.count.saturating_sub(lo.len() + hi.len());
+    vec![
+        format!("{}└{}", " ".repeat(GUTTER - 1), "─".repeat(columns.count)),
+        format!("{}{}", " ".repeat(GUTTER), markers),
+        format!("{}{}{}{}", " ".repeat(GUTTER), lo, " ".repeat(padding), hi),
+    ]
+}
+
+/// One config's text block: summary, histogram of totals and success curve
+fn panel(label: &str, table: &ProbabilityTableV2, tn: Option<i32>, columns: &Columns) -> Vec<String> {
+    let statistics = &table.statistics;
+    let mut lines = vec![
+        label.to_string(),
+        format!("mean {:.1}  sd {:.1}", statistics.mean, statistics.stddev),
+        format!("median {}  IQR {}–{}", statistics.median, statistics.percentile_25, statistics.percentile_75),
+    ];
+    if let Some(tn) = tn {
+        lines.push(format!("P(≥{}) {:.1}%", tn, table.probability_at_least(tn) * 100.0));
+    }
+    lines.push(String::new());
+
+    // Histogram: P(total in column)
+    let pmf: Vec<f64> = (0..columns.count)
+        .map(|i| columns.totals(i).map(|total| table.probability_exactly(total)).sum())
+        .collect();
+    let max = pmf.iter().copied().fold(f64::EPSILON, f64::max);
+    lines.push("Totals".to_string());
+    lines.extend(bar_rows(&pmf, max, &format!("{:.0}%", max * 100.0)));
+    lines.extend(axis_rows(columns, table, tn));
+    lines.push(String::new());
+
+    // Success curve: P(total ≥ first TN of the column)
+    let at_least: Vec<f64> = (0..columns.count)
+        .map(|i| table.probability_at_least(columns.totals(i).start))
+        .collect();
+    lines.push("P(≥ TN)".to_string());
+    lines.extend(bar_rows(&at_least, 1.0, "100%"));
+    lines.extend(axis_rows(columns, table, tn));
+    lines
+}
+
+/// Render one config, or several side by side on a shared x axis
+///
+/// `width` is the chart width of each panel; wider ranges are grouped into
+/// several totals per column.
+pub fn render_show(configs: &[(String, &ProbabilityTableV2)], tn: Option<i32>, width: usize) -> String {
+    let tables: Vec<&ProbabilityTableV2> = configs.iter().map(|(_, t)| *t).collect();
+    let (lo, hi) = shared_range(&tables);
+    let columns = Columns::new(lo, hi, width.max(1));
+
+    let panels: Vec<Vec<String>> = configs.iter()
+        .map(|(label, table)| panel(label, table, tn, &columns))
+        .collect();
+    let panel_width = GUTTER + columns.count;
+    let rows = panels.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for row in 0..rows {
+        let line: Vec<String> = panels.iter()
+            .map(|p| {
+                let text = p.get(row).map(String::as_str).unwrap_or("");
+                format!("{}{}", text, " ".repeat(panel_width.saturating_sub(text.chars().count())))
+            })
+            .collect();
+        out.push_str(line.join(&" ".repeat(GAP)).trim_end());
+        out.push('\n');
+    }
+
+    let mut legend = String::from("▲ TN  ◆ median  ━ interquartile range");
+    if columns.bin > 1 {
+        legend.push_str(&format!("  ({} totals per column)", columns.bin));
+    }
+    out.push_str(&legend);
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::fixture_tables_v2;
+    use crate::schema::MAX_PRECISION;
+    use crate::{Engine, RollConfig};
+
+    fn tables() -> ProbabilityTablesV2 {
+        let configs = [
+            RollConfig::new(2, 1, ExplosionMode::Unskilled, false).unwrap(),
+            RollConfig::new(5, 3, ExplosionMode::Skilled, false).unwrap(),
+            RollConfig::new(5, 3, ExplosionMode::Mastery, true).unwrap(),
+        ];
+        fixture_tables_v2(&configs, Engine::Exact, MAX_PRECISION)
+    }
+
+    #[test]
+    fn test_query_parsing() {
+        let query: ShowQuery = "7k4:mastery+e".parse().unwrap();
+        assert_eq!(query, ShowQuery { pool: Pool { roll: 7, keep: 4 }, mode: ExplosionMode::Mastery, emphasis: true });
+        assert_eq!(query.to_string(), "7k4 mastery + emphasis");
+
+        let query: ShowQuery = "5k3".parse().unwrap();
+        assert_eq!((query.mode, query.emphasis), (ExplosionMode::Skilled, false));
+        assert!("5k3:wild".parse::<ShowQuery>().is_err());
+        assert!("5x3".parse::<ShowQuery>().is_err());
+    }
+
+    #[test]
+    fn test_single_config() {
+        let tables = tables();
+        let query: ShowQuery = "2k1:unskilled".parse().unwrap();
+        let table = query.find(&tables, &[]).unwrap();
+        let out = render_show(&[(query.to_string(), table)], Some(6), DEFAULT_WIDTH);
+
+        // 2k1 unskilled: totals 1-10, one column each
+        assert!(out.starts_with("2k1 unskilled\n"));
+        assert!(out.contains("P(≥6) 75.0%"));
+        assert!(out.contains(&format!("{}1       10\n", " ".repeat(GUTTER))));
+        let markers = out.lines().find(|l| l.contains('▲')).unwrap();
+        assert_eq!(markers.chars().position(|c| c == '▲'), Some(GUTTER + 5));
+        assert_eq!(markers.chars().position(|c| c == '◆'), Some(GUTTER + 7));
+        assert!(markers.contains('━'));
+        assert!(!out.contains("totals per column"));
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let tables = tables();
+        let queries: Vec<ShowQuery> = ["5k3", "5k3:mastery+e"].iter().map(|q| q.parse().unwrap()).collect();
+        let configs: Vec<(String, &ProbabilityTableV2)> = queries.iter()
+            .map(|q| (q.to_string(), q.find(&tables, &[]).unwrap()))
+            .collect();
+        let out = render_show(&configs, Some(25), 30);
+
+        let first = out.lines().next().unwrap();
+        assert!(first.starts_with("5k3 skilled ") && first.ends_with("5k3 mastery + emphasis"));
+        // Both panels have their own P(≥25) on the same line
+        assert_eq!(out.lines().filter(|l| l.contains("P(≥25)")).count(), 1);
+        assert_eq!(out.matches("P(≥25)").count(), 2);
+        assert!(out.contains("totals per column"));
+
+        // Every chart row fits both panels
+        let panel_width = GUTTER + 30;
+        assert!(out.lines().all(|l| l.chars().count() <= 2 * panel_width + GAP));
+        let marker_rows: Vec<&str> = out.lines().filter(|l| l.contains('◆') && !l.starts_with('▲')).collect();
+        assert_eq!(marker_rows.len(), 2);
+        assert!(marker_rows.iter().all(|l| l.matches('▲').count() == 2));
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+
+use crate::config::{Engine, ExplosionRule, RollConfig};
+use crate::error::ValidationError;
+use crate::pipeline::Stage;
+use crate::rules::RuleSet;
+
+/// Validation results for every configuration of a generation run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub generated_at: String,
+    pub engine: Engine,
+    pub rule_set: RuleSet,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub configs: Vec<ConfigValidation>,
+}
+
+/// Validation result for one configuration
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigValidation {
+    pub roll: u8,
+    pub keep: u8,
+    pub explosion: ExplosionRule,
+    pub emphasis: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipeline: Vec<Stage>,
+    /// Every failed check; empty when the configuration passed
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn new(engine: Engine, rule_set: RuleSet) -> Self {
+        Self {
+            generated_at: Utc::now().to_rfc3339(),
+            engine,
+            rule_set,
+            total: 0,
+            passed: 0,
+            failed: 0,
+            configs: Vec::new(),
+        }
+    }
+
+    /// Record the checks for one configuration
+    pub fn record(&mut self, config: &RollConfig, errors: Vec<ValidationError>) {
+        self.total += 1;
+        if errors.is_empty() {
+            self.passed += 1;
+        } else {
+            self.failed += 1;
+        }
+
+        self.configs.push(ConfigValidation {
+            roll: config.roll,
+            keep: config.keep,
+            explosion: config.explosion,
+            emphasis: config.emphasis,
+            pipeline: config.pipeline.stages.clone(),
+            errors,
+        });
+    }
+
+    /// Configurations with at least one error
+    pub fn failures(&self) -> impl Iterator<Item = &ConfigValidation> {
+        self.configs.iter().filter(|c| !c.errors.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExplosionMode;
+
+    #[test]
+    fn test_report_collects_every_failure() {
+        let mut report = ValidationReport::new(Engine::Simulation, RuleSet::default());
+        let config = RollConfig::new(3, 2, ExplosionMode::Skilled, false).unwrap();
+
+        report.record(&config, Vec::new());
+        report.record(&config, vec![
+            ValidationError::CountMismatch { expected: 10, actual: 9 },
+            ValidationError::NonMonotonic { tn: 12, probability: 0.5, previous: 0.4 },
+        ]);
+
+        assert_eq!((report.total, report.passed, report.failed), (2, 1, 1));
+        assert_eq!(report.failures().count(), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        let errors = &json["configs"][1]["errors"];
+        assert_eq!(errors[0]["kind"], "count_mismatch");
+        assert_eq!(errors[0]["expected"], 10);
+        assert_eq!(errors[1]["kind"], "non_monotonic");
+        assert_eq!(errors[1]["tn"], 12);
+    }
+}